@@ -33,6 +33,11 @@ fn bench_fft(b: &mut Bencher, len: usize) {
 #[bench] fn complex_p2_16777216(b: &mut Bencher) { bench_fft(b, 16777216); }
 
 
+// Powers of 3
+#[bench] fn complex_p3_00081(b: &mut Bencher) { bench_fft(b,    81); }
+#[bench] fn complex_p3_02187(b: &mut Bencher) { bench_fft(b,  2187); }
+#[bench] fn complex_p3_59049(b: &mut Bencher) { bench_fft(b, 59049); }
+
 // Powers of 7
 #[bench] fn complex_p7_00343(b: &mut Bencher) { bench_fft(b,   343); }
 #[bench] fn complex_p7_02401(b: &mut Bencher) { bench_fft(b,  2401); }
@@ -63,6 +68,25 @@ fn bench_fft(b: &mut Bencher, len: usize) {
 // small mixed composites times a large prime
 #[bench] fn complex_composite_30270(b: &mut Bencher) { bench_fft(b,  30270); }
 
+// products of the butterfly sizes, which the planner computes entirely with butterflies
+#[bench] fn complex_composite_00210(b: &mut Bencher) { bench_fft(b,    210); } // 2 * 3 * 5 * 7
+#[bench] fn complex_composite_01260(b: &mut Bencher) { bench_fft(b,   1260); } // 4 * 9 * 5 * 7
+
+
+/// Times just the FFT execution (not allocation and pre-calculation)
+/// for a given length, using the naive DFT, as a baseline for the planned FFTs
+fn bench_dft(b: &mut Bencher, len: usize) {
+
+    let fft = DFT::new(len, false);
+
+    let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; len];
+    let mut spectrum = signal.clone();
+    b.iter(|| {fft.process(&mut signal, &mut spectrum);} );
+}
+
+#[bench] fn dft_00210(b: &mut Bencher) { bench_dft(b,  210); }
+#[bench] fn dft_01260(b: &mut Bencher) { bench_dft(b, 1260); }
+
 
 /// Times just the FFT execution (not allocation and pre-calculation)
 /// for a given length, specific to the Good-Thomas algorithm
@@ -72,7 +96,7 @@ fn bench_good_thomas(b: &mut Bencher, width: usize, height: usize) {
     let width_fft = planner.plan_fft(width);
     let height_fft = planner.plan_fft(height);
 
-    let fft : Arc<FFT<_>> = Arc::new(GoodThomasAlgorithm::new(width_fft, height_fft));
+    let fft : Arc<dyn FFT<_>> = Arc::new(GoodThomasAlgorithm::new(width_fft, height_fft));
 
     let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
     let mut spectrum = signal.clone();
@@ -88,6 +112,29 @@ fn bench_good_thomas(b: &mut Bencher, width: usize, height: usize) {
 #[bench] fn good_thomas_2048_3(b: &mut Bencher) { bench_good_thomas(b,  2048, 3); }
 #[bench] fn good_thomas_2048_2187(b: &mut Bencher) { bench_good_thomas(b,  2048, 2187); }
 
+/// Times a Radix4 FFT that reorders its input with the recursive digit reversal, against one that uses the blocked
+/// digit reversal, to tune `DEFAULT_BLOCKED_REORDER_THRESHOLD`
+fn bench_radix4_reorder(b: &mut Bencher, len: usize, blocked: bool) {
+
+    let mut fft = Radix4::new(len, false);
+    fft.set_blocked_reorder_threshold(if blocked { 0 } else { usize::MAX });
+
+    let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; len];
+    let mut spectrum = signal.clone();
+    b.iter(|| {fft.process(&mut signal, &mut spectrum);} );
+}
+
+#[bench] fn radix4_recursive_reorder_0016384(b: &mut Bencher) { bench_radix4_reorder(b,   16384, false); }
+#[bench] fn radix4_blocked_reorder_0016384(b: &mut Bencher) { bench_radix4_reorder(b,   16384, true); }
+#[bench] fn radix4_recursive_reorder_0065536(b: &mut Bencher) { bench_radix4_reorder(b,   65536, false); }
+#[bench] fn radix4_blocked_reorder_0065536(b: &mut Bencher) { bench_radix4_reorder(b,   65536, true); }
+#[bench] fn radix4_recursive_reorder_0262144(b: &mut Bencher) { bench_radix4_reorder(b,  262144, false); }
+#[bench] fn radix4_blocked_reorder_0262144(b: &mut Bencher) { bench_radix4_reorder(b,  262144, true); }
+#[bench] fn radix4_recursive_reorder_1048576(b: &mut Bencher) { bench_radix4_reorder(b, 1048576, false); }
+#[bench] fn radix4_blocked_reorder_1048576(b: &mut Bencher) { bench_radix4_reorder(b, 1048576, true); }
+#[bench] fn radix4_recursive_reorder_4194304(b: &mut Bencher) { bench_radix4_reorder(b, 4194304, false); }
+#[bench] fn radix4_blocked_reorder_4194304(b: &mut Bencher) { bench_radix4_reorder(b, 4194304, true); }
+
 /// Times just the FFT execution (not allocation and pre-calculation)
 /// for a given length, specific to the Mixed-Radix algorithm
 fn bench_mixed_radix(b: &mut Bencher, width: usize, height: usize) {
@@ -96,7 +143,7 @@ fn bench_mixed_radix(b: &mut Bencher, width: usize, height: usize) {
     let width_fft = planner.plan_fft(width);
     let height_fft = planner.plan_fft(height);
 
-    let fft : Arc<FFT<_>> = Arc::new(MixedRadix::new(width_fft, height_fft));
+    let fft : Arc<dyn FFT<_>> = Arc::new(MixedRadix::new(width_fft, height_fft));
 
     let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
     let mut spectrum = signal.clone();
@@ -112,9 +159,61 @@ fn bench_mixed_radix(b: &mut Bencher, width: usize, height: usize) {
 #[bench] fn mixed_radix_2048_3(b: &mut Bencher) { bench_mixed_radix(b,  2048, 3); }
 #[bench] fn mixed_radix_2048_2187(b: &mut Bencher) { bench_mixed_radix(b,  2048, 2187); }
 
+/// Times MixedRadix in a hot loop, where it borrows its transpose scratch from the input buffer, against the same loop
+/// with a freshly allocated scratch buffer of the full size per call, which is the allocation that it avoids
+fn bench_mixed_radix_scratch(b: &mut Bencher, width: usize, height: usize, allocate: bool) {
+
+    let mut planner = rustfft::FFTplanner::new(false);
+    let fft = MixedRadix::new(planner.plan_fft(width), planner.plan_fft(height));
+
+    let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
+    let mut spectrum = signal.clone();
+    if allocate {
+        b.iter(|| {
+            let mut scratch = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
+            fft.process(&mut signal, &mut scratch);
+            spectrum.copy_from_slice(&scratch);
+        });
+    } else {
+        b.iter(|| {fft.process(&mut signal, &mut spectrum);} );
+    }
+}
+
+#[bench] fn mixed_radix_borrowed_scratch_0030_40(b: &mut Bencher) { bench_mixed_radix_scratch(b,   30,   40, false); }
+#[bench] fn mixed_radix_allocated_scratch_0030_40(b: &mut Bencher) { bench_mixed_radix_scratch(b,   30,   40, true); }
+#[bench] fn mixed_radix_borrowed_scratch_0144_144(b: &mut Bencher) { bench_mixed_radix_scratch(b,  144,  144, false); }
+#[bench] fn mixed_radix_allocated_scratch_0144_144(b: &mut Bencher) { bench_mixed_radix_scratch(b,  144,  144, true); }
+#[bench] fn mixed_radix_borrowed_scratch_1024_1024(b: &mut Bencher) { bench_mixed_radix_scratch(b, 1024, 1024, false); }
+#[bench] fn mixed_radix_allocated_scratch_1024_1024(b: &mut Bencher) { bench_mixed_radix_scratch(b, 1024, 1024, true); }
 
+/// Times a single large Mixed-Radix FFT with its inner FFTs split between threads, against the same FFT on one thread.
+/// Requires the `parallel` feature: `cargo +nightly bench --features "bench parallel"`
+#[cfg(feature = "parallel")]
+fn bench_mixed_radix_parallel(b: &mut Bencher, width: usize, height: usize, parallel: bool) {
 
-fn plan_butterfly(len: usize) -> Arc<FFTButterfly<f32>> {
+    let mut planner = rustfft::FFTplanner::new(false);
+    let width_fft = planner.plan_fft(width);
+    let height_fft = planner.plan_fft(height);
+
+    let fft = MixedRadix::new(width_fft, height_fft);
+
+    let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
+    let mut spectrum = signal.clone();
+    if parallel {
+        b.iter(|| {fft.process_parallel(&mut signal, &mut spectrum);} );
+    } else {
+        b.iter(|| {fft.process(&mut signal, &mut spectrum);} );
+    }
+}
+
+#[cfg(feature = "parallel")] #[bench] fn mixed_radix_serial_2048_2048(b: &mut Bencher) { bench_mixed_radix_parallel(b, 2048, 2048, false); }
+#[cfg(feature = "parallel")] #[bench] fn mixed_radix_parallel_2048_2048(b: &mut Bencher) { bench_mixed_radix_parallel(b, 2048, 2048, true); }
+#[cfg(feature = "parallel")] #[bench] fn mixed_radix_serial_4096_4096(b: &mut Bencher) { bench_mixed_radix_parallel(b, 4096, 4096, false); }
+#[cfg(feature = "parallel")] #[bench] fn mixed_radix_parallel_4096_4096(b: &mut Bencher) { bench_mixed_radix_parallel(b, 4096, 4096, true); }
+
+
+
+fn plan_butterfly(len: usize) -> Arc<dyn FFTButterfly<f32>> {
         match len {
             2 => Arc::new(Butterfly2::new(false)),
             3 => Arc::new(Butterfly3::new(false)),
@@ -123,6 +222,7 @@ fn plan_butterfly(len: usize) -> Arc<FFTButterfly<f32>> {
             6 => Arc::new(Butterfly6::new(false)),
             7 => Arc::new(Butterfly7::new(false)),
             8 => Arc::new(Butterfly8::new(false)),
+            10 => Arc::new(Butterfly10::new(false)),
             16 => Arc::new(Butterfly16::new(false)),
             32 => Arc::new(Butterfly32::new(false)),
             _ => panic!("Invalid butterfly size: {}", len),
@@ -136,7 +236,7 @@ fn bench_mixed_radix_butterfly(b: &mut Bencher, width: usize, height: usize) {
     let width_fft = plan_butterfly(width);
     let height_fft = plan_butterfly(height);
 
-    let fft : Arc<FFT<_>> = Arc::new(MixedRadixDoubleButterfly::new(width_fft, height_fft));
+    let fft : Arc<dyn FFT<_>> = Arc::new(MixedRadixDoubleButterfly::new(width_fft, height_fft));
 
     let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
     let mut spectrum = signal.clone();
@@ -155,7 +255,7 @@ fn bench_good_thomas_butterfly(b: &mut Bencher, width: usize, height: usize) {
     let width_fft = plan_butterfly(width);
     let height_fft = plan_butterfly(height);
 
-    let fft : Arc<FFT<_>> = Arc::new(GoodThomasAlgorithmDoubleButterfly::new(width_fft, height_fft));
+    let fft : Arc<dyn FFT<_>> = Arc::new(GoodThomasAlgorithmDoubleButterfly::new(width_fft, height_fft));
 
     let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; width * height];
     let mut spectrum = signal.clone();
@@ -165,4 +265,55 @@ fn bench_good_thomas_butterfly(b: &mut Bencher, width: usize, height: usize) {
 #[bench] fn good_thomas_butterfly_0002_3(b: &mut Bencher) { bench_good_thomas_butterfly(b,  2, 3); }
 #[bench] fn good_thomas_butterfly_0003_4(b: &mut Bencher) { bench_good_thomas_butterfly(b,  3, 4); }
 #[bench] fn good_thomas_butterfly_0004_5(b: &mut Bencher) { bench_good_thomas_butterfly(b,  4, 5); }
-#[bench] fn good_thomas_butterfly_0007_32(b: &mut Bencher) { bench_good_thomas_butterfly(b, 7, 32); }
\ No newline at end of file
+#[bench] fn good_thomas_butterfly_0007_32(b: &mut Bencher) { bench_good_thomas_butterfly(b, 7, 32); }
+/// Times many tiny butterflies in one buffer, computed by `process_multi_inplace`, against a loop that calls
+/// `process_inplace` on each chunk
+fn bench_butterfly_multi<B: FFTButterfly<f32>>(b: &mut Bencher, butterfly: B, num_chunks: usize, per_chunk: bool) {
+
+    let mut buffer = vec![Complex{re: 0_f32, im: 0_f32}; butterfly.len() * num_chunks];
+    if per_chunk {
+        b.iter(|| {
+            for chunk in buffer.chunks_mut(butterfly.len()) {
+                unsafe { butterfly.process_inplace(chunk) };
+            }
+        });
+    } else {
+        b.iter(|| unsafe { butterfly.process_multi_inplace(&mut buffer) });
+    }
+}
+
+#[bench] fn butterfly2_per_chunk_1000000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly2::new(false), 1000000, true); }
+#[bench] fn butterfly2_multi_1000000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly2::new(false), 1000000, false); }
+#[bench] fn butterfly4_per_chunk_1000000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly4::new(false), 1000000, true); }
+#[bench] fn butterfly4_multi_1000000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly4::new(false), 1000000, false); }
+#[bench] fn butterfly4_per_chunk_1000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly4::new(false), 1000, true); }
+#[bench] fn butterfly4_multi_1000(b: &mut Bencher) { bench_butterfly_multi(b, Butterfly4::new(false), 1000, false); }
+
+/// Times a PrunedFFT that computes `band_len` bins of a FFT of size `len`, for comparison against a full Radix4 FFT of
+/// the same size
+fn bench_pruned(b: &mut Bencher, len: usize, band_len: usize) {
+
+    let fft = PrunedFFT::new(len, 0..band_len, false);
+
+    let signal = vec![Complex{re: 0_f32, im: 0_f32}; len];
+    let mut spectrum = vec![Complex{re: 0_f32, im: 0_f32}; band_len];
+    let mut scratch = vec![Complex{re: 0_f32, im: 0_f32}; fft.required_scratch_len()];
+    b.iter(|| {fft.process_with_scratch(&signal, &mut spectrum, &mut scratch);} );
+}
+
+fn bench_radix4(b: &mut Bencher, len: usize) {
+
+    let fft = Radix4::new(len, false);
+
+    let mut signal = vec![Complex{re: 0_f32, im: 0_f32}; len];
+    let mut spectrum = signal.clone();
+    b.iter(|| {fft.process(&mut signal, &mut spectrum);} );
+}
+
+#[bench] fn pruned_0004096_16(b: &mut Bencher) { bench_pruned(b,    4096,    16); }
+#[bench] fn pruned_0004096_4096(b: &mut Bencher) { bench_pruned(b,    4096,  4096); }
+#[bench] fn radix4_0004096(b: &mut Bencher) { bench_radix4(b,    4096); }
+#[bench] fn pruned_0065536_16(b: &mut Bencher) { bench_pruned(b,   65536,    16); }
+#[bench] fn pruned_0065536_1024(b: &mut Bencher) { bench_pruned(b,   65536,  1024); }
+#[bench] fn pruned_0065536_65536(b: &mut Bencher) { bench_pruned(b,   65536, 65536); }
+#[bench] fn radix4_0065536(b: &mut Bencher) { bench_radix4(b,   65536); }
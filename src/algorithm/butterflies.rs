@@ -1,10 +1,12 @@
+use std::sync::Arc;
+
 use num_complex::Complex;
 use num_traits::{FromPrimitive, Zero};
 
 use common::{FFTnum, verify_length, verify_length_divisible};
 
 use twiddles;
-use ::{Length, IsInverse, FFT};
+use ::{Length, IsInverse, FFT, Direction};
 
 
 pub trait FFTButterfly<T: FFTnum>: Length + IsInverse + Sync + Send {
@@ -19,6 +21,14 @@ pub trait FFTButterfly<T: FFTnum>: Length + IsInverse + Sync + Send {
     /// # Safety
     /// This method performs unsafe reads/writes on `buffer`. Make sure `buffer.len()` is a multiple of `self.len()`
     unsafe fn process_multi_inplace(&self, buffer: &mut [Complex<T>]);
+
+    /// Returns a butterfly of the same size that computes the opposite direction of this one
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>>;
+
+    /// Returns the name of this butterfly, like `Butterfly8`. This is what `FFT::describe` reports for butterflies.
+    fn describe(&self) -> String {
+        format!("Butterfly{}", self.len())
+    }
 }
 
 
@@ -38,9 +48,10 @@ pub struct Butterfly2 {
 }
 impl Butterfly2 {
     #[inline(always)]
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         Butterfly2 {
-            inverse: inverse,
+            inverse,
         }
     }
 
@@ -51,6 +62,13 @@ impl Butterfly2 {
         *right = *left - *right;
         *left = temp;
     }
+
+    /// Computes a FFT of size 2 in place in `buffer`, with no length check, dynamic dispatch, or allocation. See
+    /// `Butterfly4::perform_fft`
+    #[inline(always)]
+    pub fn perform_fft<T: FFTnum>(&self, buffer: &mut [Complex<T>; 2]) {
+        unsafe { self.process_inplace(buffer) }
+    }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly2 {
     #[inline(always)]
@@ -62,17 +80,32 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly2 {
     }
     #[inline(always)]
     unsafe fn process_multi_inplace(&self, buffer: &mut [Complex<T>]) {
-    	for chunk in buffer.chunks_mut(self.len()) {
-    		self.process_inplace(chunk);
-    	}
+        // transform four chunks per iteration. the inner loop has a constant trip count, so it's fully unrolled, and
+        // the independent arithmetic of the four chunks can be interleaved
+        let mut blocks = buffer.chunks_exact_mut(8);
+        for block in &mut blocks {
+            for chunk in block.chunks_exact_mut(2) {
+                self.process_inplace(chunk);
+            }
+        }
+        for chunk in blocks.into_remainder().chunks_exact_mut(2) {
+            self.process_inplace(chunk);
+        }
+    }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly2::new(!self.inverse))
     }
 }
 impl<T: FFTnum> FFT<T> for Butterfly2 {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -80,6 +113,17 @@ impl<T: FFTnum> FFT<T> for Butterfly2 {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly2::new(!self.inverse))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl Length for Butterfly2 {
     #[inline(always)]
@@ -102,10 +146,11 @@ pub struct Butterfly3<T> {
 }
 impl<T: FFTnum> Butterfly3<T> {
 	#[inline(always)]
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         Butterfly3 {
             twiddle: twiddles::single_twiddle(1, 3, inverse),
-            inverse: inverse,
+            inverse,
         }
     }
 
@@ -116,6 +161,13 @@ impl<T: FFTnum> Butterfly3<T> {
             inverse: !fft.inverse,
         }
     }
+
+    /// Computes a FFT of size 3 in place in `buffer`, with no length check, dynamic dispatch, or allocation. See
+    /// `Butterfly4::perform_fft`
+    #[inline(always)]
+    pub fn perform_fft(&self, buffer: &mut [Complex<T>; 3]) {
+        unsafe { self.process_inplace(buffer) }
+    }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly3<T> {
     #[inline(always)]
@@ -138,13 +190,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly3<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly3::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly3<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -152,6 +211,17 @@ impl<T: FFTnum> FFT<T> for Butterfly3<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly3::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly3<T> {
     #[inline(always)]
@@ -176,8 +246,30 @@ pub struct Butterfly4 {
 impl Butterfly4
 {
     #[inline(always)]
-    pub fn new(inverse: bool) -> Self {
-        Butterfly4 { inverse:inverse }
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        Butterfly4 { inverse }
+    }
+
+    /// Computes a FFT of size 4 in place in `buffer`
+    ///
+    /// This is the kernel that `process_inplace` and `FFT::process` run, called directly. The length is part of the
+    /// buffer's type, so there's no length check, no dynamic dispatch, and no allocation, and the call is inlined into
+    /// the caller. To transform a slice of length 4, convert it with `<&mut [_; 4]>::try_from(slice)` first.
+    ///
+    /// ~~~
+    /// use rustfft::algorithm::butterflies::Butterfly4;
+    /// use rustfft::num_complex::Complex;
+    ///
+    /// let mut buffer = [Complex::new(1f32, 0f32), Complex::new(0f32, 0f32), Complex::new(0f32, 0f32), Complex::new(0f32, 0f32)];
+    /// Butterfly4::new(false).perform_fft(&mut buffer);
+    ///
+    /// // an impulse has a flat spectrum
+    /// assert_eq!(buffer, [Complex::new(1f32, 0f32); 4]);
+    /// ~~~
+    #[inline(always)]
+    pub fn perform_fft<T: FFTnum>(&self, buffer: &mut [Complex<T>; 4]) {
+        unsafe { self.process_inplace(buffer) }
     }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly4 {
@@ -215,13 +307,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly4 {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly4::new(!self.inverse))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly4 {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -229,6 +328,17 @@ impl<T: FFTnum> FFT<T> for Butterfly4 {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly4::new(!self.inverse))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl Length for Butterfly4 {
     #[inline(always)]
@@ -252,7 +362,8 @@ pub struct Butterfly5<T> {
 	inverse: bool,
 }
 impl<T: FFTnum> Butterfly5<T> {
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
 
     	//we're going to hardcode a raders algorithm of size 5 and an inner FFT of size 4
     	let quarter: T = FromPrimitive::from_f32(0.25f32).unwrap();
@@ -262,14 +373,33 @@ impl<T: FFTnum> Butterfly5<T> {
     	//our primitive root will be 2, and our inverse will be 3. the powers of 3 mod 5 are 1.3.4.2, so we hardcode to use the twiddles in that order
     	let mut fft_data = [twiddle1, twiddle2.conj(), twiddle1.conj(), twiddle2];
 
+    	// the precomputed data must be transformed in the same direction as the first inner FFT in process_inplace.
+    	// rader's algorithm needs the inner FFTs to be a forward/inverse pair in either order, so the direction of
+    	// the first inner FFT (and of this one) follows `inverse`, and the second inner FFT is always the opposite
     	let butterfly = Butterfly4::new(inverse);
     	unsafe { butterfly.process_inplace(&mut fft_data) };
 
         Butterfly5 { 
         	inner_fft_multiply: fft_data,
-        	inverse: inverse,
+        	inverse,
         }
     }
+
+    #[inline(always)]
+    pub fn inverse_of(fft: &Butterfly5<T>) -> Self {
+        let data = &fft.inner_fft_multiply;
+        Butterfly5 {
+            inner_fft_multiply: [data[0].conj(), data[1].conj(), data[2].conj(), data[3].conj()],
+            inverse: !fft.inverse,
+        }
+    }
+
+    /// Computes a FFT of size 5 in place in `buffer`, with no length check, dynamic dispatch, or allocation. See
+    /// `Butterfly4::perform_fft`
+    #[inline(always)]
+    pub fn perform_fft(&self, buffer: &mut [Complex<T>; 5]) {
+        unsafe { self.process_inplace(buffer) }
+    }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly5<T> {
     #[inline(always)]
@@ -278,15 +408,15 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly5<T> {
         //our primitive root is 2. the powers of 2 mod 5 are 1, 2,4,3 so use that ordering
         let mut scratch = [*buffer.get_unchecked(1), *buffer.get_unchecked(2), *buffer.get_unchecked(4), *buffer.get_unchecked(3)];
 
-        //perform the first inner FFT
+        //perform the first inner FFT. this must be in the same direction as the FFT used to precompute inner_fft_multiply
         Butterfly4::new(self.inverse).process_inplace(&mut scratch);
 
         //multiply the fft result with our precomputed data
-        for i in 0..4 {
-            scratch[i] = scratch[i] * self.inner_fft_multiply[i];
+        for (scratch_element, &multiple) in scratch.iter_mut().zip(self.inner_fft_multiply.iter()) {
+            *scratch_element = *scratch_element * multiple;
         }
 
-        //perform the second inner FFT
+        //perform the second inner FFT. this must be in the opposite direction of the first one, to complete the convolution
         Butterfly4::new(!self.inverse).process_inplace(&mut scratch);
 
         //the first element of the output is the sum of the rest
@@ -309,13 +439,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly5<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly5::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly5<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -323,6 +460,17 @@ impl<T: FFTnum> FFT<T> for Butterfly5<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly5::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly5<T> {
     #[inline(always)]
@@ -345,12 +493,21 @@ pub struct Butterfly6<T> {
 }
 impl<T: FFTnum> Butterfly6<T> {
 
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+
+        let inverse = direction.into().is_inverse();
         Butterfly6 { butterfly3: Butterfly3::new(inverse) }
     }
     pub fn inverse_of(fft: &Butterfly6<T>) -> Self {
         Butterfly6 { butterfly3: Butterfly3::inverse_of(&fft.butterfly3) }
     }
+
+    /// Computes a FFT of size 6 in place in `buffer`, with no length check, dynamic dispatch, or allocation. See
+    /// `Butterfly4::perform_fft`
+    #[inline(always)]
+    pub fn perform_fft(&self, buffer: &mut [Complex<T>; 6]) {
+        unsafe { self.process_inplace(buffer) }
+    }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly6<T> {
     #[inline(always)]
@@ -400,13 +557,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly6<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly6::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly6<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -414,6 +578,17 @@ impl<T: FFTnum> FFT<T> for Butterfly6<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly6::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly6<T> {
     #[inline(always)]
@@ -434,7 +609,8 @@ pub struct Butterfly7<T> {
     inner_fft_multiply: [Complex<T>; 6]
 }
 impl<T: FFTnum> Butterfly7<T> {
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
 
         //we're going to hardcode a raders algorithm of size 5 and an inner FFT of size 4
         let sixth: T = FromPrimitive::from_f64(1f64/6f64).unwrap();
@@ -453,6 +629,21 @@ impl<T: FFTnum> Butterfly7<T> {
             inner_fft_multiply: fft_data,
         }
     }
+
+    pub fn inverse_of(fft: &Butterfly7<T>) -> Self {
+        let data = &fft.inner_fft_multiply;
+        Butterfly7 {
+            inner_fft: Butterfly6::inverse_of(&fft.inner_fft),
+            inner_fft_multiply: [data[0].conj(), data[1].conj(), data[2].conj(), data[3].conj(), data[4].conj(), data[5].conj()],
+        }
+    }
+
+    /// Computes a FFT of size 7 in place in `buffer`, with no length check, dynamic dispatch, or allocation. See
+    /// `Butterfly4::perform_fft`
+    #[inline(always)]
+    pub fn perform_fft(&self, buffer: &mut [Complex<T>; 7]) {
+        unsafe { self.process_inplace(buffer) }
+    }
 }
 impl<T: FFTnum> FFTButterfly<T> for Butterfly7<T> {
     #[inline(always)]
@@ -472,8 +663,8 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly7<T> {
         self.inner_fft.process_inplace(&mut scratch);
 
         //multiply the fft result with our precomputed data
-        for i in 0..6 {
-            scratch[i] = scratch[i] * self.inner_fft_multiply[i];
+        for (scratch_element, &multiple) in scratch.iter_mut().zip(self.inner_fft_multiply.iter()) {
+            *scratch_element = *scratch_element * multiple;
         }
 
         //perform the second inner FFT
@@ -502,13 +693,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly7<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly7::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly7<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -516,6 +714,17 @@ impl<T: FFTnum> FFT<T> for Butterfly7<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly7::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly7<T> {
     #[inline(always)]
@@ -539,13 +748,22 @@ pub struct Butterfly8<T> {
 impl<T: FFTnum> Butterfly8<T>
 {
     #[inline(always)]
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         Butterfly8 {
-            inverse: inverse,
+            inverse,
             twiddle: twiddles::single_twiddle(1, 8, inverse)
         }
     }
 
+    #[inline(always)]
+    pub fn inverse_of(fft: &Butterfly8<T>) -> Self {
+        Butterfly8 {
+            inverse: !fft.inverse,
+            twiddle: fft.twiddle.conj(),
+        }
+    }
+
     #[inline(always)]
     unsafe fn transpose_4x2_to_2x4(buffer: &mut [Complex<T>; 8]) {
         let temp1 = buffer[1];
@@ -617,13 +835,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly8<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly8::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly8<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -631,6 +856,17 @@ impl<T: FFTnum> FFT<T> for Butterfly8<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly8::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly8<T> {
     #[inline(always)]
@@ -647,6 +883,336 @@ impl<T> IsInverse for Butterfly8<T> {
 
 
 
+pub struct Butterfly10<T> {
+	butterfly5: Butterfly5<T>,
+}
+impl<T: FFTnum> Butterfly10<T> {
+
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+
+        let inverse = direction.into().is_inverse();
+        Butterfly10 { butterfly5: Butterfly5::new(inverse) }
+    }
+    pub fn inverse_of(fft: &Butterfly10<T>) -> Self {
+        Butterfly10 { butterfly5: Butterfly5::inverse_of(&fft.butterfly5) }
+    }
+}
+impl<T: FFTnum> FFTButterfly<T> for Butterfly10<T> {
+    #[inline(always)]
+    unsafe fn process_inplace(&self, buffer: &mut [Complex<T>]) {
+        //since GCD(2,5) == 1 we're going to hardcode a step of the Good-Thomas algorithm to avoid twiddle factors,
+        //exactly like Butterfly6 does for 2 * 3
+
+        // step 1: reorder the input directly into the scratch, using a precomputed ordering
+        let mut scratch_a = [
+            *buffer.get_unchecked(0),
+            *buffer.get_unchecked(2),
+            *buffer.get_unchecked(4),
+            *buffer.get_unchecked(6),
+            *buffer.get_unchecked(8),
+        ];
+
+        let mut scratch_b = [
+            *buffer.get_unchecked(5),
+            *buffer.get_unchecked(7),
+            *buffer.get_unchecked(9),
+            *buffer.get_unchecked(1),
+            *buffer.get_unchecked(3),
+        ];
+
+        // step 2: column FFTs
+        self.butterfly5.process_inplace(&mut scratch_a);
+        self.butterfly5.process_inplace(&mut scratch_b);
+
+        // step 3: apply twiddle factors -- SKIPPED because good-thomas doesn't have twiddle factors :)
+
+        // step 4: SKIPPED because the next FFTs will be non-contiguous
+
+        // step 5: row FFTs
+        Butterfly2::perform_fft_direct(&mut scratch_a[0], &mut scratch_b[0]);
+        Butterfly2::perform_fft_direct(&mut scratch_a[1], &mut scratch_b[1]);
+        Butterfly2::perform_fft_direct(&mut scratch_a[2], &mut scratch_b[2]);
+        Butterfly2::perform_fft_direct(&mut scratch_a[3], &mut scratch_b[3]);
+        Butterfly2::perform_fft_direct(&mut scratch_a[4], &mut scratch_b[4]);
+
+        // step 6: reorder the result back into the buffer, using a precomputed ordering that includes the transpose
+        *buffer.get_unchecked_mut(0) = scratch_a[0];
+        *buffer.get_unchecked_mut(5) = scratch_b[0];
+        *buffer.get_unchecked_mut(6) = scratch_a[1];
+        *buffer.get_unchecked_mut(1) = scratch_b[1];
+        *buffer.get_unchecked_mut(2) = scratch_a[2];
+        *buffer.get_unchecked_mut(7) = scratch_b[2];
+        *buffer.get_unchecked_mut(8) = scratch_a[3];
+        *buffer.get_unchecked_mut(3) = scratch_b[3];
+        *buffer.get_unchecked_mut(4) = scratch_a[4];
+        *buffer.get_unchecked_mut(9) = scratch_b[4];
+    }
+    #[inline(always)]
+    unsafe fn process_multi_inplace(&self, buffer: &mut [Complex<T>]) {
+        for chunk in buffer.chunks_mut(self.len()) {
+            self.process_inplace(chunk);
+        }
+    }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly10::inverse_of(self))
+    }
+}
+impl<T: FFTnum> FFT<T> for Butterfly10<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        output.copy_from_slice(input);
+
+        self.process_inplace(output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length_divisible(input, output, self.len());
+        output.copy_from_slice(input);
+
+        unsafe { self.process_multi_inplace(output) };
+    }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly10::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
+}
+impl<T> Length for Butterfly10<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        10
+    }
+}
+impl<T> IsInverse for Butterfly10<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.butterfly5.is_inverse()
+    }
+}
+
+
+
+pub struct Butterfly14<T> {
+	butterfly7: Butterfly7<T>,
+}
+impl<T: FFTnum> Butterfly14<T> {
+    // Good-Thomas index maps for 2 rows of 7: the input map reads row r, column c from (7r + 2c) % 14, and the output
+    // map writes it to the index that's congruent to c mod 7 and r mod 2
+    const INPUT_ORDER: [usize; 14] = [0, 2, 4, 6, 8, 10, 12, 7, 9, 11, 13, 1, 3, 5];
+    const OUTPUT_ORDER: [usize; 14] = [0, 8, 2, 10, 4, 12, 6, 7, 1, 9, 3, 11, 5, 13];
+
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+
+        let inverse = direction.into().is_inverse();
+        Butterfly14 { butterfly7: Butterfly7::new(inverse) }
+    }
+    pub fn inverse_of(fft: &Butterfly14<T>) -> Self {
+        Butterfly14 { butterfly7: Butterfly7::inverse_of(&fft.butterfly7) }
+    }
+}
+impl<T: FFTnum> FFTButterfly<T> for Butterfly14<T> {
+    #[inline(always)]
+    unsafe fn process_inplace(&self, buffer: &mut [Complex<T>]) {
+        //since GCD(2,7) == 1 we're going to hardcode a step of the Good-Thomas algorithm to avoid twiddle factors,
+        //like Butterfly6 does for 2 * 3, but with the orderings stored in tables since they're longer
+
+        // step 1: reorder the input directly into the scratch, using the precomputed ordering
+        let mut scratch_a = [Zero::zero(); 7];
+        let mut scratch_b = [Zero::zero(); 7];
+        for i in 0..7 {
+            scratch_a[i] = *buffer.get_unchecked(Self::INPUT_ORDER[i]);
+            scratch_b[i] = *buffer.get_unchecked(Self::INPUT_ORDER[i + 7]);
+        }
+
+        // step 2: column FFTs
+        self.butterfly7.process_inplace(&mut scratch_a);
+        self.butterfly7.process_inplace(&mut scratch_b);
+
+        // step 3: apply twiddle factors -- SKIPPED because good-thomas doesn't have twiddle factors :)
+
+        // step 4: SKIPPED because the next FFTs will be non-contiguous
+
+        // step 5: row FFTs
+        for (a, b) in scratch_a.iter_mut().zip(scratch_b.iter_mut()) {
+            Butterfly2::perform_fft_direct(a, b);
+        }
+
+        // step 6: reorder the result back into the buffer, using the precomputed ordering that includes the transpose
+        for i in 0..7 {
+            *buffer.get_unchecked_mut(Self::OUTPUT_ORDER[i]) = scratch_a[i];
+            *buffer.get_unchecked_mut(Self::OUTPUT_ORDER[i + 7]) = scratch_b[i];
+        }
+    }
+    #[inline(always)]
+    unsafe fn process_multi_inplace(&self, buffer: &mut [Complex<T>]) {
+        for chunk in buffer.chunks_mut(self.len()) {
+            self.process_inplace(chunk);
+        }
+    }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly14::inverse_of(self))
+    }
+}
+impl<T: FFTnum> FFT<T> for Butterfly14<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        output.copy_from_slice(input);
+
+        self.process_inplace(output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length_divisible(input, output, self.len());
+        output.copy_from_slice(input);
+
+        unsafe { self.process_multi_inplace(output) };
+    }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly14::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
+}
+impl<T> Length for Butterfly14<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        14
+    }
+}
+impl<T> IsInverse for Butterfly14<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.butterfly7.is_inverse()
+    }
+}
+
+
+
+pub struct Butterfly15<T> {
+	butterfly3: Butterfly3<T>,
+	butterfly5: Butterfly5<T>,
+}
+impl<T: FFTnum> Butterfly15<T> {
+    // Good-Thomas index maps for 3 rows of 5: the input map reads row r, column c from (5r + 3c) % 15, and the output
+    // map writes it to the index that's congruent to c mod 5 and r mod 3
+    const INPUT_ORDER: [usize; 15] = [0, 3, 6, 9, 12, 5, 8, 11, 14, 2, 10, 13, 1, 4, 7];
+    const OUTPUT_ORDER: [usize; 15] = [0, 6, 12, 3, 9, 10, 1, 7, 13, 4, 5, 11, 2, 8, 14];
+
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+
+        let inverse = direction.into().is_inverse();
+        Butterfly15 {
+            butterfly3: Butterfly3::new(inverse),
+            butterfly5: Butterfly5::new(inverse),
+        }
+    }
+    pub fn inverse_of(fft: &Butterfly15<T>) -> Self {
+        Butterfly15 {
+            butterfly3: Butterfly3::inverse_of(&fft.butterfly3),
+            butterfly5: Butterfly5::inverse_of(&fft.butterfly5),
+        }
+    }
+}
+impl<T: FFTnum> FFTButterfly<T> for Butterfly15<T> {
+    #[inline(always)]
+    unsafe fn process_inplace(&self, buffer: &mut [Complex<T>]) {
+        //since GCD(3,5) == 1 we're going to hardcode a step of the Good-Thomas algorithm to avoid twiddle factors,
+        //like Butterfly14 does for 2 * 7
+
+        // step 1: reorder the input directly into the scratch, using the precomputed ordering
+        let mut scratch = [Zero::zero(); 15];
+        for (i, element) in scratch.iter_mut().enumerate() {
+            *element = *buffer.get_unchecked(Self::INPUT_ORDER[i]);
+        }
+
+        // step 2: column FFTs, one for each of the 3 rows of the scratch
+        self.butterfly5.process_multi_inplace(&mut scratch);
+
+        // step 3: apply twiddle factors -- SKIPPED because good-thomas doesn't have twiddle factors :)
+
+        // steps 4, 5, and 6: gather each column, compute its row FFT, and scatter it back into the buffer using the
+        // precomputed ordering that includes the transpose
+        for column in 0..5 {
+            let mut row = [scratch[column], scratch[column + 5], scratch[column + 10]];
+            self.butterfly3.process_inplace(&mut row);
+
+            *buffer.get_unchecked_mut(Self::OUTPUT_ORDER[column]) = row[0];
+            *buffer.get_unchecked_mut(Self::OUTPUT_ORDER[column + 5]) = row[1];
+            *buffer.get_unchecked_mut(Self::OUTPUT_ORDER[column + 10]) = row[2];
+        }
+    }
+    #[inline(always)]
+    unsafe fn process_multi_inplace(&self, buffer: &mut [Complex<T>]) {
+        for chunk in buffer.chunks_mut(self.len()) {
+            self.process_inplace(chunk);
+        }
+    }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly15::inverse_of(self))
+    }
+}
+impl<T: FFTnum> FFT<T> for Butterfly15<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        output.copy_from_slice(input);
+
+        self.process_inplace(output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length_divisible(input, output, self.len());
+        output.copy_from_slice(input);
+
+        unsafe { self.process_multi_inplace(output) };
+    }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly15::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
+}
+impl<T> Length for Butterfly15<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        15
+    }
+}
+impl<T> IsInverse for Butterfly15<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.butterfly3.is_inverse()
+    }
+}
+
+
+
 pub struct Butterfly16<T> {
     butterfly8: Butterfly8<T>,
     twiddle1: Complex<T>,
@@ -657,13 +1223,25 @@ pub struct Butterfly16<T> {
 impl<T: FFTnum> Butterfly16<T>
 {
     #[inline(always)]
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         Butterfly16 {
             butterfly8: Butterfly8::new(inverse),
             twiddle1: twiddles::single_twiddle(1, 16, inverse),
             twiddle2: twiddles::single_twiddle(2, 16, inverse),
             twiddle3: twiddles::single_twiddle(3, 16, inverse),
-            inverse: inverse,
+            inverse,
+        }
+    }
+
+    #[inline(always)]
+    pub fn inverse_of(fft: &Butterfly16<T>) -> Self {
+        Butterfly16 {
+            butterfly8: Butterfly8::inverse_of(&fft.butterfly8),
+            twiddle1: fft.twiddle1.conj(),
+            twiddle2: fft.twiddle2.conj(),
+            twiddle3: fft.twiddle3.conj(),
+            inverse: !fft.inverse,
         }
     }
 }
@@ -750,13 +1328,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly16<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly16::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly16<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -764,6 +1349,17 @@ impl<T: FFTnum> FFT<T> for Butterfly16<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly16::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly16<T> {
     #[inline(always)]
@@ -789,7 +1385,8 @@ pub struct Butterfly32<T> {
 impl<T: FFTnum> Butterfly32<T>
 {
     #[inline(always)]
-    pub fn new(inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         Butterfly32 {
             butterfly16: Butterfly16::new(inverse),
             butterfly8: Butterfly8::new(inverse),
@@ -802,7 +1399,22 @@ impl<T: FFTnum> Butterfly32<T>
                 twiddles::single_twiddle(6, 32, inverse),
                 twiddles::single_twiddle(7, 32, inverse),
             ],
-            inverse: inverse,
+            inverse,
+        }
+    }
+
+    #[inline(always)]
+    pub fn inverse_of(fft: &Butterfly32<T>) -> Self {
+        let mut twiddles = fft.twiddles;
+        for twiddle in twiddles.iter_mut() {
+            *twiddle = twiddle.conj();
+        }
+
+        Butterfly32 {
+            butterfly16: Butterfly16::inverse_of(&fft.butterfly16),
+            butterfly8: Butterfly8::inverse_of(&fft.butterfly8),
+            twiddles,
+            inverse: !fft.inverse,
         }
     }
 }
@@ -939,13 +1551,20 @@ impl<T: FFTnum> FFTButterfly<T> for Butterfly32<T> {
             self.process_inplace(chunk);
         }
     }
+    fn inverse_butterfly(&self) -> Arc<dyn FFTButterfly<T>> {
+        Arc::new(Butterfly32::inverse_of(self))
+    }
 }
 impl<T: FFTnum> FFT<T> for Butterfly32<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         output.copy_from_slice(input);
 
-        unsafe { self.process_inplace(output) };
+        self.process_inplace(output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -953,6 +1572,17 @@ impl<T: FFTnum> FFT<T> for Butterfly32<T> {
 
         unsafe { self.process_multi_inplace(output) };
     }
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        unsafe { self.process_multi_inplace(buffer) };
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Butterfly32::inverse_of(self))
+    }
+    fn describe(&self) -> String {
+        FFTButterfly::<T>::describe(self)
+    }
 }
 impl<T> Length for Butterfly32<T> {
     #[inline(always)]
@@ -991,6 +1621,11 @@ mod unit_tests {
 
                 check_fft_algorithm(&butterfly_inverse, $size, true);
                 check_butterfly(&butterfly_inverse, $size, true);
+
+                check_fft_algorithm(&*butterfly.inverse_of(), $size, true);
+                check_butterfly(&*butterfly.inverse_butterfly(), $size, true);
+                check_fft_algorithm(&*butterfly_inverse.inverse_of(), $size, false);
+                check_butterfly(&*butterfly_inverse.inverse_butterfly(), $size, false);
             }
         )
     }
@@ -1001,11 +1636,89 @@ mod unit_tests {
     test_butterfly_func!(test_butterfly6, Butterfly6, 6);
     test_butterfly_func!(test_butterfly7, Butterfly7, 7);
     test_butterfly_func!(test_butterfly8, Butterfly8, 8);
+    test_butterfly_func!(test_butterfly10, Butterfly10, 10);
+    test_butterfly_func!(test_butterfly14, Butterfly14, 14);
+    test_butterfly_func!(test_butterfly15, Butterfly15, 15);
     test_butterfly_func!(test_butterfly16, Butterfly16, 16);
     test_butterfly_func!(test_butterfly32, Butterfly32, 32);
     
 
-    fn check_butterfly(butterfly: &FFTButterfly<f32>, size: usize, inverse: bool) {
+    // Butterfly5's inner FFTs must run in opposite directions, so a sign slip would make one direction wrong while
+    // the other stays right. compare every element in both directions, rather than the average error
+    #[test]
+    fn test_butterfly5_directions() {
+        for &inverse in &[false, true] {
+            let butterfly = Butterfly5::new(inverse);
+            let dft = DFT::new(5, inverse);
+
+            let mut expected_input = random_signal(5);
+            let mut actual_input = expected_input.clone();
+            let mut expected_output = vec![Zero::zero(); 5];
+            let mut actual_output = vec![Zero::zero(); 5];
+
+            dft.process(&mut expected_input, &mut expected_output);
+            butterfly.process(&mut actual_input, &mut actual_output);
+
+            for (expected, actual) in expected_output.iter().zip(actual_output.iter()) {
+                assert!((expected - actual).norm() < 1e-4, "inverse = {}: expected {}, got {}", inverse, expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn test_perform_fft() {
+        use std::convert::TryInto;
+
+        fn check<const N: usize>(perform_fft: &dyn Fn(&mut [Complex<f32>; N]), inverse: bool) {
+            let signal = random_signal(N);
+            let mut expected = vec![Zero::zero(); N];
+            DFT::new(N, inverse).process(&mut signal.clone(), &mut expected);
+
+            let mut actual: [Complex<f32>; N] = signal[..].try_into().unwrap();
+            perform_fft(&mut actual);
+            assert!(compare_vectors(&expected, &actual), "len = {}, inverse = {}", N, inverse);
+        }
+
+        for &inverse in &[false, true] {
+            let butterfly2 = Butterfly2::new(inverse);
+            let butterfly3 = Butterfly3::new(inverse);
+            let butterfly4 = Butterfly4::new(inverse);
+            let butterfly5 = Butterfly5::new(inverse);
+            let butterfly6 = Butterfly6::new(inverse);
+            let butterfly7 = Butterfly7::new(inverse);
+            check(&|buffer| butterfly2.perform_fft(buffer), inverse);
+            check(&|buffer| butterfly3.perform_fft(buffer), inverse);
+            check(&|buffer| butterfly4.perform_fft(buffer), inverse);
+            check(&|buffer| butterfly5.perform_fft(buffer), inverse);
+            check(&|buffer| butterfly6.perform_fft(buffer), inverse);
+            check(&|buffer| butterfly7.perform_fft(buffer), inverse);
+        }
+    }
+
+    #[test]
+    fn test_butterfly_multi_matches_single() {
+        // 1000 chunks, plus a partial block of chunks at the end that the unrolled loops process one at a time
+        let butterflies: Vec<Arc<dyn FFTButterfly<f32>>> = vec![
+            Arc::new(Butterfly2::new(false)), Arc::new(Butterfly2::new(true)),
+            Arc::new(Butterfly4::new(false)), Arc::new(Butterfly4::new(true)),
+        ];
+        for butterfly in &butterflies {
+            for &num_chunks in &[1000, 1003] {
+                let signal = random_signal(butterfly.len() * num_chunks);
+
+                let mut expected = signal.clone();
+                for chunk in expected.chunks_mut(butterfly.len()) {
+                    unsafe { butterfly.process_inplace(chunk) };
+                }
+
+                let mut actual = signal.clone();
+                unsafe { butterfly.process_multi_inplace(&mut actual) };
+                assert_eq!(expected, actual, "len = {}, inverse = {}, chunks = {}", butterfly.len(), butterfly.is_inverse(), num_chunks);
+            }
+        }
+    }
+
+    fn check_butterfly(butterfly: &dyn FFTButterfly<f32>, size: usize, inverse: bool) {
         assert_eq!(butterfly.len(), size, "Butterfly algorithm reported wrong size");
         assert_eq!(butterfly.is_inverse(), inverse, "Butterfly algorithm reported wrong inverse value");
 
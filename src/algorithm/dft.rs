@@ -1,9 +1,12 @@
+use std::sync::Arc;
+use std::mem;
+
 use num_complex::Complex;
 use num_traits::Zero;
 
-use common::{FFTnum, verify_length, verify_length_divisible};
+use common::{FFTnum, verify_length, verify_length_divisible, epsilon};
 
-use ::{Length, IsInverse, FFT};
+use ::{Length, IsInverse, FFT, Direction};
 use twiddles;
 
 /// Naive O(n^2 ) Discrete Fourier Transform implementation
@@ -26,24 +29,73 @@ use twiddles;
 /// dft.process(&mut input, &mut output);
 /// ~~~
 pub struct DFT<T> {
-    twiddles: Vec<Complex<T>>,
+    twiddles: Arc<[Complex<T>]>,
     inverse: bool,
 }
 
 impl<T: FFTnum> DFT<T> {
     /// Preallocates necessary arrays and precomputes necessary data to efficiently compute DFT
-    pub fn new(len: usize, inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        Self::with_twiddles(inverse, Self::generate_twiddles(len, inverse))
+    }
+
+    /// Creates a DFT instance that uses an existing twiddle factor table, instead of computing its own. The size of the
+    /// DFT is the length of the table.
+    ///
+    /// `twiddles` must be the table returned by `DFT::generate_twiddles(len, inverse)`, or by `twiddles()` on another
+    /// instance with the same size and direction. This lets several instances of the same size share one table.
+    pub fn with_twiddles<D: Into<Direction>>(direction: D, twiddles: Arc<[Complex<T>]>) -> Self {
+        let inverse = direction.into().is_inverse();
         DFT {
-            twiddles: twiddles::generate_twiddle_factors(len, inverse),
-            inverse: inverse
+            twiddles,
+            inverse
         }
     }
 
+    /// Computes the twiddle factor table used by a DFT of size `len`, so that it can be shared between instances with
+    /// `with_twiddles`
+    pub fn generate_twiddles<D: Into<Direction>>(len: usize, direction: D) -> Arc<[Complex<T>]> {
+        let inverse = direction.into().is_inverse();
+        twiddles::generate_twiddle_factors(len, inverse).into()
+    }
+
+    /// The twiddle factor table used by this instance
+    pub fn twiddles(&self) -> &Arc<[Complex<T>]> {
+        &self.twiddles
+    }
+
+    /// Computes a DFT like `process`, but splits the output bins between one thread per available CPU
+    ///
+    /// Each output bin only depends on the input, so the threads don't need to coordinate, and the result is exactly
+    /// the same as the result of `process`. Spawning threads has a cost, so this is only worthwhile for large sizes.
+    /// Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn process_parallel(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        use std::thread;
+
+        verify_length(input, output, self.len());
+
+        let num_threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let bins_per_thread = (self.len() / num_threads).max(1);
+
+        let signal: &[Complex<T>] = input;
+        thread::scope(|scope| {
+            for (chunk_index, spectrum_chunk) in output.chunks_mut(bins_per_thread).enumerate() {
+                scope.spawn(move || self.perform_fft_bins(signal, spectrum_chunk, chunk_index * bins_per_thread));
+            }
+        });
+    }
+
     #[inline(always)]
     fn perform_fft(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>]) {
-        for k in 0..spectrum.len() {
-            let output_cell = spectrum.get_mut(k).unwrap();
+        self.perform_fft_bins(signal, spectrum, 0);
+    }
 
+    // computes the output bins starting at `first_bin`, and stores them in `spectrum`
+    #[inline(always)]
+    fn perform_fft_bins(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>], first_bin: usize) {
+        for (k, output_cell) in (first_bin..).zip(spectrum.iter_mut()) {
             *output_cell = Zero::zero();
             let mut twiddle_index = 0;
 
@@ -64,6 +116,9 @@ impl<T: FFTnum> FFT<T> for DFT<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         self.perform_fft(input, output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
@@ -73,6 +128,23 @@ impl<T: FFTnum> FFT<T> for DFT<T> {
             self.perform_fft(in_chunk, out_chunk);
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        let twiddles: Vec<Complex<T>> = self.twiddles.iter().map(|t| t.conj()).collect();
+        Arc::new(DFT::with_twiddles(!self.inverse, twiddles.into()))
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&self.twiddles[..])
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        epsilon::<T>() * (self.len() as f64).sqrt().max(1f64)
+    }
+    fn describe(&self) -> String {
+        format!("DFT({})", self.len())
+    }
+    fn operation_count(&self) -> usize {
+        // every output is a sum of len products
+        self.len() * self.len()
+    }
 }
 impl<T> Length for DFT<T> {
     #[inline(always)]
@@ -90,19 +162,20 @@ impl<T> IsInverse for DFT<T> {
 #[cfg(test)]
 mod unit_tests {
     use super::*;
-    use std::f32;
-    use test_utils::{random_signal, compare_vectors};
+    use test_utils::{random_signal, compare_vectors, check_fft_algorithm};
     use num_complex::Complex;
     use num_traits::Zero;
 
+    /// Naive O(n^2) reference DFT, which computes each twiddle factor `exp(-2 * pi * i * j * k / n)` directly,
+    /// independently of the twiddle tables the DFT algorithm uses
     fn dft(signal: &[Complex<f32>], spectrum: &mut [Complex<f32>]) {
         for (k, spec_bin) in spectrum.iter_mut().enumerate() {
             let mut sum = Zero::zero();
-            for (i, &x) in signal.iter().enumerate() {
-                let angle = -1f32 * (i * k) as f32 * 2f32 * f32::consts::PI / signal.len() as f32;
+            for (j, &x) in signal.iter().enumerate() {
+                let angle = -((j * k) as f32) * 2f32 * ::std::f32::consts::PI / signal.len() as f32;
                 let twiddle = Complex::from_polar(&1f32, &angle);
 
-                sum = sum + twiddle * x;
+                sum += twiddle * x;
             }
             *spec_bin = sum;
         }
@@ -137,6 +210,8 @@ mod unit_tests {
 
             assert!(compare_vectors(&expected_output, &actual_output), "process() failed, length = {}", len);
             assert!(compare_vectors(&expected_output, &multi_output), "process_multi() failed, length = {}", len);
+
+            check_fft_algorithm(&*dft_instance.inverse_of(), len, true);
         }
 
         //verify that it doesn't crash if we have a length of 0
@@ -147,6 +222,49 @@ mod unit_tests {
         zero_dft.process(&mut zero_input, &mut zero_output);
     }
 
+    #[test]
+    fn test_dft_with_twiddles() {
+        for len in 0..20 {
+            for &inverse in &[false, true] {
+                let dft_instance: DFT<f32> = DFT::new(len, inverse);
+                let shared_dft = DFT::with_twiddles(inverse, Arc::clone(dft_instance.twiddles()));
+                let generated_dft = DFT::with_twiddles(inverse, DFT::generate_twiddles(len, inverse));
+
+                assert!(Arc::ptr_eq(dft_instance.twiddles(), shared_dft.twiddles()));
+                assert_eq!(shared_dft.len(), len);
+
+                // all three instances have identical twiddles, so the output should be bit-for-bit identical
+                let signal = random_signal(len);
+                let mut expected = vec![Zero::zero(); len];
+                dft_instance.process(&mut signal.clone(), &mut expected);
+                for other_dft in &[shared_dft, generated_dft] {
+                    let mut actual = vec![Zero::zero(); len];
+                    other_dft.process(&mut signal.clone(), &mut actual);
+                    assert_eq!(expected, actual, "len = {}, inverse = {}", len, inverse);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_process_parallel() {
+        for &len in &[0, 1, 2, 7, 64, 97, 500] {
+            for &inverse in &[false, true] {
+                let dft_instance = DFT::new(len, inverse);
+                let signal = random_signal(len);
+
+                let mut serial_output = vec![Zero::zero(); len];
+                let mut parallel_output = vec![Zero::zero(); len];
+                dft_instance.process(&mut signal.clone(), &mut serial_output);
+                dft_instance.process_parallel(&mut signal.clone(), &mut parallel_output);
+
+                // each bin is computed with exactly the same operations, so the results should be bit-for-bit identical
+                assert_eq!(serial_output, parallel_output, "len = {}, inverse = {}", len, inverse);
+            }
+        }
+    }
+
     /// Returns true if our `dft` function calculates the given spectrum from the
     /// given signal, and if rustfft's DFT struct does the same
     fn test_dft_correct(signal: &[Complex<f32>], spectrum: &[Complex<f32>]) -> bool {
@@ -163,7 +281,7 @@ mod unit_tests {
         let dft_instance = DFT::new(signal.len(), false);
         dft_instance.process(&mut actual_signal, &mut actual_spectrum);
 
-        return compare_vectors(spectrum, &expected_spectrum) && compare_vectors(spectrum, &actual_spectrum);
+        compare_vectors(spectrum, &expected_spectrum) && compare_vectors(spectrum, &actual_spectrum)
     }
 
     #[test]
@@ -1,7 +1,10 @@
 use std::sync::Arc;
+use std::mem;
+use std::cmp;
 
 use num_complex::Complex;
-use common::{FFTnum, verify_length, verify_length_divisible};
+use num_traits::Zero;
+use common::{FFTnum, verify_length, verify_length_divisible, radix2_operation_count};
 
 use math_utils;
 use array_utils;
@@ -39,10 +42,10 @@ use algorithm::butterflies::FFTButterfly;
 /// ~~~
 pub struct GoodThomasAlgorithm<T> {
     width: usize,
-    width_size_fft: Arc<FFT<T>>,
+    width_size_fft: Arc<dyn FFT<T>>,
 
     height: usize,
-    height_size_fft: Arc<FFT<T>>,
+    height_size_fft: Arc<dyn FFT<T>>,
 
     input_output_map: Box<[usize]>,
 
@@ -53,7 +56,7 @@ impl<T: FFTnum> GoodThomasAlgorithm<T> {
     /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`
     ///
     /// GCD(width_fft.len(), height_fft.len()) must be equal to 1
-    pub fn new(width_fft: Arc<FFT<T>>, height_fft: Arc<FFT<T>>) -> Self {
+    pub fn new(width_fft: Arc<dyn FFT<T>>, height_fft: Arc<dyn FFT<T>>) -> Self {
         assert_eq!(
             width_fft.is_inverse(), height_fft.is_inverse(), 
             "width_fft and height_fft must both be inverse, or neither. got width inverse={}, height inverse={}",
@@ -93,17 +96,17 @@ impl<T: FFTnum> GoodThomasAlgorithm<T> {
         GoodThomasAlgorithm {
             inverse: width_fft.is_inverse(),
 
-            width: width,
+            width,
             width_size_fft: width_fft,
 
-            height: height,
+            height,
             height_size_fft: height_fft,
             
             input_output_map: input_output_map.into_boxed_slice(),
         }
     }
 
-    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
         let (input_map, output_map) = self.input_output_map.split_at(self.len());
 
         // copy the input into the output buffer
@@ -112,13 +115,13 @@ impl<T: FFTnum> GoodThomasAlgorithm<T> {
         }
 
         // run FFTs of size `width`
-        self.width_size_fft.process_multi(output, input);
+        self.width_size_fft.process_multi_with_scratch(output, input, scratch);
 
         // transpose
         array_utils::transpose(self.width, self.height, input, output);
 
         // run FFTs of size 'height'
-        self.height_size_fft.process_multi(output, input);
+        self.height_size_fft.process_multi_with_scratch(output, input, scratch);
 
         // copy to the output, using our output redordeing mapping
         for (input_element, &output_index) in input.iter().zip(output_map.iter()) {
@@ -131,15 +134,55 @@ impl<T: FFTnum> FFT<T> for GoodThomasAlgorithm<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
-        self.perform_fft(input, output);
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.perform_fft(input, output, &mut scratch);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.process_multi_with_scratch(input, output, &mut scratch);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        // the inner FFTs run one after the other, so they can share their extra scratch
+        cmp::max(self.width_size_fft.extra_scratch_len(), self.height_size_fft.extra_scratch_len())
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
+        let scratch_len = self.extra_scratch_len();
+        assert!(scratch.len() >= scratch_len, "Scratch is the wrong length. Expected at least {}, got {}", scratch_len, scratch.len());
 
         for (in_chunk, out_chunk) in input.chunks_mut(self.len()).zip(output.chunks_mut(self.len())) {
-            self.perform_fft(in_chunk, out_chunk);
+            self.perform_fft(in_chunk, out_chunk, scratch);
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(GoodThomasAlgorithm {
+            width: self.width,
+            width_size_fft: self.width_size_fft.inverse_of(),
+
+            height: self.height,
+            height_size_fft: self.height_size_fft.inverse_of(),
+
+            input_output_map: self.input_output_map.clone(),
+
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.input_output_map) + self.width_size_fft.memory_footprint() + self.height_size_fft.memory_footprint()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        self.width_size_fft.error_bound_estimate() + self.height_size_fft.error_bound_estimate()
+    }
+    fn describe(&self) -> String {
+        format!("GoodThomasAlgorithm({}, {})", self.width_size_fft.describe(), self.height_size_fft.describe())
+    }
+    fn operation_count(&self) -> usize {
+        // the same as mixed radix, but without the twiddle factors
+        self.height * self.width_size_fft.operation_count() + self.width * self.height_size_fft.operation_count()
+    }
 }
 impl<T> Length for GoodThomasAlgorithm<T> {
     #[inline(always)]
@@ -188,10 +231,10 @@ impl<T> IsInverse for GoodThomasAlgorithm<T> {
 /// ~~~
 pub struct GoodThomasAlgorithmDoubleButterfly<T> {
     width: usize,
-    width_size_fft: Arc<FFTButterfly<T>>,
+    width_size_fft: Arc<dyn FFTButterfly<T>>,
 
     height: usize,
-    height_size_fft: Arc<FFTButterfly<T>>,
+    height_size_fft: Arc<dyn FFTButterfly<T>>,
 
     input_output_map: Box<[usize]>,
 
@@ -202,7 +245,7 @@ impl<T: FFTnum> GoodThomasAlgorithmDoubleButterfly<T> {
     /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`
     ///
     /// GCD(n1.len(), n2.len()) must be equal to 1
-    pub fn new(width_fft: Arc<FFTButterfly<T>>, height_fft: Arc<FFTButterfly<T>>) -> Self {
+    pub fn new(width_fft: Arc<dyn FFTButterfly<T>>, height_fft: Arc<dyn FFTButterfly<T>>) -> Self {
         assert_eq!(
             width_fft.is_inverse(), height_fft.is_inverse(), 
             "n1_fft and height_fft must both be inverse, or neither. got width inverse={}, height inverse={}",
@@ -242,10 +285,10 @@ impl<T: FFTnum> GoodThomasAlgorithmDoubleButterfly<T> {
         GoodThomasAlgorithmDoubleButterfly {
             inverse: width_fft.is_inverse(),
 
-            width: width,
+            width,
             width_size_fft: width_fft,
 
-            height: height,
+            height,
             height_size_fft: height_fft,
             
             input_output_map: input_output_map.into_boxed_slice(),
@@ -281,7 +324,10 @@ impl<T: FFTnum> FFT<T> for GoodThomasAlgorithmDoubleButterfly<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
-        unsafe { self.perform_fft(input, output) };
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.perform_fft(input, output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -290,6 +336,28 @@ impl<T: FFTnum> FFT<T> for GoodThomasAlgorithmDoubleButterfly<T> {
              unsafe { self.perform_fft(in_chunk, out_chunk) };
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(GoodThomasAlgorithmDoubleButterfly {
+            width: self.width,
+            width_size_fft: self.width_size_fft.inverse_butterfly(),
+
+            height: self.height,
+            height_size_fft: self.height_size_fft.inverse_butterfly(),
+
+            input_output_map: self.input_output_map.clone(),
+
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.input_output_map)
+    }
+    fn describe(&self) -> String {
+        format!("GoodThomasAlgorithmDoubleButterfly({}, {})", self.width_size_fft.describe(), self.height_size_fft.describe())
+    }
+    fn operation_count(&self) -> usize {
+        self.height * radix2_operation_count(self.width) + self.width * radix2_operation_count(self.height)
+    }
 }
 impl<T> Length for GoodThomasAlgorithmDoubleButterfly<T> {
     #[inline(always)]
@@ -344,12 +412,13 @@ mod unit_tests {
     }
 
     fn test_good_thomas_with_lengths(width: usize, height: usize, inverse: bool) {
-        let width_fft = Arc::new(DFT::new(width, inverse)) as Arc<FFT<f32>>;
-        let height_fft = Arc::new(DFT::new(height, inverse)) as Arc<FFT<f32>>;
+        let width_fft = Arc::new(DFT::new(width, inverse)) as Arc<dyn FFT<f32>>;
+        let height_fft = Arc::new(DFT::new(height, inverse)) as Arc<dyn FFT<f32>>;
 
         let fft = GoodThomasAlgorithm::new(width_fft, height_fft);
 
         check_fft_algorithm(&fft, width * height, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), width * height, !inverse);
     }
 
     fn test_good_thomas_butterfly_with_lengths(width: usize, height: usize, inverse: bool) {
@@ -359,5 +428,6 @@ mod unit_tests {
         let fft = GoodThomasAlgorithmDoubleButterfly::new(width_fft, height_fft);
 
         check_fft_algorithm(&fft, width * height, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), width * height, !inverse);
     }
 }
@@ -1,19 +1,25 @@
 use std::sync::Arc;
+use std::mem;
 
 use num_complex::Complex;
+use num_traits::Zero;
 
-use common::{FFTnum, verify_length, verify_length_divisible};
+use common::{FFTnum, verify_length, verify_length_divisible, epsilon, radix2_operation_count};
 
 use ::{Length, IsInverse, FFT};
 use algorithm::butterflies::FFTButterfly;
+use algorithm::six_step::{six_step_twiddles, six_step_scratch_len, six_step_with_hook};
 use array_utils;
-use twiddles;
 
 /// Implementation of the Mixed-Radix FFT algorithm
 ///
 /// This algorithm factors a size n FFT into n1 * n2, computes several inner FFTs of size n1 and n2, then combines the 
 /// results to get the final answer
 ///
+/// All of the scratch space needed for the transposes between steps is borrowed from the `input` buffer, so processing
+/// never allocates, unless an inner FFT needs extra scratch space (see `FFT::extra_scratch_len`). Repeated calls to
+/// `process` in a hot loop don't cause any allocator churn.
+///
 /// ~~~
 /// // Computes a forward FFT of size 1200, using the Mixed-Radix Algorithm
 /// use rustfft::algorithm::MixedRadix;
@@ -34,13 +40,12 @@ use twiddles;
 /// let fft = MixedRadix::new(inner_fft_n1, inner_fft_n2);
 /// fft.process(&mut input, &mut output);
 /// ~~~
-
 pub struct MixedRadix<T> {
     width: usize,
-    width_size_fft: Arc<FFT<T>>,
+    width_size_fft: Arc<dyn FFT<T>>,
 
     height: usize,
-    height_size_fft: Arc<FFT<T>>,
+    height_size_fft: Arc<dyn FFT<T>>,
 
     twiddles: Box<[Complex<T>]>,
     inverse: bool,
@@ -48,7 +53,7 @@ pub struct MixedRadix<T> {
 
 impl<T: FFTnum> MixedRadix<T> {
     /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`
-    pub fn new(width_fft: Arc<FFT<T>>, height_fft: Arc<FFT<T>>) -> Self {
+    pub fn new(width_fft: Arc<dyn FFT<T>>, height_fft: Arc<dyn FFT<T>>) -> Self {
         assert_eq!(
             width_fft.is_inverse(), height_fft.is_inverse(), 
             "width_fft and height_fft must both be inverse, or neither. got width inverse={}, height inverse={}",
@@ -59,65 +64,156 @@ impl<T: FFTnum> MixedRadix<T> {
         let width = width_fft.len();
         let height = height_fft.len();
 
-        let len = width * height;
-
-        let mut twiddles = Vec::with_capacity(len);
-        for x in 0..width {
-            for y in 0..height {
-                twiddles.push(twiddles::single_twiddle(x * y, len, inverse));
-            }
-        }
+        let twiddles = six_step_twiddles(width, height, inverse);
 
         MixedRadix {
-            width: width,
+            width,
             width_size_fft: width_fft,
 
-            height: height,
+            height,
             height_size_fft: height_fft,
 
             twiddles: twiddles.into_boxed_slice(),
-            inverse: inverse,
+            inverse,
         }
     }
 
 
-    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
-        // SIX STEP FFT:
+    /// Computes an FFT like `process`, and pushes a snapshot of the intermediate buffer onto `trace` after each major
+    /// stage: the size-`height` FFTs, the twiddle factor multiplication, and the size-`width` FFTs, in that order.
+    ///
+    /// The snapshots are in the algorithm's internal (transposed) layout, so they're meant for comparing two versions
+    /// of the algorithm against each other, not for comparing against a spectrum. Requires the `trace` feature.
+    #[cfg(feature = "trace")]
+    pub fn process_with_trace(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], trace: &mut Vec<Vec<Complex<T>>>) {
+        verify_length(input, output, self.len());
+
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.perform_fft_with_hook(input, output, &mut scratch, |stage| trace.push(stage.to_vec()));
+    }
+
+    /// Computes an FFT like `process`, but splits the inner FFTs of each step between one thread per available CPU
+    ///
+    /// Each of the size-`height` FFTs and each of the size-`width` FFTs reads and writes its own row of the buffer, so
+    /// the rows are divided into one contiguous block per thread. The transposes between the steps run on the calling
+    /// thread. This speeds up a single large FFT, like a `MixedRadix` of two `Radix4(4096)` FFTs. To spread many
+    /// independent FFTs across threads instead, share one planned `Arc<dyn FFT<T>>` between the threads, like
+    /// `examples/concurrency.rs` does. Each step spawns its threads with `std::thread::scope`, so this is only
+    /// worthwhile for sizes around 2^20 and up, where the FFT takes much longer than spawning the threads. The result
+    /// is exactly the same as the result of `process`. Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn process_parallel(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+        if self.len() == 0 {
+            return;
+        }
+
+        let num_threads = ::std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
 
         // STEP 1: transpose
         array_utils::transpose(self.width, self.height, input, output);
 
-        // STEP 2: perform FFTs of size `height`
-        self.height_size_fft.process_multi(output, input);
-
-        // STEP 3: Apply twiddle factors
-        for (element, &twiddle) in input.iter_mut().zip(self.twiddles.iter()) {
-            *element = *element * twiddle;
-        }
+        // STEP 2 and 3: perform FFTs of size `height`, and apply twiddle factors to each block as soon as it's done
+        process_rows_parallel(&*self.height_size_fft, output, input, num_threads, |offset, block| {
+            for (element, &twiddle) in block.iter_mut().zip(self.twiddles[offset..].iter()) {
+                *element = *element * twiddle;
+            }
+        });
 
         // STEP 4: transpose again
         array_utils::transpose(self.height, self.width, input, output);
 
         // STEP 5: perform FFTs of size `width`
-        self.width_size_fft.process_multi(output, input);
+        process_rows_parallel(&*self.width_size_fft, output, input, num_threads, |_, _| {});
 
         // STEP 6: transpose again
         array_utils::transpose(self.width, self.height, input, output);
     }
+
+    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        // the hook does nothing, so it gets compiled away
+        self.perform_fft_with_hook(input, output, scratch, |_| {});
+    }
+
+    #[inline(always)]
+    fn perform_fft_with_hook<F: FnMut(&[Complex<T>])>(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>], stage_hook: F) {
+        six_step_with_hook(&*self.width_size_fft, &*self.height_size_fft, &self.twiddles, input, output, scratch, stage_hook);
+    }
 }
+
+// computes `fft.process_multi(input, output)` with the rows divided into one block per thread. once a thread has
+// computed its block, it calls `finish` with the block's offset in the buffer, and the block's output
+#[cfg(feature = "parallel")]
+fn process_rows_parallel<T, F>(fft: &dyn FFT<T>, input: &mut [Complex<T>], output: &mut [Complex<T>], num_threads: usize, finish: F)
+    where T: FFTnum, F: Fn(usize, &mut [Complex<T>]) + Sync
+{
+    let row_count = input.len() / fft.len();
+    let block_len = (row_count + num_threads - 1) / num_threads * fft.len();
+
+    let finish = &finish;
+    ::std::thread::scope(|scope| {
+        for (block_index, (input_block, output_block)) in input.chunks_mut(block_len).zip(output.chunks_mut(block_len)).enumerate() {
+            scope.spawn(move || {
+                // each thread needs its own extra scratch for the inner FFT, which is usually none
+                let mut scratch = vec![Zero::zero(); fft.extra_scratch_len()];
+                fft.process_multi_with_scratch(input_block, output_block, &mut scratch);
+                finish(block_index * block_len, output_block);
+            });
+        }
+    });
+}
+
 impl<T: FFTnum> FFT<T> for MixedRadix<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
-        self.perform_fft(input, output);
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.perform_fft(input, output, &mut scratch);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.process_multi_with_scratch(input, output, &mut scratch);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        six_step_scratch_len(&*self.width_size_fft, &*self.height_size_fft)
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
+        let scratch_len = self.extra_scratch_len();
+        assert!(scratch.len() >= scratch_len, "Scratch is the wrong length. Expected at least {}, got {}", scratch_len, scratch.len());
 
         for (in_chunk, out_chunk) in input.chunks_mut(self.len()).zip(output.chunks_mut(self.len())) {
-            self.perform_fft(in_chunk, out_chunk);
+            self.perform_fft(in_chunk, out_chunk, scratch);
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(MixedRadix {
+            width: self.width,
+            width_size_fft: self.width_size_fft.inverse_of(),
+
+            height: self.height,
+            height_size_fft: self.height_size_fft.inverse_of(),
+
+            twiddles: self.twiddles.iter().map(|t| t.conj()).collect::<Vec<_>>().into_boxed_slice(),
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.twiddles) + self.width_size_fft.memory_footprint() + self.height_size_fft.memory_footprint()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        self.width_size_fft.error_bound_estimate() + self.height_size_fft.error_bound_estimate() + epsilon::<T>()
+    }
+    fn describe(&self) -> String {
+        format!("MixedRadix({}, {})", self.width_size_fft.describe(), self.height_size_fft.describe())
+    }
+    fn operation_count(&self) -> usize {
+        // height FFTs of size width, width FFTs of size height, and a twiddle factor multiply for each element in between
+        self.height * self.width_size_fft.operation_count() + self.width * self.height_size_fft.operation_count() + self.len()
+    }
 }
 impl<T> Length for MixedRadix<T> {
     #[inline(always)]
@@ -163,10 +259,10 @@ impl<T> IsInverse for MixedRadix<T> {
 /// ~~~
 pub struct MixedRadixDoubleButterfly<T> {
     width: usize,
-    width_size_fft: Arc<FFTButterfly<T>>,
+    width_size_fft: Arc<dyn FFTButterfly<T>>,
 
     height: usize,
-    height_size_fft: Arc<FFTButterfly<T>>,
+    height_size_fft: Arc<dyn FFTButterfly<T>>,
 
     twiddles: Box<[Complex<T>]>,
     inverse: bool,
@@ -174,7 +270,7 @@ pub struct MixedRadixDoubleButterfly<T> {
 
 impl<T: FFTnum> MixedRadixDoubleButterfly<T> {
     /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`
-    pub fn new(width_fft: Arc<FFTButterfly<T>>, height_fft: Arc<FFTButterfly<T>>) -> Self {
+    pub fn new(width_fft: Arc<dyn FFTButterfly<T>>, height_fft: Arc<dyn FFTButterfly<T>>) -> Self {
         assert_eq!(
             width_fft.is_inverse(), height_fft.is_inverse(), 
             "width_fft and height_fft must both be inverse, or neither. got width inverse={}, height inverse={}",
@@ -185,24 +281,17 @@ impl<T: FFTnum> MixedRadixDoubleButterfly<T> {
         let width = width_fft.len();
         let height = height_fft.len();
 
-        let len = width * height;
-
-        let mut twiddles = Vec::with_capacity(len);
-        for x in 0..width {
-            for y in 0..height {
-                twiddles.push(twiddles::single_twiddle(x * y, len, inverse));
-            }
-        }
+        let twiddles = six_step_twiddles(width, height, inverse);
 
         MixedRadixDoubleButterfly {
-            width: width,
+            width,
             width_size_fft: width_fft,
 
-            height: height,
+            height,
             height_size_fft: height_fft,
 
             twiddles: twiddles.into_boxed_slice(),
-            inverse: inverse
+            inverse
         }
     }
 
@@ -236,7 +325,10 @@ impl<T: FFTnum> FFT<T> for MixedRadixDoubleButterfly<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
-        unsafe { self.perform_fft(input, output) };
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.perform_fft(input, output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
@@ -245,6 +337,27 @@ impl<T: FFTnum> FFT<T> for MixedRadixDoubleButterfly<T> {
             unsafe { self.perform_fft(in_chunk, out_chunk) };
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(MixedRadixDoubleButterfly {
+            width: self.width,
+            width_size_fft: self.width_size_fft.inverse_butterfly(),
+
+            height: self.height,
+            height_size_fft: self.height_size_fft.inverse_butterfly(),
+
+            twiddles: self.twiddles.iter().map(|t| t.conj()).collect::<Vec<_>>().into_boxed_slice(),
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.twiddles)
+    }
+    fn describe(&self) -> String {
+        format!("MixedRadixDoubleButterfly({}, {})", self.width_size_fft.describe(), self.height_size_fft.describe())
+    }
+    fn operation_count(&self) -> usize {
+        self.height * radix2_operation_count(self.width) + self.width * radix2_operation_count(self.height) + self.len()
+    }
 }
 impl<T> Length for MixedRadixDoubleButterfly<T> {
     #[inline(always)]
@@ -289,16 +402,84 @@ mod unit_tests {
         }
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_process_parallel() {
+        use num_traits::Zero;
+        use test_utils::random_signal;
+        use FFTplanner;
+
+        for &inverse in &[false, true] {
+            let mut planner = FFTplanner::new(inverse);
+            for &(width, height) in &[(1, 1), (3, 5), (64, 64), (1024, 1024), (1024, 3)] {
+                let fft = MixedRadix::new(planner.plan_fft(width), planner.plan_fft(height));
+                let len = width * height;
+                let signal = random_signal(len);
+
+                let mut serial_output = vec![Zero::zero(); len];
+                let mut parallel_output = vec![Zero::zero(); len];
+                fft.process(&mut signal.clone(), &mut serial_output);
+                fft.process_parallel(&mut signal.clone(), &mut parallel_output);
+
+                // every element goes through exactly the same operations, so the results should be bit-for-bit identical
+                assert_eq!(serial_output, parallel_output, "width = {}, height = {}, inverse = {}", width, height, inverse);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mixed_radix_rejects_mismatched_directions() {
+        let width_fft = Arc::new(DFT::new(4, false)) as Arc<dyn FFT<f32>>;
+        let height_fft = Arc::new(DFT::new(5, true)) as Arc<dyn FFT<f32>>;
 
+        MixedRadix::new(width_fft, height_fft);
+    }
 
+    #[test]
+    fn test_mixed_radix_memory_footprint() {
+        let width_fft = Arc::new(DFT::new(4, false)) as Arc<dyn FFT<f32>>;
+        let height_fft = Arc::new(DFT::new(5, false)) as Arc<dyn FFT<f32>>;
+
+        let fft = MixedRadix::new(width_fft, height_fft);
+
+        // 20 twiddles for the mixed radix step, plus 4 and 5 for the inner DFTs
+        assert_eq!(fft.memory_footprint(), (20 + 4 + 5) * mem::size_of::<Complex<f32>>());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_mixed_radix_trace() {
+        use num_traits::Zero;
+        use test_utils::{random_signal, compare_vectors};
+
+        let width_fft = Arc::new(DFT::new(2, false)) as Arc<dyn FFT<f32>>;
+        let height_fft = Arc::new(DFT::new(3, false)) as Arc<dyn FFT<f32>>;
+        let fft = MixedRadix::new(width_fft, height_fft);
+
+        let signal = random_signal(6);
+        let mut trace = Vec::new();
+        let mut traced_output = vec![Zero::zero(); 6];
+        fft.process_with_trace(&mut signal.clone(), &mut traced_output, &mut trace);
+
+        // one snapshot each for the column FFTs, the twiddle factors, and the row FFTs
+        assert_eq!(trace.len(), 3);
+        assert!(trace.iter().all(|stage| stage.len() == 6));
+
+        // tracing shouldn't change the result
+        let mut output = vec![Zero::zero(); 6];
+        fft.process(&mut signal.clone(), &mut output);
+        assert!(compare_vectors(&output, &traced_output));
+    }
 
     fn test_mixed_radix_with_lengths(width: usize, height: usize, inverse: bool) {
-        let width_fft = Arc::new(DFT::new(width, inverse)) as Arc<FFT<f32>>;
-        let height_fft = Arc::new(DFT::new(height, inverse)) as Arc<FFT<f32>>;
+        let width_fft = Arc::new(DFT::new(width, inverse)) as Arc<dyn FFT<f32>>;
+        let height_fft = Arc::new(DFT::new(height, inverse)) as Arc<dyn FFT<f32>>;
 
         let fft = MixedRadix::new(width_fft, height_fft);
 
         check_fft_algorithm(&fft, width * height, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), width * height, !inverse);
     }
 
     fn test_mixed_radix_butterfly_with_lengths(width: usize, height: usize, inverse: bool) {
@@ -308,5 +489,6 @@ mod unit_tests {
         let fft = MixedRadixDoubleButterfly::new(width_fft, height_fft);
 
         check_fft_algorithm(&fft, width * height, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), width * height, !inverse);
     }
 }
@@ -1,14 +1,73 @@
+//! The FFT algorithms that the planner builds its plans from
+//!
+//! The planner chooses between these automatically, but they can also be created directly, to benchmark them against
+//! each other or to compose a specific plan by hand. Apart from `PrunedFFT` and `Radix4DIF`, each algorithm implements
+//! the `FFT` trait:
+//!
+//! - `butterflies::Butterfly2` through `butterflies::Butterfly32`: hardcoded FFTs of sizes 2, 3, 4, 5, 6, 7, 8, 10,
+//!   14, 15, 16, and 32. These are the fastest way to compute their sizes, and they transform in place without scratch
+//!   space.
+//! - `Radix4`: any power of two. No inner FFTs to configure, and the fastest choice for powers of two.
+//! - `Radix3`: any power of three. The base-3 counterpart to `Radix4`, which avoids the transposes of splitting
+//!   powers of three with `MixedRadix`.
+//! - `MixedRadix`: any composite size `width * height`, computed with a FFT of size `width` and one of size `height`.
+//!   Multiplies by twiddle factors between the two passes. `MixedRadixDoubleButterfly` is the same algorithm with two
+//!   butterflies, which is faster for small sizes.
+//! - `GoodThomasAlgorithm`: sizes `width * height` where the two are coprime. Skips the twiddle factors of
+//!   `MixedRadix`, at the cost of a more expensive reordering. `GoodThomasAlgorithmDoubleButterfly` uses two
+//!   butterflies. `PrimeFactorAlgorithm` generalizes it to any number of pairwise coprime factors.
+//! - `RadersAlgorithm`: prime sizes, computed with a FFT of size `len - 1`. Fast when `len - 1` factors nicely.
+//! - `DFT`: any size, in O(n^2) time. Useful as a reference, and for very small sizes without a butterfly.
+//! - `PrunedFFT`: power-of-two sizes when only a contiguous range of the output is needed. Faster than a full FFT
+//!   when the range is narrow. Its output is shorter than its input, so it has its own `process` method instead of
+//!   implementing `FFT`.
+//! - `Radix4DIF`: any power of two, with natural-order input and bit-reversed output. Skips the reorder that `Radix4`
+//!   performs, for chaining with stages that accept bit-reversed input. Also has its own `process` method.
+//!
+//! The six-step algorithm inside `MixedRadix` is also available as the function `six_step`, which takes the inner FFTs
+//! and the twiddle factor table as parameters, for composing custom transforms.
+//!
+//! ```
+//! // Compute a size-1024 FFT with Radix4 directly, then a size-96 FFT composed from a butterfly and Radix4
+//! use std::sync::Arc;
+//! use rustfft::FFT;
+//! use rustfft::algorithm::{Radix4, MixedRadix};
+//! use rustfft::algorithm::butterflies::Butterfly3;
+//! use rustfft::num_complex::Complex;
+//! use rustfft::num_traits::Zero;
+//!
+//! let radix4 = Radix4::new(1024, false);
+//! let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 1024];
+//! let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 1024];
+//! radix4.process(&mut input, &mut output);
+//!
+//! let mixed_radix = MixedRadix::new(Arc::new(Butterfly3::new(false)), Arc::new(Radix4::new(32, false)));
+//! let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 96];
+//! let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 96];
+//! mixed_radix.process(&mut input, &mut output);
+//! ```
+
 mod good_thomas_algorithm;
+mod prime_factor_algorithm;
+mod pruned_fft;
 mod mixed_radix;
 mod raders_algorithm;
+mod radix3;
 mod radix4;
+mod radix4_dif;
 mod dft;
+mod six_step;
 
 /// Hardcoded size-specfic FFT algorithms
 pub mod butterflies;
 
 pub use self::mixed_radix::{MixedRadix, MixedRadixDoubleButterfly};
 pub use self::raders_algorithm::RadersAlgorithm;
-pub use self::radix4::Radix4;
+pub use self::radix3::Radix3;
+pub use self::radix4::{Radix4, DEFAULT_BLOCKED_REORDER_THRESHOLD};
+pub use self::radix4_dif::Radix4DIF;
 pub use self::good_thomas_algorithm::{GoodThomasAlgorithm, GoodThomasAlgorithmDoubleButterfly};
+pub use self::prime_factor_algorithm::PrimeFactorAlgorithm;
+pub use self::pruned_fft::PrunedFFT;
 pub use self::dft::DFT;
+pub use self::six_step::{six_step, six_step_twiddles};
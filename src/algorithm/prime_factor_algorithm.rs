@@ -0,0 +1,276 @@
+use std::sync::Arc;
+use std::mem;
+
+use num_complex::Complex;
+use num_integer::gcd;
+use num_traits::Zero;
+
+use common::{FFTnum, verify_length, verify_length_divisible};
+
+use math_utils;
+use array_utils;
+
+use ::{Length, IsInverse, FFT};
+
+/// Implementation of the [Prime-Factor Algorithm](https://en.wikipedia.org/wiki/Prime-factor_FFT_algorithm) for any
+/// number of factors
+///
+/// This algorithm factors a size n FFT into n1 * n2 * ... * nk, where each pair of factors is coprime. It's a
+/// generalization of the Good-Thomas Algorithm to more than two factors: the size n FFT is re-indexed using the Chinese
+/// Remainder Theorem into a k-dimensional FFT, which is computed by running the inner FFTs along each dimension in
+/// turn. Like Good-Thomas, no twiddle factors are needed between the inner FFTs.
+///
+/// ~~~
+/// // Computes a forward FFT of size 105, using the Prime-Factor Algorithm
+/// use rustfft::algorithm::PrimeFactorAlgorithm;
+/// use rustfft::{FFT, FFTplanner};
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 105];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 105];
+///
+/// // 105 = 3 * 5 * 7, and 3, 5, and 7 are pairwise coprime
+/// let mut planner = FFTplanner::new(false);
+/// let inner_ffts = vec![planner.plan_fft(3), planner.plan_fft(5), planner.plan_fft(7)];
+///
+/// // the prime-factor FFT length will be the product of the inner FFT lengths
+/// let fft = PrimeFactorAlgorithm::new(inner_ffts);
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct PrimeFactorAlgorithm<T> {
+    inner_ffts: Box<[Arc<dyn FFT<T>>]>,
+    input_output_map: Box<[usize]>,
+
+    len: usize,
+    inverse: bool,
+}
+
+impl<T: FFTnum> PrimeFactorAlgorithm<T> {
+    /// Creates a FFT instance which will process inputs/outputs of size `inner_ffts[0].len() * inner_ffts[1].len() * ...`
+    ///
+    /// The lengths of the inner FFTs must be pairwise coprime, and the inner FFTs must all have the same direction.
+    pub fn new(inner_ffts: Vec<Arc<dyn FFT<T>>>) -> Self {
+        assert!(!inner_ffts.is_empty(), "The Prime-Factor Algorithm requires at least one inner FFT");
+
+        let inverse = inner_ffts[0].is_inverse();
+        for fft in &inner_ffts {
+            assert_eq!(inverse, fft.is_inverse(),
+                "inner FFTs must all be inverse, or all be forward. got inverse={} and inverse={}", inverse, fft.is_inverse());
+        }
+
+        let lengths: Vec<usize> = inner_ffts.iter().map(|fft| fft.len()).collect();
+        for (i, &a) in lengths.iter().enumerate() {
+            for &b in &lengths[i + 1..] {
+                assert!(gcd(a, b) == 1,
+                    "Invalid inner FFT sizes for the Prime-Factor Algorithm: {:?}. Sizes must be pairwise coprime", lengths);
+            }
+        }
+
+        let len = lengths.iter().product();
+
+        let input_output_map: Vec<usize> = compute_input_map(&lengths).into_iter().chain(compute_output_map(&lengths)).collect();
+
+        PrimeFactorAlgorithm {
+            inner_ffts: inner_ffts.into_boxed_slice(),
+            input_output_map: input_output_map.into_boxed_slice(),
+
+            len,
+            inverse,
+        }
+    }
+
+    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        let (input_map, output_map) = self.input_output_map.split_at(self.len());
+
+        // copy the input into the output buffer, using our input reordering mapping
+        for (output_element, &input_index) in output.iter_mut().zip(input_map.iter()) {
+            *output_element = input[input_index];
+        }
+
+        // the data is now a multidimensional array with the last dimension contiguous. run FFTs along the contiguous
+        // dimension, then transpose to rotate the next dimension into the contiguous position
+        let (first_fft, remaining_ffts) = self.inner_ffts.split_first().unwrap();
+        for fft in remaining_ffts.iter().rev() {
+            fft.process_multi_with_scratch(output, input, scratch);
+            array_utils::transpose(fft.len(), self.len() / fft.len(), input, output);
+        }
+
+        // run the FFTs of the last remaining dimension. we skip the final transpose, because the output mapping accounts for it
+        first_fft.process_multi_with_scratch(output, input, scratch);
+
+        // copy to the output, using our output reordering mapping
+        for (input_element, &output_index) in input.iter().zip(output_map.iter()) {
+            output[output_index] = *input_element;
+        }
+    }
+}
+
+// For each element of the multidimensional array whose dimensions are `lengths` (in row major order), computes the
+// index of the input element that should be copied to it. This is the "Ruritanian" mapping: n = sum(n_i * N / N_i) mod N
+fn compute_input_map(lengths: &[usize]) -> Vec<usize> {
+    let len: usize = lengths.iter().product();
+
+    (0..len).map(|mut position| {
+        let mut index = 0;
+        for &dimension_len in lengths.iter().rev() {
+            let digit = position % dimension_len;
+            position /= dimension_len;
+
+            index = (index + digit * (len / dimension_len)) % len;
+        }
+        index
+    }).collect()
+}
+
+// For each element of the multidimensional array whose dimensions are `lengths`, rotated so that the first dimension is
+// last, computes the index of the output element it should be copied to. This is the Chinese Remainder Theorem mapping:
+// k = sum(k_i * (N / N_i) * ((N / N_i)^-1 mod N_i)) mod N
+fn compute_output_map(lengths: &[usize]) -> Vec<usize> {
+    let len: usize = lengths.iter().product();
+
+    let coefficients: Vec<usize> = lengths.iter().map(|&dimension_len| {
+        let others = len / dimension_len;
+        let inverse = math_utils::multiplicative_inverse((others % dimension_len) as u64, dimension_len as u64) as usize;
+        others * inverse % len
+    }).collect();
+
+    // the first dimension is the contiguous one, followed by the rest of the dimensions from last to second
+    let dimension_order: Vec<usize> = Some(0).into_iter().chain((1..lengths.len()).rev()).collect();
+
+    (0..len).map(|mut position| {
+        let mut index = 0;
+        for &dimension in &dimension_order {
+            let digit = position % lengths[dimension];
+            position /= lengths[dimension];
+
+            index = (index + digit * coefficients[dimension]) % len;
+        }
+        index
+    }).collect()
+}
+
+impl<T: FFTnum> FFT<T> for PrimeFactorAlgorithm<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.perform_fft(input, output, &mut scratch);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.process_multi_with_scratch(input, output, &mut scratch);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        // the inner FFTs run one after the other, so they can share their extra scratch
+        self.inner_ffts.iter().map(|fft| fft.extra_scratch_len()).max().unwrap_or(0)
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        verify_length_divisible(input, output, self.len());
+        let scratch_len = self.extra_scratch_len();
+        assert!(scratch.len() >= scratch_len, "Scratch is the wrong length. Expected at least {}, got {}", scratch_len, scratch.len());
+
+        for (in_chunk, out_chunk) in input.chunks_mut(self.len()).zip(output.chunks_mut(self.len())) {
+            self.perform_fft(in_chunk, out_chunk, scratch);
+        }
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(PrimeFactorAlgorithm {
+            inner_ffts: self.inner_ffts.iter().map(|fft| fft.inverse_of()).collect::<Vec<_>>().into_boxed_slice(),
+            input_output_map: self.input_output_map.clone(),
+
+            len: self.len,
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.input_output_map) + self.inner_ffts.iter().map(|fft| fft.memory_footprint()).sum::<usize>()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        self.inner_ffts.iter().map(|fft| fft.error_bound_estimate()).sum()
+    }
+    fn describe(&self) -> String {
+        let inner_descriptions: Vec<String> = self.inner_ffts.iter().map(|fft| fft.describe()).collect();
+        format!("PrimeFactorAlgorithm({})", inner_descriptions.join(", "))
+    }
+    fn operation_count(&self) -> usize {
+        // each inner FFT is computed once for every row of the other dimensions
+        self.inner_ffts.iter().map(|fft| self.len() / fft.len() * fft.operation_count()).sum()
+    }
+}
+impl<T> Length for PrimeFactorAlgorithm<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<T> IsInverse for PrimeFactorAlgorithm<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use std::sync::Arc;
+    use test_utils::check_fft_algorithm;
+    use algorithm::DFT;
+
+    #[test]
+    fn test_prime_factor() {
+        let test_list: &[&[usize]] = &[
+            &[7],
+            &[2, 3],
+            &[3, 5, 7],
+            &[2, 3, 5, 7],
+            &[4, 9, 5],
+            &[8, 3, 1, 5],
+            &[1, 1],
+        ];
+
+        for &lengths in test_list {
+            test_prime_factor_with_lengths(lengths, false);
+            test_prime_factor_with_lengths(lengths, true);
+        }
+    }
+
+    #[test]
+    fn test_prime_factor_maps_are_permutations() {
+        let test_list: &[&[usize]] = &[&[3, 5, 7], &[2, 3, 5, 7], &[16, 9, 25, 7]];
+
+        for &lengths in test_list {
+            let len: usize = lengths.iter().product();
+
+            for map in &[compute_input_map(lengths), compute_output_map(lengths)] {
+                let mut seen = vec![false; len];
+                for &index in map {
+                    assert!(!seen[index], "lengths = {:?}: index {} appears twice", lengths, index);
+                    seen[index] = true;
+                }
+                assert_eq!(map.len(), len);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_prime_factor_rejects_non_coprime() {
+        let inner_ffts = [3, 5, 6].iter().map(|&len| Arc::new(DFT::new(len, false)) as Arc<dyn FFT<f32>>).collect();
+        PrimeFactorAlgorithm::new(inner_ffts);
+    }
+
+    fn test_prime_factor_with_lengths(lengths: &[usize], inverse: bool) {
+        let inner_ffts = lengths.iter().map(|&len| Arc::new(DFT::new(len, inverse)) as Arc<dyn FFT<f32>>).collect();
+        let len = lengths.iter().product();
+
+        let fft = PrimeFactorAlgorithm::new(inner_ffts);
+
+        check_fft_algorithm(&fft, len, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), len, !inverse);
+    }
+}
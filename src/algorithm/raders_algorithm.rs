@@ -1,9 +1,10 @@
 use std::sync::Arc;
+use std::mem;
 
 use num_complex::Complex;
 use num_traits::{FromPrimitive, Zero};
 
-use common::{FFTnum, verify_length, verify_length_divisible};
+use common::{FFTnum, verify_length, verify_length_divisible, epsilon};
 
 use math_utils;
 use twiddles;
@@ -38,9 +39,13 @@ use ::{Length, IsInverse, FFT};
 /// Rader's Algorithm is relatively expensive compared to other FFT algorithms. Benchmarking shows that it is up to
 /// an order of magnitude slower than similar composite sizes. In the example size above of 1201, benchmarking shows
 /// that it takes 2.5x more time to compute than a FFT of size 1200.
-
+///
+/// Internally, Rader's Algorithm computes a cyclic convolution of size n - 1. If n - 1 has a large prime factor, the
+/// inner FFT will need Rader's Algorithm again, making it even slower. `new_with_bluestein_inner` avoids this by
+/// zero-padding the convolution to a size of the caller's choosing, usually a power of two, like Bluestein's Algorithm.
 pub struct RadersAlgorithm<T> {
-    inner_fft: Arc<FFT<T>>,
+    len: usize,
+    inner_fft: Arc<dyn FFT<T>>,
     inner_fft_data: Box<[Complex<T>]>,
 
     input_output_map: Box<[usize]>,
@@ -54,10 +59,45 @@ impl<T: FFTnum> RadersAlgorithm<T> {
     /// FFT algorithms
     ///
     /// Note also that if `len` is not prime, this algorithm may silently produce garbage output
-    pub fn new(len: usize, inner_fft: Arc<FFT<T>>) -> Self {
+    pub fn new(len: usize, inner_fft: Arc<dyn FFT<T>>) -> Self {
         assert_eq!(len - 1, inner_fft.len(), "For raders algorithm, inner_fft.len() must be self.len() - 1. Expected {}, got {}", len - 1, inner_fft.len());
 
-        let inner_fft_len = len - 1;
+        Self::new_with_inner(len, inner_fft)
+    }
+
+    /// Creates a FFT instance which will process inputs/outputs of size `len`, computing its inner cyclic convolution
+    /// of size `len - 1` with a zero-padded convolution of size `inner_fft.len()`, which must be at least `2 * len - 3`
+    ///
+    /// This is faster than `new` when `len - 1` has a large prime factor, for example when `len - 1` is twice a prime,
+    /// because the inner FFT can be a power of two instead of another instance of Rader's Algorithm. The price is that
+    /// the inner FFT is roughly twice as large, and the convolution needs two buffers of that size, which `process`
+    /// allocates on every call, and `process_with_scratch` takes from the caller's scratch buffer.
+    ///
+    /// ~~~
+    /// // Computes a forward FFT of size 1019 (prime number, and 1018 = 2 * 509), with an inner FFT of size 2048
+    /// use rustfft::algorithm::{RadersAlgorithm, Radix4};
+    /// use rustfft::FFT;
+    /// use rustfft::num_complex::Complex;
+    /// use rustfft::num_traits::Zero;
+    /// use std::sync::Arc;
+    ///
+    /// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 1019];
+    /// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 1019];
+    ///
+    /// let fft = RadersAlgorithm::new_with_bluestein_inner(1019, Arc::new(Radix4::new(2048, false)));
+    /// fft.process(&mut input, &mut output);
+    /// ~~~
+    pub fn new_with_bluestein_inner(len: usize, inner_fft: Arc<dyn FFT<T>>) -> Self {
+        assert!(len >= 2, "For raders algorithm, len must be at least 2. Got {}", len);
+        let min_inner_len = 2 * len - 3;
+        assert!(inner_fft.len() >= min_inner_len, "For raders algorithm with a padded convolution, inner_fft.len() must be at least 2 * self.len() - 3. Expected at least {}, got {}", min_inner_len, inner_fft.len());
+
+        Self::new_with_inner(len, inner_fft)
+    }
+
+    fn new_with_inner(len: usize, inner_fft: Arc<dyn FFT<T>>) -> Self {
+        let convolution_len = len - 1;
+        let inner_fft_len = inner_fft.len();
 
         // compute the primitive root and its inverse for this size
         let primitive_root = math_utils::primitive_root(len as u64).unwrap();
@@ -65,12 +105,20 @@ impl<T: FFTnum> RadersAlgorithm<T> {
 
         // precompute the coefficients to use inside the process method
         let unity_scale: T = FromPrimitive::from_f64(1f64 / inner_fft_len as f64).unwrap();
-        let mut inner_fft_input: Vec<Complex<T>> = (0..inner_fft_len)
+        let coefficients: Vec<Complex<T>> = (0..convolution_len)
             .map(|i| math_utils::modular_exponent(root_inverse, i as u64, len as u64) as usize)
             .map(|i| twiddles::single_twiddle(i, len, inner_fft.is_inverse()))
             .map(|c| c * unity_scale)
             .collect();
 
+        // if the convolution is padded, the input is padded with zeros, so the coefficients must wrap around instead:
+        // the last `convolution_len - 1` elements are the coefficients for the negative offsets -1, -2, etc
+        let mut inner_fft_input = vec![Zero::zero(); inner_fft_len];
+        inner_fft_input[..convolution_len].copy_from_slice(&coefficients);
+        if inner_fft_len > convolution_len {
+            inner_fft_input[inner_fft_len - convolution_len + 1..].copy_from_slice(&coefficients[1..]);
+        }
+
         //precompute a FFT of our reordered twiddle factors
         let mut inner_fft_output = vec![Zero::zero(); inner_fft_len];
         inner_fft.process(&mut inner_fft_input, &mut inner_fft_output);
@@ -80,14 +128,22 @@ impl<T: FFTnum> RadersAlgorithm<T> {
         let input_output_map: Vec<usize> = (1..len64-1).map(|i| math_utils::modular_exponent(primitive_root, i, len64) as usize - 1).collect();
 
         RadersAlgorithm {
-            inner_fft: inner_fft,
+            len,
+            inner_fft,
             inner_fft_data: inner_fft_output.into_boxed_slice(),
 
             input_output_map: input_output_map.into_boxed_slice(),
         }
     }
 
-    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+    // the number of elements of extra scratch the padded convolution needs, beyond the input buffer: a buffer and a
+    // scratch buffer for the inner FFT. an unpadded convolution fits in the input and output buffers, and needs none
+    fn padded_scratch_len(&self) -> usize {
+        if self.inner_fft.len() == self.len - 1 { 0 } else { 2 * self.inner_fft.len() }
+    }
+
+    // `scratch` holds the padded convolution buffers, if any, followed by the inner FFT's extra scratch
+    fn perform_fft(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
 
         // The first output element is just the sum of all the input elements
         let (first_output, output) = output.split_first_mut().unwrap();
@@ -95,40 +151,64 @@ impl<T: FFTnum> RadersAlgorithm<T> {
 
         // Also split off the first input elements. After this, both input and output len will be n - 1
         let (first_input, input) = input.split_first_mut().unwrap();
+        let first_input_val = *first_input;
 
-        {
-            // prepare the inner FFT by reordering the input buffer into the output buffer
-            // Split off the last target element, because we're going to treat it separately
-            let (output_last, output) = output.split_last_mut().unwrap();
-
-            // we could compute the indexes here on the fly, but benchmarking shows it's faster to precompute and store them
-            for (input_index, output_element) in self.input_output_map.iter().zip(output.iter_mut()) {
-                *output_element = input[*input_index];
+        if self.inner_fft.len() == input.len() {
+            // the convolution fits in our buffers: use the output as the inner FFT's input, and the input as its scratch
+            self.reorder_input(input, output);
+            self.convolve(output, input, scratch);
+            self.reorder_output(input, output, first_input_val);
+        } else {
+            let (padded_scratch, inner_scratch) = scratch.split_at_mut(self.padded_scratch_len());
+            let (buffer, scratch) = padded_scratch.split_at_mut(self.inner_fft.len());
+
+            // the reordered input only fills the first n - 1 elements, so zero the padding left over from last time
+            self.reorder_input(input, &mut buffer[..input.len()]);
+            for element in buffer[input.len()..].iter_mut() {
+                *element = Zero::zero();
             }
+            self.convolve(buffer, scratch, inner_scratch);
+            self.reorder_output(&scratch[..input.len()], output, first_input_val);
+        }
+    }
+
+    // reorder the input buffer into the first n - 1 elements of the convolution buffer
+    fn reorder_input(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        // Split off the last target element, because we're going to treat it separately
+        let (output_last, output) = output.split_last_mut().unwrap();
 
-            // the first element always gets copied to the last element
-            *output_last = input[0];
+        // we could compute the indexes here on the fly, but benchmarking shows it's faster to precompute and store them
+        for (input_index, output_element) in self.input_output_map.iter().zip(output.iter_mut()) {
+            *output_element = input[*input_index];
         }
 
+        // the first element always gets copied to the last element
+        *output_last = input[0];
+    }
+
+    // convolve `buffer` with our cached setup data. `buffer` is used as scratch, and the conjugate of the result is
+    // left in `scratch`. `inner_scratch` is passed on to the inner FFT
+    fn convolve(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>], inner_scratch: &mut [Complex<T>]) {
         // perform the first of two inner FFTs
-        self.inner_fft.process(output, input);
+        self.inner_fft.process_multi_with_scratch(buffer, scratch, inner_scratch);
 
         // multiply the inner result with our cached setup data
         // also conjugate every entry. this sets us up to do an inverse FFT
         // (because an inverse FFT is equivalent to a normal FFT where you conjugate both the inputs and outputs)
-        for ((&input_cell, output_cell), &multiple) in input.iter().zip(output.iter_mut()).zip(self.inner_fft_data.iter()) {
-            *output_cell = (input_cell * multiple).conj();
+        for ((&scratch_cell, buffer_cell), &multiple) in scratch.iter().zip(buffer.iter_mut()).zip(self.inner_fft_data.iter()) {
+            *buffer_cell = (scratch_cell * multiple).conj();
         }
 
         // execute the second FFT
-        self.inner_fft.process(output, input);
+        self.inner_fft.process_multi_with_scratch(buffer, scratch, inner_scratch);
+    }
 
-        // copy the input buffer to the output buffer, reordering the elements as we go.
+    // copy the convolution result to the output buffer, reordering the elements as we go
+    fn reorder_output(&self, input: &[Complex<T>], output: &mut [Complex<T>], first_input_val: Complex<T>) {
         // Split off the last input element, because we're going to treat it separately
-        let (input_last, input) = input.split_last_mut().unwrap();
+        let (input_last, input) = input.split_last().unwrap();
 
         // we could compute the indexes here on the fly, but benchmarking shows it's faster to precompute and store them
-        let first_input_val = *first_input;
         for (&output_index, input_element) in self.input_output_map.iter().rev().zip(input.iter()) {
             output[output_index] = input_element.conj() + first_input_val;
         }
@@ -142,20 +222,62 @@ impl<T: FFTnum> FFT<T> for RadersAlgorithm<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
-        self.perform_fft(input, output);
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.perform_fft(input, output, &mut scratch);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Zero::zero(); self.extra_scratch_len()];
+        self.process_multi_with_scratch(input, output, &mut scratch);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        self.padded_scratch_len() + self.inner_fft.extra_scratch_len()
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
         verify_length_divisible(input, output, self.len());
+        let scratch_len = self.extra_scratch_len();
+        assert!(scratch.len() >= scratch_len, "Scratch is the wrong length. Expected at least {}, got {}", scratch_len, scratch.len());
 
+        let scratch = &mut scratch[..scratch_len];
         for (in_chunk, out_chunk) in input.chunks_mut(self.len()).zip(output.chunks_mut(self.len())) {
-            self.perform_fft(in_chunk, out_chunk);
+            self.perform_fft(in_chunk, out_chunk, scratch);
+        }
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        // the inverse twiddles are the conjugates of the forward twiddles, so the inner FFT data is just conjugated too
+        Arc::new(RadersAlgorithm {
+            len: self.len,
+            inner_fft: self.inner_fft.inverse_of(),
+            inner_fft_data: self.inner_fft_data.iter().map(|c| c.conj()).collect::<Vec<_>>().into_boxed_slice(),
+
+            input_output_map: self.input_output_map.clone(),
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.inner_fft_data) + mem::size_of_val(&*self.input_output_map) + self.inner_fft.memory_footprint()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        // two inner FFTs, with a multiplication by the precomputed data in between
+        2f64 * self.inner_fft.error_bound_estimate() + epsilon::<T>()
+    }
+    fn describe(&self) -> String {
+        if self.inner_fft.len() == self.len - 1 {
+            format!("RadersAlgorithm({})", self.inner_fft.describe())
+        } else {
+            format!("RadersAlgorithm({}, Bluestein({}))", self.len, self.inner_fft.describe())
         }
     }
+    fn operation_count(&self) -> usize {
+        // two inner FFTs, with a multiplication by the precomputed data in between
+        2 * self.inner_fft.operation_count() + self.inner_fft.len()
+    }
 }
 impl<T> Length for RadersAlgorithm<T> {
     #[inline(always)]
     fn len(&self) -> usize {
-        self.inner_fft_data.len() + 1
+        self.len
     }
 }
 impl<T> IsInverse for RadersAlgorithm<T> {
@@ -169,7 +291,7 @@ impl<T> IsInverse for RadersAlgorithm<T> {
 mod unit_tests {
     use super::*;
     use std::sync::Arc;
-    use test_utils::check_fft_algorithm;
+    use test_utils::{check_fft_algorithm, compare_vectors, random_signal};
     use algorithm::DFT;
 
     #[test]
@@ -180,10 +302,55 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_raders_bluestein_inner() {
+        // 23 - 1 = 2 * 11, and 47 - 1 = 2 * 23, where both halves are prime
+        for &len in &[3usize, 5, 7, 11, 13, 23, 47] {
+            let min_inner_len = 2 * len - 3;
+            for &inner_len in &[min_inner_len, min_inner_len.next_power_of_two()] {
+                test_raders_bluestein_with_length(len, inner_len, false);
+                test_raders_bluestein_with_length(len, inner_len, true);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_raders_bluestein_inner_too_short() {
+        RadersAlgorithm::new_with_bluestein_inner(23, Arc::new(DFT::<f32>::new(42, false)));
+    }
+
+    #[test]
+    fn test_raders_bluestein_process_with_scratch() {
+        let fft = RadersAlgorithm::new_with_bluestein_inner(23, Arc::new(DFT::<f32>::new(64, false)));
+        assert_eq!(fft.required_scratch_len(), 23 + 2 * 64);
+
+        // reuse one scratch buffer for several signals, to make sure the padding doesn't carry over between calls
+        let mut scratch = vec![Zero::zero(); fft.required_scratch_len()];
+        for _ in 0..3 {
+            let signal = random_signal(23);
+            let mut expected = vec![Zero::zero(); 23];
+            fft.process(&mut signal.clone(), &mut expected);
+
+            let mut actual = vec![Zero::zero(); 23];
+            fft.process_with_scratch(&signal, &mut actual, &mut scratch);
+            assert!(compare_vectors(&expected, &actual));
+        }
+    }
+
+    fn test_raders_bluestein_with_length(len: usize, inner_len: usize, inverse: bool) {
+        let inner_fft = Arc::new(DFT::new(inner_len, inverse));
+        let fft = RadersAlgorithm::new_with_bluestein_inner(len, inner_fft);
+
+        check_fft_algorithm(&fft, len, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), len, !inverse);
+    }
+
     fn test_raders_with_length(len: usize, inverse: bool) {
         let inner_fft = Arc::new(DFT::new(len - 1, inverse));
         let fft = RadersAlgorithm::new(len, inner_fft);
 
         check_fft_algorithm(&fft, len, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), len, !inverse);
     }
 }
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::mem;
+
+use num_complex::Complex;
+
+use common::{FFTnum, verify_length, verify_length_divisible};
+
+use algorithm::butterflies::{Butterfly3, FFTButterfly};
+use ::{Length, IsInverse, FFT, Direction};
+use twiddles;
+use math_utils::is_power_of_three;
+
+/// FFT algorithm optimized for power-of-three sizes
+///
+/// This is the base-3 counterpart to `Radix4`: it reorders the input into base-3 digit-reversed order, performs a layer
+/// of size-3 butterflies, and then every layer after that is a radix-3 cross-FFT. Unlike splitting the FFT with the
+/// Mixed-Radix algorithm, it never transposes the data.
+///
+/// The planner uses this algorithm for power-of-three sizes from 9 up.
+///
+/// ~~~
+/// // Computes a forward FFT of size 2187
+/// use rustfft::algorithm::Radix3;
+/// use rustfft::FFT;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 2187];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 2187];
+///
+/// let fft = Radix3::new(2187, false);
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct Radix3<T> {
+    twiddles: Arc<[Complex<T>]>,
+    butterfly3: Butterfly3<T>,
+    len: usize,
+    inverse: bool,
+}
+
+impl<T: FFTnum> Radix3<T> {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the power-of-three FFT
+    pub fn new<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        assert!(is_power_of_three(len), "Radix3 algorithm requires a power-of-three input size. Got {}", len);
+
+        // pack the twiddle factors of every layer of cross-FFTs into a single array, starting with the bottom layer
+        let mut twiddle_factors = Vec::with_capacity(len);
+        let mut cross_fft_len = 9;
+        while cross_fft_len <= len {
+            let num_columns = cross_fft_len / 3;
+            let twiddle_stride = len / cross_fft_len;
+            for i in 0..num_columns {
+                for k in 1..3 {
+                    twiddle_factors.push(twiddles::single_twiddle(i * k * twiddle_stride, len, inverse));
+                }
+            }
+            cross_fft_len *= 3;
+        }
+
+        Radix3 {
+            twiddles: twiddle_factors.into(),
+            butterfly3: Butterfly3::new(inverse),
+            len,
+            inverse,
+        }
+    }
+
+    fn perform_fft(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>]) {
+        if self.len < 3 {
+            spectrum.copy_from_slice(signal);
+            return;
+        }
+
+        // copy the data into the spectrum vector, in digit-reversed order
+        prepare_radix3(signal.len(), signal, spectrum, 1);
+
+        unsafe { self.butterfly3.process_multi_inplace(spectrum) };
+
+        // the size-3 cross-FFTs only need the imaginary part of single_twiddle(1, 3), since its real part is -1/2
+        let half = T::from_f64(0.5).unwrap();
+        let rotation: Complex<T> = twiddles::single_twiddle(1, 3, self.inverse);
+
+        // now, perform all the cross-FFTs, one "layer" at a time
+        let mut layer_twiddles: &[Complex<T>] = &self.twiddles;
+        let mut current_size = 9;
+        while current_size <= signal.len() {
+            let num_columns = current_size / 3;
+
+            for chunk in spectrum.chunks_mut(current_size) {
+                for i in 0..num_columns {
+                    let a = chunk[i];
+                    let b = chunk[i + num_columns] * layer_twiddles[2 * i];
+                    let c = chunk[i + 2 * num_columns] * layer_twiddles[2 * i + 1];
+
+                    let sum = b + c;
+                    let rotated_diff = Complex::new(-(b.im - c.im), b.re - c.re) * rotation.im;
+                    let partial = a - sum * half;
+
+                    chunk[i] = a + sum;
+                    chunk[i + num_columns] = partial + rotated_diff;
+                    chunk[i + 2 * num_columns] = partial - rotated_diff;
+                }
+            }
+
+            //skip past all the twiddle factors used in this layer
+            layer_twiddles = &layer_twiddles[2 * num_columns..];
+            current_size *= 3;
+        }
+    }
+}
+
+impl<T: FFTnum> FFT<T> for Radix3<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length(input, output, self.len());
+
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.perform_fft(input, output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        verify_length_divisible(input, output, self.len());
+
+        for (in_chunk, out_chunk) in input.chunks_mut(self.len()).zip(output.chunks_mut(self.len())) {
+            self.perform_fft(in_chunk, out_chunk);
+        }
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Radix3 {
+            twiddles: self.twiddles.iter().map(|t| t.conj()).collect::<Vec<_>>().into(),
+            butterfly3: Butterfly3::inverse_of(&self.butterfly3),
+            len: self.len,
+            inverse: !self.inverse,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.twiddles)
+    }
+    fn describe(&self) -> String {
+        format!("Radix3({})", self.len())
+    }
+}
+impl<T> Length for Radix3<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<T> IsInverse for Radix3<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+}
+
+// copies `signal` into `spectrum` in base-3 digit-reversed order, the same way that `prepare_radix4` does for base 4
+fn prepare_radix3<T: FFTnum>(size: usize,
+                             signal: &[Complex<T>],
+                             spectrum: &mut [Complex<T>],
+                             stride: usize) {
+    if size <= 3 {
+        for i in 0..size {
+            spectrum[i] = signal[i * stride];
+        }
+    } else {
+        for i in 0..3 {
+            prepare_radix3(size / 3,
+                           &signal[i * stride..],
+                           &mut spectrum[i * (size / 3)..],
+                           stride * 3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::check_fft_algorithm;
+
+    #[test]
+    fn test_radix3() {
+        for &len in &[1, 3, 9, 27, 81, 243, 729] {
+            for &inverse in &[false, true] {
+                let fft = Radix3::new(len, inverse);
+
+                check_fft_algorithm(&fft, len, inverse);
+                check_fft_algorithm(&*fft.inverse_of(), len, !inverse);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_radix3_rejects_other_sizes() {
+        Radix3::<f32>::new(12, false);
+    }
+}
@@ -1,14 +1,32 @@
+use std::sync::Arc;
+use std::mem;
+
 use num_complex::Complex;
 use num_traits::Zero;
 
 use common::{FFTnum, verify_length, verify_length_divisible};
 
 use algorithm::butterflies::{Butterfly2, Butterfly4, Butterfly8, Butterfly16, FFTButterfly};
-use ::{Length, IsInverse, FFT};
+use ::{Length, IsInverse, FFT, Direction};
 use twiddles;
 
+/// The default size at which `Radix4` switches to a cache-blocked reordering of its input. See
+/// `Radix4::set_blocked_reorder_threshold`
+///
+/// The planner only uses `Radix4` up to 65536, and splits larger sizes with `MixedRadix`, so planned FFTs never reach
+/// this threshold. It only applies to `Radix4` instances created directly, with sizes of 2^19 and up. Benchmarks show
+/// that below 2^19 the blocked reordering is no faster, so lowering the default wouldn't speed up planned FFTs.
+pub const DEFAULT_BLOCKED_REORDER_THRESHOLD: usize = 1 << 19;
+
 /// FFT algorithm optimized for power-of-two sizes
 ///
+/// Every power of two is supported, not just powers of four. The FFT starts with a layer of size-16 butterflies if
+/// the number of bits in the size is even, or a layer of size-8 butterflies if it's odd, and every layer after
+/// that is radix 4, so no separate radix-2 cleanup stage is needed.
+///
+/// The planner uses this algorithm for power-of-two sizes from 64 up to 65536. Smaller sizes use the hardcoded
+/// butterflies, and larger sizes are split with the Mixed-Radix algorithm into two halves, each of which uses Radix4.
+///
 /// ~~~
 /// // Computes a forward FFT of size 4096
 /// use rustfft::algorithm::Radix4;
@@ -22,32 +40,55 @@ use twiddles;
 /// let fft = Radix4::new(4096, false);
 /// fft.process(&mut input, &mut output);
 /// ~~~
-
 pub struct Radix4<T> {
-    twiddles: Box<[Complex<T>]>,
+    twiddles: Arc<[Complex<T>]>,
     butterfly8: Butterfly8<T>,
     butterfly16: Butterfly16<T>,
     len: usize,
     inverse: bool,
+    blocked_reorder_threshold: usize,
 }
 
 impl<T: FFTnum> Radix4<T> {
     /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the power-of-two FFT
-    pub fn new(len: usize, inverse: bool) -> Self {
+    pub fn new<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        Self::with_twiddles(len, inverse, Self::generate_twiddles(len, inverse))
+    }
+
+    /// Creates a FFT instance that uses an existing twiddle factor table, instead of computing its own
+    ///
+    /// `twiddles` must be the table returned by `Radix4::generate_twiddles(len, inverse)`, or by `twiddles()` on another
+    /// instance with the same size and direction. This lets several instances of the same size share one table.
+    pub fn with_twiddles<D: Into<Direction>>(len: usize, direction: D, twiddles: Arc<[Complex<T>]>) -> Self {
+        let inverse = direction.into().is_inverse();
+        assert!(len.is_power_of_two(), "Radix4 algorithm requires a power-of-two input size. Got {}", len);
+        assert_eq!(twiddles.len(), twiddle_count(len),
+            "Twiddle table is the wrong length for a Radix4 FFT of size {}. Expected {}, got {}", len, twiddle_count(len), twiddles.len());
+
+        Radix4 {
+            twiddles,
+            butterfly8: Butterfly8::new(inverse),
+            butterfly16: Butterfly16::new(inverse),
+            len,
+            inverse,
+            blocked_reorder_threshold: DEFAULT_BLOCKED_REORDER_THRESHOLD,
+        }
+    }
+
+    /// Computes the twiddle factor table used by a Radix4 FFT of size `len`, so that it can be shared between instances
+    /// with `with_twiddles`
+    pub fn generate_twiddles<D: Into<Direction>>(len: usize, direction: D) -> Arc<[Complex<T>]> {
+        let inverse = direction.into().is_inverse();
         assert!(len.is_power_of_two(), "Radix4 algorithm requires a power-of-two input size. Got {}", len);
 
         // precompute the twiddle factors this algorithm will use.
         // we're doing the same precomputation of twiddle factors as the mixed radix algorithm where width=4 and height=len/4
         // but mixed radix only does one step and then calls itself recusrively, and this algorithm does every layer all the way down
         // so we're going to pack all the "layers" of twiddle factors into a single array, starting with the bottom and going up
-        let num_bits = len.trailing_zeros();
-        let mut twiddle_stride = if num_bits%2 == 0 {
-            len / 64
-        } else {
-            len / 32
-        };
+        let mut twiddle_stride = initial_twiddle_stride(len);
 
-        let mut twiddle_factors = Vec::with_capacity(len * 2);
+        let mut twiddle_factors = Vec::with_capacity(twiddle_count(len));
         while twiddle_stride > 0 {
             let num_rows = len / (twiddle_stride * 4);
             for i in 0..num_rows {
@@ -59,18 +100,35 @@ impl<T: FFTnum> Radix4<T> {
             twiddle_stride >>= 2;
         }
 
-        Radix4 {
-            twiddles: twiddle_factors.into_boxed_slice(),
-            butterfly8: Butterfly8::new(inverse),
-            butterfly16: Butterfly16::new(inverse),
-            len: len,
-            inverse: inverse,
-        }
+        twiddle_factors.into()
+    }
+
+    /// The twiddle factor table used by this instance
+    pub fn twiddles(&self) -> &Arc<[Complex<T>]> {
+        &self.twiddles
+    }
+
+    /// Sets the smallest size at which the input is reordered with a cache-blocked digit reversal
+    ///
+    /// Before its first layer of butterflies, Radix4 copies the input into the output in base-4 digit-reversed order.
+    /// The default recursive reordering reads the input with large power-of-two strides, which is fast while the input
+    /// fits in cache, but misses the cache on almost every read once it doesn't. The blocked reordering reorders one
+    /// tile of a few hundred rows at a time instead, which costs some bookkeeping but keeps its working set in cache.
+    /// Both produce exactly the same ordering, so this only affects speed. The default is
+    /// `DEFAULT_BLOCKED_REORDER_THRESHOLD`, which is larger than any `Radix4` that the planner creates. Set it to 0 to
+    /// always use the blocked reordering, or to `usize::MAX` to never use it.
+    pub fn set_blocked_reorder_threshold(&mut self, threshold: usize) {
+        self.blocked_reorder_threshold = threshold;
+    }
+
+    /// The smallest size at which the input is reordered with a cache-blocked digit reversal
+    pub fn blocked_reorder_threshold(&self) -> usize {
+        self.blocked_reorder_threshold
     }
 
     fn perform_fft(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>]) {
         match self.len() {
-            0...1 => spectrum.copy_from_slice(signal),
+            0..=1 => spectrum.copy_from_slice(signal),
             2 => {
                 spectrum.copy_from_slice(signal);
                 unsafe { Butterfly2::new(self.inverse).process_inplace(spectrum) }
@@ -81,7 +139,11 @@ impl<T: FFTnum> Radix4<T> {
             },
             _ => {
                 // copy the data into the spectrum vector
-                prepare_radix4(signal.len(), signal, spectrum, 1);
+                if signal.len() >= self.blocked_reorder_threshold {
+                    blocked_prepare_radix4(signal, spectrum);
+                } else {
+                    prepare_radix4(signal.len(), signal, spectrum, 1);
+                }
 
                 // perform the butterflies. the butterfly size depends on the input size
                 let num_bits = signal.len().trailing_zeros();
@@ -127,6 +189,9 @@ impl<T: FFTnum> FFT<T> for Radix4<T> {
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         verify_length(input, output, self.len());
 
+        unsafe { self.process_unchecked(input, output) };
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
         self.perform_fft(input, output);
     }
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
@@ -136,6 +201,22 @@ impl<T: FFTnum> FFT<T> for Radix4<T> {
             self.perform_fft(in_chunk, out_chunk);
         }
     }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(Radix4 {
+            twiddles: self.twiddles.iter().map(|t| t.conj()).collect::<Vec<_>>().into(),
+            butterfly8: Butterfly8::inverse_of(&self.butterfly8),
+            butterfly16: Butterfly16::inverse_of(&self.butterfly16),
+            len: self.len,
+            inverse: !self.inverse,
+            blocked_reorder_threshold: self.blocked_reorder_threshold,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(&*self.twiddles)
+    }
+    fn describe(&self) -> String {
+        format!("Radix4({})", self.len())
+    }
 }
 impl<T> Length for Radix4<T> {
     #[inline(always)]
@@ -152,6 +233,26 @@ impl<T> IsInverse for Radix4<T> {
 
 
 
+// the twiddle stride of the bottom layer of cross-FFTs. it depends on whether the first layer is size 8 or size 16
+fn initial_twiddle_stride(len: usize) -> usize {
+    if len.trailing_zeros() % 2 == 0 {
+        len / 64
+    } else {
+        len / 32
+    }
+}
+
+// the number of twiddle factors needed by all the layers of cross-FFTs, combined
+fn twiddle_count(len: usize) -> usize {
+    let mut twiddle_stride = initial_twiddle_stride(len);
+    let mut count = 0;
+    while twiddle_stride > 0 {
+        count += 3 * len / (twiddle_stride * 4);
+        twiddle_stride >>= 2;
+    }
+    count
+}
+
 // after testing an iterative bit reversal algorithm, this recursive algorithm
 // was almost an order of magnitude faster at setting up
 fn prepare_radix4<T: FFTnum>(size: usize,
@@ -190,16 +291,68 @@ fn prepare_radix4<T: FFTnum>(size: usize,
     }
 }
 
+// the number of base-4 digits on each side of the tiles that blocked_prepare_radix4 reorders at a time
+const TILE_DIGITS: u32 = 2;
+
+// reverses the order of the lowest `digits` base-4 digits of `index`
+fn reverse_base4_digits(index: usize, digits: u32) -> usize {
+    if digits == 0 {
+        0
+    } else {
+        // reversing the bits also swaps the two bits within each digit, so swap them back
+        let reversed = index.reverse_bits() >> (usize::BITS - 2 * digits);
+        let low_bits = !0usize / 3; // 0b0101...0101
+        ((reversed & low_bits) << 1) | ((reversed >> 1) & low_bits)
+    }
+}
+
+// computes the same ordering as prepare_radix4, but one tile of rows at a time, so that the working set stays in cache
+//
+// prepare_radix4 writes element `j * rows + r` of the signal to element `reverse(r) * leaf + j` of the spectrum, where
+// `leaf` is the size of the base butterflies, `rows = len / leaf` is a power of four, and `reverse` reverses the
+// base-4 digits of a row index. Split each row index into its top `TILE_DIGITS` digits `a`, its bottom `TILE_DIGITS`
+// digits `c`, and the digits `b` in between. Reversing a row index reverses each part and swaps `a` with `c`, so for a
+// fixed `b`, every read lands in one of `4^TILE_DIGITS` runs of the signal, and every write lands in one of
+// `4^TILE_DIGITS` runs of the spectrum. The recursive version reads from all over the signal instead, which misses the
+// cache on almost every read once the signal is larger than the cache.
+fn blocked_prepare_radix4<T: FFTnum>(signal: &[Complex<T>], spectrum: &mut [Complex<T>]) {
+    let len = signal.len();
+    let leaf = if len.trailing_zeros() % 2 == 0 { 16 } else { 8 };
+    let rows = len / leaf;
+    let row_digits = rows.trailing_zeros() / 2;
+
+    let tile_digits = TILE_DIGITS.min(row_digits / 2);
+    let tile_len = 1 << (2 * tile_digits);
+    let middle_digits = row_digits - 2 * tile_digits;
+    let outer_stride = rows / tile_len;
+
+    let tile_reversals: Vec<usize> = (0..tile_len).map(|index| reverse_base4_digits(index, tile_digits)).collect();
+
+    for b in 0..1 << (2 * middle_digits) {
+        let reversed_b = reverse_base4_digits(b, middle_digits);
+        for (c, &reversed_c) in tile_reversals.iter().enumerate() {
+            for (a, &reversed_a) in tile_reversals.iter().enumerate() {
+                let source_row = a * outer_stride + b * tile_len + c;
+                let destination = (reversed_c * outer_stride + reversed_b * tile_len + reversed_a) * leaf;
+                for j in 0..leaf {
+                    unsafe {
+                        *spectrum.get_unchecked_mut(destination + j) = *signal.get_unchecked(j * rows + source_row);
+                    }
+                }
+            }
+        }
+    }
+}
+
 unsafe fn butterfly_4<T: FFTnum>(data: &mut [Complex<T>],
                              twiddles: &[Complex<T>],
                              num_ffts: usize,
                              inverse: bool)
 {
-    let mut idx = 0usize;
     let mut tw_idx = 0usize;
     let mut scratch: [Complex<T>; 6] = [Zero::zero(); 6];
-    for _ in 0..num_ffts {
-        scratch[0] = data.get_unchecked(idx + 1 * num_ffts) * twiddles[tw_idx];
+    for idx in 0..num_ffts {
+        scratch[0] = data.get_unchecked(idx + num_ffts) * twiddles[tw_idx];
         scratch[1] = data.get_unchecked(idx + 2 * num_ffts) * twiddles[tw_idx + 1];
         scratch[2] = data.get_unchecked(idx + 3 * num_ffts) * twiddles[tw_idx + 2];
         scratch[5] = data.get_unchecked(idx) - scratch[1];
@@ -221,7 +374,6 @@ unsafe fn butterfly_4<T: FFTnum>(data: &mut [Complex<T>],
         }
 
         tw_idx += 3;
-        idx += 1;
     }
 }
 
@@ -232,16 +384,92 @@ mod unit_tests {
 
     #[test]
     fn test_radix4() {
-        for pow in 0..8 {
+        // test both odd and even powers of two, since they use different base butterflies
+        for pow in 0..11 {
             let len = 1 << pow;
             test_radix4_with_length(len, false);
             test_radix4_with_length(len, true);
         }
     }
 
+    #[test]
+    fn test_radix4_memory_footprint() {
+        let fft: Radix4<f32> = Radix4::new(1024, false);
+
+        // radix 4 stores a bit less than one twiddle factor per element
+        let expected = 1024 * mem::size_of::<Complex<f32>>();
+        let footprint = fft.memory_footprint();
+        assert!(footprint <= expected && footprint > expected * 9 / 10, "footprint = {}, expected roughly {}", footprint, expected);
+    }
+
+    #[test]
+    fn test_radix4_with_twiddles() {
+        use num_traits::Zero;
+        use test_utils::random_signal;
+
+        for pow in 0..11 {
+            let len = 1 << pow;
+            for &inverse in &[false, true] {
+                let fft: Radix4<f32> = Radix4::new(len, inverse);
+                let shared_fft = Radix4::with_twiddles(len, inverse, Arc::clone(fft.twiddles()));
+                let generated_fft = Radix4::with_twiddles(len, inverse, Radix4::generate_twiddles(len, inverse));
+
+                assert!(Arc::ptr_eq(fft.twiddles(), shared_fft.twiddles()));
+
+                // all three instances have identical twiddles, so the output should be bit-for-bit identical
+                let signal = random_signal(len);
+                let mut expected = vec![Zero::zero(); len];
+                fft.process(&mut signal.clone(), &mut expected);
+                for other_fft in &[shared_fft, generated_fft] {
+                    let mut actual = vec![Zero::zero(); len];
+                    other_fft.process(&mut signal.clone(), &mut actual);
+                    assert_eq!(expected, actual, "len = {}, inverse = {}", len, inverse);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_blocked_prepare_radix4() {
+        use num_traits::Zero;
+        use test_utils::random_signal;
+
+        for pow in 4..17 {
+            let len = 1 << pow;
+            let signal = random_signal(len);
+
+            let mut expected = vec![Zero::zero(); len];
+            prepare_radix4(len, &signal, &mut expected, 1);
+
+            let mut actual = vec![Zero::zero(); len];
+            blocked_prepare_radix4(&signal, &mut actual);
+            assert_eq!(expected, actual, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_radix4_blocked_reorder() {
+        for pow in 4..11 {
+            let len = 1 << pow;
+            for &inverse in &[false, true] {
+                let mut fft = Radix4::new(len, inverse);
+                fft.set_blocked_reorder_threshold(0);
+                check_fft_algorithm(&fft, len, inverse);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_radix4_with_twiddles_wrong_length() {
+        let twiddles = Radix4::<f32>::generate_twiddles(256, false);
+        Radix4::with_twiddles(1024, false, twiddles);
+    }
+
     fn test_radix4_with_length(len: usize, inverse: bool) {
         let fft = Radix4::new(len, inverse);
 
         check_fft_algorithm(&fft, len, inverse);
+        check_fft_algorithm(&*fft.inverse_of(), len, !inverse);
     }
 }
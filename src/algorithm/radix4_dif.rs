@@ -0,0 +1,185 @@
+use num_complex::Complex;
+
+use common::FFTnum;
+
+use twiddles;
+use ::{Length, IsInverse, Direction};
+
+/// Decimation-in-frequency FFT algorithm for power-of-two sizes, which takes its input in natural order and produces
+/// its output in bit-reversed order
+///
+/// For a FFT of size `len = 2^bits`, output index `i` holds bin `reverse_bits(i)`, where `reverse_bits` reverses the
+/// lowest `bits` bits of `i`. For example, with `len = 8`, the output holds the bins in the order
+/// `[0, 4, 2, 6, 1, 5, 3, 7]`. Bit reversal is its own inverse, so applying the same permutation to the output puts the
+/// bins in natural order.
+///
+/// `Radix4` is decimation-in-time: it reorders its input before the first layer of butterflies. This algorithm skips
+/// that reorder, so it's useful for chaining with a stage that accepts bit-reversed input, like a pointwise
+/// multiplication followed by a decimation-in-time inverse FFT that starts from bit-reversed order. Since its output
+/// isn't in natural order, it doesn't implement the `FFT` trait, and can't be used as the inner FFT of another
+/// algorithm.
+///
+/// Each layer is a radix-4 butterfly, with a final radix-2 layer when `bits` is odd. The radix-4 butterflies store their
+/// middle two outputs swapped, so that the result is bit-reversed rather than base-4 digit-reversed.
+///
+/// ~~~
+/// // Computes a forward FFT of size 4096, with the output in bit-reversed order
+/// use rustfft::algorithm::Radix4DIF;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 4096];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 4096];
+///
+/// let fft = Radix4DIF::new(4096, false);
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct Radix4DIF<T> {
+    twiddles: Box<[Complex<T>]>,
+    len: usize,
+    inverse: bool,
+}
+
+impl<T: FFTnum> Radix4DIF<T> {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the power-of-two FFT
+    pub fn new<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        assert!(len.is_power_of_two(), "Radix4DIF algorithm requires a power-of-two input size. Got {}", len);
+
+        // every layer uses twiddles of the form single_twiddle(j * len / layer_size, len), with j < 3/4 of the layer size,
+        // so one table of the first 3/4 of the twiddles of the full size covers all of them
+        let twiddles: Vec<Complex<T>> = (0..len * 3 / 4).map(|i| twiddles::single_twiddle(i, len, inverse)).collect();
+
+        Radix4DIF {
+            twiddles: twiddles.into_boxed_slice(),
+            len,
+            inverse,
+        }
+    }
+
+    /// Computes the FFT of `input`, and places it in `output` in bit-reversed order
+    ///
+    /// Both buffers must have length `self.len()`. Unlike `FFT::process`, this method doesn't modify `input`.
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        assert_eq!(input.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), input.len());
+        assert_eq!(output.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), output.len());
+
+        output.copy_from_slice(input);
+        self.perform_fft(output);
+    }
+
+    /// Computes the FFT of `buffer` in place, leaving the result in bit-reversed order
+    ///
+    /// `buffer` must have length `self.len()`.
+    pub fn process_inplace(&self, buffer: &mut [Complex<T>]) {
+        assert_eq!(buffer.len(), self.len(), "Buffer is the wrong length. Expected {}, got {}", self.len(), buffer.len());
+
+        self.perform_fft(buffer);
+    }
+
+    fn perform_fft(&self, buffer: &mut [Complex<T>]) {
+        let mut layer_size = self.len;
+
+        // each radix-4 layer splits every block into 4 blocks, each of which holds the FFT inputs for every 4th bin
+        while layer_size >= 4 {
+            let quarter = layer_size / 4;
+            let twiddle_stride = self.len / layer_size;
+
+            for block in buffer.chunks_mut(layer_size) {
+                for i in 0..quarter {
+                    let a = block[i];
+                    let b = block[i + quarter];
+                    let c = block[i + 2 * quarter];
+                    let d = block[i + 3 * quarter];
+
+                    let sum_ac = a + c;
+                    let diff_ac = a - c;
+                    let sum_bd = b + d;
+                    let rotated_diff_bd = twiddles::rotate_90(b - d, self.inverse);
+
+                    // store the outputs for bins 1 and 2 swapped, to produce bit-reversed rather than digit-reversed order
+                    block[i] = sum_ac + sum_bd;
+                    block[i + quarter] = (sum_ac - sum_bd) * self.twiddles[2 * i * twiddle_stride];
+                    block[i + 2 * quarter] = (diff_ac + rotated_diff_bd) * self.twiddles[i * twiddle_stride];
+                    block[i + 3 * quarter] = (diff_ac - rotated_diff_bd) * self.twiddles[3 * i * twiddle_stride];
+                }
+            }
+
+            layer_size = quarter;
+        }
+
+        // if the number of bits is odd, finish with a layer of size-2 butterflies, which need no twiddles
+        if layer_size == 2 {
+            for pair in buffer.chunks_mut(2) {
+                let a = pair[0];
+                pair[0] = a + pair[1];
+                pair[1] = a - pair[1];
+            }
+        }
+    }
+}
+impl<T> Length for Radix4DIF<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<T> IsInverse for Radix4DIF<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use algorithm::DFT;
+    use test_utils::{random_signal, compare_vectors};
+    use FFT;
+
+    fn reverse_bits(index: usize, bits: u32) -> usize {
+        (0..bits).fold(0, |reversed, bit| (reversed << 1) | ((index >> bit) & 1))
+    }
+
+    #[test]
+    fn test_radix4_dif() {
+        // test both odd and even powers of two, since odd powers end with a radix-2 layer
+        for bits in 0..11 {
+            let len = 1 << bits;
+            for &inverse in &[false, true] {
+                let signal = random_signal(len);
+
+                let mut expected = vec![Zero::zero(); len];
+                DFT::new(len, inverse).process(&mut signal.clone(), &mut expected);
+
+                let fft = Radix4DIF::new(len, inverse);
+                let mut bit_reversed = vec![Zero::zero(); len];
+                fft.process(&signal, &mut bit_reversed);
+
+                let mut inplace = signal.clone();
+                fft.process_inplace(&mut inplace);
+                assert_eq!(bit_reversed, inplace, "len = {}, inverse = {}", len, inverse);
+
+                // applying the documented reorder should give the bins in natural order
+                let actual: Vec<Complex<f32>> = (0..len).map(|i| bit_reversed[reverse_bits(i, bits)]).collect();
+                assert!(compare_vectors(&expected, &actual), "len = {}, inverse = {}", len, inverse);
+            }
+        }
+    }
+
+    #[test]
+    fn test_radix4_dif_order() {
+        // an impulse at input 1 produces the twiddles W^k, so the output order reveals which bin went where
+        let len = 8;
+        let mut buffer = vec![Zero::zero(); len];
+        buffer[1] = Complex::new(1f32, 0f32);
+        Radix4DIF::new(len, false).process_inplace(&mut buffer);
+
+        for (i, &bin) in [0, 4, 2, 6, 1, 5, 3, 7].iter().enumerate() {
+            let expected: Complex<f32> = twiddles::single_twiddle(bin, len, false);
+            assert!((buffer[i] - expected).norm() < 1e-6, "index {} should hold bin {}", i, bin);
+        }
+    }
+}
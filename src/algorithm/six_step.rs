@@ -0,0 +1,146 @@
+use std::cmp;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use common::{FFTnum, verify_length};
+
+use {FFT, Direction};
+use array_utils;
+use twiddles;
+
+/// Computes the twiddle factors for `six_step`, for a FFT of size `width * height`
+///
+/// The twiddle factor for column `x` of the output of the size-`height` FFTs, and row `y`, is `w^(x * y)`, where `w`
+/// is the twiddle factor for index 1 of a FFT of size `width * height`. They're stored in the transposed order that
+/// `six_step` visits them in, IE the factor for `(x, y)` is at index `x * height + y`. `direction` is a `Direction`, or
+/// a `bool` that's true for inverse FFTs.
+pub fn six_step_twiddles<T: FFTnum, D: Into<Direction>>(width: usize, height: usize, direction: D) -> Vec<Complex<T>> {
+    let inverse = direction.into().is_inverse();
+    let len = width * height;
+
+    let mut twiddles = Vec::with_capacity(len);
+    for x in 0..width {
+        for y in 0..height {
+            twiddles.push(twiddles::single_twiddle(x * y, len, inverse));
+        }
+    }
+    twiddles
+}
+
+/// Computes a FFT of size `width_fft.len() * height_fft.len()` with the six-step algorithm, which is the algorithm
+/// inside `MixedRadix`
+///
+/// The six steps are: transpose the input, compute FFTs of size `height` on each of the `width` rows of the transposed
+/// input, multiply by `twiddles`, transpose again, compute FFTs of size `width` on each of the `height` rows, and
+/// transpose back. `input` is used as scratch space between the steps, so its contents should be considered garbage
+/// afterwards. Nothing else is allocated, unless an inner FFT needs extra scratch space (see `FFT::extra_scratch_len`).
+///
+/// `twiddles` must have `width * height` elements. `six_step_twiddles` computes the ones that make this a FFT, but
+/// since the table is a parameter, it can be shared between several transforms, or replaced, for example to fold a
+/// window or a phase shift into it. Both inner FFTs must have the same direction as the twiddle factors.
+///
+/// ~~~
+/// // Compose a FFT of size 6 * 40 from a butterfly and a planned FFT
+/// use std::sync::Arc;
+/// use rustfft::FFTplanner;
+/// use rustfft::algorithm::{six_step, six_step_twiddles};
+/// use rustfft::algorithm::butterflies::Butterfly6;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let width_fft = Butterfly6::new(false);
+/// let height_fft = FFTplanner::new(false).plan_fft(40);
+/// let twiddles = six_step_twiddles(6, 40, false);
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 240];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 240];
+/// six_step(&width_fft, &*height_fft, &twiddles, &mut input, &mut output);
+/// ~~~
+pub fn six_step<T: FFTnum>(width_fft: &dyn FFT<T>, height_fft: &dyn FFT<T>, twiddles: &[Complex<T>], input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+    let len = width_fft.len() * height_fft.len();
+    assert_eq!(twiddles.len(), len, "The twiddle table is the wrong length. Expected {}, got {}", len, twiddles.len());
+    verify_length(input, output, len);
+
+    let mut scratch = vec![Zero::zero(); six_step_scratch_len(width_fft, height_fft)];
+    six_step_with_hook(width_fft, height_fft, twiddles, input, output, &mut scratch, |_| {});
+}
+
+// the extra scratch space `six_step_with_hook` needs: the inner FFTs run one after the other, so they can share it
+pub fn six_step_scratch_len<T: FFTnum>(width_fft: &dyn FFT<T>, height_fft: &dyn FFT<T>) -> usize {
+    cmp::max(width_fft.extra_scratch_len(), height_fft.extra_scratch_len())
+}
+
+// the six-step algorithm, calling `stage_hook` with the buffer after the size-`height` FFTs, after the twiddle factors,
+// and after the size-`width` FFTs. `scratch` is passed on to the inner FFTs. the lengths have already been checked
+#[inline(always)]
+pub fn six_step_with_hook<T, F>(width_fft: &dyn FFT<T>, height_fft: &dyn FFT<T>, twiddles: &[Complex<T>], input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>], mut stage_hook: F)
+    where T: FFTnum, F: FnMut(&[Complex<T>])
+{
+    let width = width_fft.len();
+    let height = height_fft.len();
+
+    // SIX STEP FFT:
+
+    // STEP 1: transpose
+    array_utils::transpose(width, height, input, output);
+
+    // STEP 2: perform FFTs of size `height`
+    height_fft.process_multi_with_scratch(output, input, scratch);
+    stage_hook(input);
+
+    // STEP 3: Apply twiddle factors
+    for (element, &twiddle) in input.iter_mut().zip(twiddles.iter()) {
+        *element = *element * twiddle;
+    }
+    stage_hook(input);
+
+    // STEP 4: transpose again
+    array_utils::transpose(height, width, input, output);
+
+    // STEP 5: perform FFTs of size `width`
+    width_fft.process_multi_with_scratch(output, input, scratch);
+    stage_hook(input);
+
+    // STEP 6: transpose again
+    array_utils::transpose(width, height, input, output);
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use algorithm::DFT;
+    use algorithm::butterflies::Butterfly4;
+    use test_utils::{random_signal, compare_vectors};
+    use FFTplanner;
+
+    #[test]
+    fn test_six_step() {
+        for &(width, height) in &[(1, 1), (4, 3), (3, 4), (4, 25), (16, 9)] {
+            for &inverse in &[false, true] {
+                let mut planner = FFTplanner::new(inverse);
+                let width_fft = planner.plan_fft(width);
+                let height_fft = planner.plan_fft(height);
+                let twiddles = six_step_twiddles(width, height, inverse);
+
+                let len = width * height;
+                let signal = random_signal(len);
+
+                let mut expected = vec![Zero::zero(); len];
+                DFT::new(len, inverse).process(&mut signal.clone(), &mut expected);
+
+                let mut actual = vec![Zero::zero(); len];
+                six_step(&*width_fft, &*height_fft, &twiddles, &mut signal.clone(), &mut actual);
+                assert!(compare_vectors(&expected, &actual), "width = {}, height = {}, inverse = {}", width, height, inverse);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "twiddle table")]
+    fn test_six_step_wrong_twiddles() {
+        let butterfly = Butterfly4::new(false);
+        let twiddles = six_step_twiddles(4, 3, false);
+        six_step(&butterfly, &butterfly, &twiddles, &mut random_signal(16), &mut random_signal(16));
+    }
+}
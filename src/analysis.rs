@@ -0,0 +1,302 @@
+use num_complex::Complex;
+use num_traits::{Zero, Float};
+
+use common::FFTnum;
+use number_theory::next_fast_len;
+
+use FFTplanner;
+
+/// Computes the circular autocorrelation of `signal`, IE `result[k] = sum(signal[(n + k) % len] * conj(signal[n]))`
+///
+/// The autocorrelation is computed as the inverse FFT of the squared magnitude of the FFT of `signal`, then scaled by
+/// `1 / len`, so the result matches the direct sum above. Since the correlation is circular, a lag of `k` is the same as
+/// a lag of `k - len`. To compute the autocorrelation of a signal that isn't periodic, use `autocorrelate_linear`.
+///
+/// ~~~
+/// // The autocorrelation of a periodic signal peaks at multiples of its period
+/// use rustfft::analysis::autocorrelate;
+/// use rustfft::num_complex::Complex;
+///
+/// let signal: Vec<Complex<f32>> = (0..32).map(|i| Complex::new(if i % 8 == 0 { 1f32 } else { 0f32 }, 0f32)).collect();
+/// let correlation = autocorrelate(&signal);
+///
+/// assert!((correlation[8].re - correlation[0].re).abs() < 1e-5);
+/// assert!(correlation[3].re.abs() < 1e-5);
+/// ~~~
+pub fn autocorrelate<T: FFTnum>(signal: &[Complex<T>]) -> Vec<Complex<T>> {
+    let len = signal.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut buffer = signal.to_vec();
+    let mut spectrum = vec![Zero::zero(); len];
+    FFTplanner::new(false).plan_fft(len).process(&mut buffer, &mut spectrum);
+
+    let scale = T::from_usize(len).unwrap();
+    for bin in spectrum.iter_mut() {
+        *bin = Complex::new(bin.norm_sqr() / scale, T::zero());
+    }
+
+    let mut correlation = vec![Zero::zero(); len];
+    FFTplanner::new(true).plan_fft(len).process(&mut spectrum, &mut correlation);
+    correlation
+}
+
+/// Computes the linear autocorrelation of `signal` for the lags `0..len`, IE
+/// `result[k] = sum(signal[n + k] * conj(signal[n]))`, where the sum only covers the indexes inside the signal
+///
+/// The signal is zero-padded to at least `2 * len - 1` elements before computing the circular autocorrelation, so
+/// that the correlation doesn't wrap around. The padded length is rounded up with `next_fast_len`. The lags `-k` are
+/// the complex conjugates of the lags `k`, so they aren't returned.
+pub fn autocorrelate_linear<T: FFTnum>(signal: &[Complex<T>]) -> Vec<Complex<T>> {
+    let len = signal.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut padded = signal.to_vec();
+    padded.resize(next_fast_len(2 * len - 1), Zero::zero());
+
+    let mut correlation = autocorrelate(&padded);
+    correlation.truncate(len);
+    correlation
+}
+
+/// Computes the real cepstrum of `signal`, IE the real part of `ifft(ln(|fft(signal)|))`, scaled by `1 / len`
+///
+/// An echo of the signal delayed by `lag` samples multiplies its spectrum by a ripple whose period in frequency is
+/// `len / lag`, and taking the logarithm turns that ripple into a sum, so the cepstrum has a peak at index `lag`.
+///
+/// `min_magnitude` is the floor for the magnitude of each bin before taking its logarithm. A bin that's exactly zero
+/// would otherwise produce `ln(0) = -inf`, and the inverse FFT would spread that to every element of the result. It
+/// must be greater than zero; a value well below the signal's quietest meaningful bin, such as `1e-10`, avoids
+/// distorting the rest of the cepstrum.
+pub fn real_cepstrum<T: FFTnum + Float>(signal: &[Complex<T>], min_magnitude: T) -> Vec<T> {
+    assert!(min_magnitude > T::zero(), "min_magnitude must be greater than zero");
+
+    let len = signal.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut buffer = signal.to_vec();
+    let mut spectrum = vec![Zero::zero(); len];
+    FFTplanner::new(false).plan_fft(len).process(&mut buffer, &mut spectrum);
+
+    let scale = T::from_usize(len).unwrap();
+    for bin in spectrum.iter_mut() {
+        *bin = Complex::new(bin.norm().max(min_magnitude).ln() / scale, T::zero());
+    }
+
+    let mut cepstrum = vec![Zero::zero(); len];
+    FFTplanner::new(true).plan_fft(len).process(&mut spectrum, &mut cepstrum);
+    cepstrum.iter().map(|element| element.re).collect()
+}
+
+/// Computes the group delay of `signal` at each bin of its FFT, IE the negative derivative of its phase with respect to
+/// frequency, in samples
+///
+/// Differentiating the unwrapped phase directly is fragile, because the unwrapping fails wherever the phase jumps. This
+/// uses the method of Smith and Oppenheim instead, which needs no unwrapping: with `X = fft(signal)` and
+/// `Y = fft(n * signal[n])`, the group delay of bin `k` is `re(Y[k] / X[k])`. Bins where `X[k]` is exactly zero have no
+/// phase, and their group delay is reported as 0.
+///
+/// ~~~
+/// // An impulse delayed by 5 samples delays every frequency by 5 samples
+/// use rustfft::analysis::group_delay;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut signal = vec![Complex::new(0f64, 0f64); 16];
+/// signal[5] = Complex::new(1f64, 0f64);
+///
+/// assert!(group_delay(&signal).iter().all(|delay| (delay - 5f64).abs() < 1e-10));
+/// ~~~
+pub fn group_delay<T: FFTnum + Float>(signal: &[Complex<T>]) -> Vec<T> {
+    let len = signal.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let fft = FFTplanner::new(false).plan_fft(len);
+
+    let mut buffer = signal.to_vec();
+    let mut spectrum = vec![Zero::zero(); len];
+    fft.process(&mut buffer, &mut spectrum);
+
+    let mut ramped: Vec<Complex<T>> = signal.iter().enumerate().map(|(n, &sample)| sample * T::from_usize(n).unwrap()).collect();
+    let mut ramped_spectrum = vec![Zero::zero(); len];
+    fft.process(&mut ramped, &mut ramped_spectrum);
+
+    // re(Y / X) = re(Y * conj(X)) / |X|^2, which avoids computing the imaginary part of the quotient
+    spectrum.iter().zip(ramped_spectrum.iter()).map(|(&x, &y)| {
+        let power = x.norm_sqr();
+        if power == T::zero() { T::zero() } else { (y * x.conj()).re / power }
+    }).collect()
+}
+
+/// Computes the analytic signal of the real signal `signal`, whose real part is `signal` and whose imaginary part is its
+/// Hilbert transform
+///
+/// The analytic signal is computed by taking the FFT of `signal`, doubling the positive frequencies, zeroing the
+/// negative frequencies, and taking the inverse FFT, scaled by `1 / len`. The DC bin, and for even lengths the Nyquist
+/// bin, are their own negative frequencies, so they're kept as they are. The magnitude of each element of the result is
+/// the instantaneous amplitude of the signal, IE its envelope, and the angle is its instantaneous phase.
+///
+/// ~~~
+/// // The analytic signal of a cosine is a complex exponential, so its envelope is flat
+/// use rustfft::analysis::hilbert;
+///
+/// let signal: Vec<f32> = (0..64).map(|i| (2f32 * std::f32::consts::PI * 5f32 * i as f32 / 64f32).cos()).collect();
+/// let analytic = hilbert(&signal);
+///
+/// assert!(analytic.iter().all(|element| (element.norm() - 1f32).abs() < 1e-4));
+/// ~~~
+pub fn hilbert<T: FFTnum>(signal: &[T]) -> Vec<Complex<T>> {
+    let len = signal.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut buffer: Vec<Complex<T>> = signal.iter().map(|&sample| Complex::new(sample, T::zero())).collect();
+    let mut spectrum = vec![Zero::zero(); len];
+    FFTplanner::new(false).plan_fft(len).process(&mut buffer, &mut spectrum);
+
+    // bins 1..(len + 1) / 2 are the positive frequencies, and the bins after them, except for the Nyquist bin of even
+    // lengths, are the negative frequencies
+    let scale = T::one() / T::from_usize(len).unwrap();
+    let double_scale = scale + scale;
+    let positive_end = (len + 1) / 2;
+    spectrum[0] = spectrum[0] * scale;
+    for bin in spectrum[1..positive_end].iter_mut() {
+        *bin = *bin * double_scale;
+    }
+    if len % 2 == 0 {
+        spectrum[len / 2] = spectrum[len / 2] * scale;
+    }
+    let negative_start = len / 2 + 1;
+    for bin in spectrum[negative_start..].iter_mut() {
+        *bin = Zero::zero();
+    }
+
+    let mut analytic = vec![Zero::zero(); len];
+    FFTplanner::new(true).plan_fft(len).process(&mut spectrum, &mut analytic);
+    analytic
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::{random_signal, compare_vectors};
+
+    fn naive_autocorrelate(signal: &[Complex<f32>], circular: bool) -> Vec<Complex<f32>> {
+        let len = signal.len();
+        (0..len).map(|k| {
+            (0..len).filter(|&n| circular || n + k < len)
+                .map(|n| signal[(n + k) % len] * signal[n].conj())
+                .fold(Zero::zero(), |sum: Complex<f32>, x| sum + x)
+        }).collect()
+    }
+
+    #[test]
+    fn test_autocorrelate() {
+        for &len in &[1, 2, 7, 16, 30] {
+            let signal = random_signal(len);
+
+            assert!(compare_vectors(&autocorrelate(&signal), &naive_autocorrelate(&signal, true)), "len = {}", len);
+            assert!(compare_vectors(&autocorrelate_linear(&signal), &naive_autocorrelate(&signal, false)), "len = {}", len);
+        }
+
+        assert!(autocorrelate::<f32>(&[]).is_empty());
+        assert!(autocorrelate_linear::<f32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_autocorrelate_periodic() {
+        let period = 10;
+        let signal: Vec<Complex<f32>> = (0..100).map(|i| {
+            let phase = 2f32 * ::std::f32::consts::PI * (i % period) as f32 / period as f32;
+            Complex::new(phase.sin() + 0.5 * (2f32 * phase).cos(), 0f32)
+        }).collect();
+
+        // ignoring lag 0, the strongest peak among the shorter lags should be at the period
+        for correlation in &[autocorrelate(&signal), autocorrelate_linear(&signal)] {
+            let peak_lag = (1..period * 3 / 2).max_by(|&a, &b| correlation[a].re.partial_cmp(&correlation[b].re).unwrap()).unwrap();
+            assert_eq!(peak_lag, period);
+        }
+    }
+
+    #[test]
+    fn test_real_cepstrum_echo() {
+        // a short burst, followed by a quieter copy of itself
+        let len = 256;
+        let lag = 40;
+        let burst = random_signal(16);
+        let mut signal = vec![Zero::zero(); len];
+        for (i, &sample) in burst.iter().enumerate() {
+            signal[i] = sample;
+            signal[i + lag] = sample * 0.6;
+        }
+
+        // ignoring the low quefrencies that describe the burst itself, the strongest peak should be at the echo lag
+        let cepstrum = real_cepstrum(&signal, 1e-10);
+        let peak = (burst.len()..len / 2).max_by(|&a, &b| cepstrum[a].partial_cmp(&cepstrum[b]).unwrap()).unwrap();
+        assert_eq!(peak, lag);
+        assert!(cepstrum.iter().all(|element| element.is_finite()));
+    }
+
+    #[test]
+    fn test_hilbert() {
+        for &len in &[64, 63, 100] {
+            let frequency = 5f32;
+            let phase = |i: usize| 2f32 * ::std::f32::consts::PI * frequency * i as f32 / len as f32;
+            let signal: Vec<f32> = (0..len).map(|i| phase(i).cos()).collect();
+
+            // the analytic signal of cos(phase) is exp(i * phase), so its imaginary part is sin(phase)
+            let expected: Vec<Complex<f32>> = (0..len).map(|i| Complex::from_polar(&1f32, &phase(i))).collect();
+            let analytic = hilbert(&signal);
+            assert!(compare_vectors(&expected, &analytic), "len = {}", len);
+            assert!(analytic.iter().all(|element| (element.norm() - 1f32).abs() < 1e-4), "len = {}", len);
+        }
+
+        // the real part of the analytic signal is always the signal itself, including its DC and Nyquist components
+        for &len in &[1, 2, 7, 16] {
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+            let real_part: Vec<f32> = hilbert(&signal).iter().map(|element| element.re).collect();
+            assert!(signal.iter().zip(real_part.iter()).all(|(a, b)| (a - b).abs() < 1e-4), "len = {}", len);
+        }
+
+        assert!(hilbert::<f32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_group_delay() {
+        for &(len, delay) in &[(1, 0), (8, 0), (16, 5), (30, 29), (97, 40)] {
+            let mut signal = vec![Zero::zero(); len];
+            signal[delay] = Complex::new(0.5f32, -0.25f32);
+
+            let group_delay = group_delay(&signal);
+            assert_eq!(group_delay.len(), len);
+            assert!(group_delay.iter().all(|&bin_delay| (bin_delay - delay as f32).abs() < 1e-3), "len = {}, delay = {}", len, delay);
+        }
+
+        // a symmetric filter with 5 taps has linear phase, so it delays every frequency by its center tap, even though
+        // its frequency response changes sign between two bins, making the phase jump by pi. its response is zero at
+        // the Nyquist frequency, so the length is odd, to keep that frequency out of the bins
+        let mut filter = vec![Zero::zero(); 31];
+        for (tap, &coefficient) in [1f32, 3f32, 4f32, 3f32, 1f32].iter().enumerate() {
+            filter[tap] = Complex::new(coefficient, 0f32);
+        }
+        assert!(group_delay(&filter).iter().all(|&bin_delay| (bin_delay - 2f32).abs() < 1e-3));
+
+        assert!(group_delay::<f32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_real_cepstrum_zero_bins() {
+        // every bin except DC is exactly zero, so the floor is what keeps the result finite
+        let signal = vec![Complex::new(1f32, 0f32); 8];
+        assert!(real_cepstrum(&signal, 1e-10).iter().all(|element| element.is_finite()));
+        assert!(real_cepstrum::<f32>(&[], 1e-10).is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 const BLOCK_SIZE: usize = 16;
 
-use common::verify_length;
+use num_complex::Complex;
+use num_traits::Zero;
+
+use common::{FFTnum, verify_length};
 
 #[inline(always)]
 unsafe fn transpose_block<T: Copy>(input: &[T], output: &mut [T], width: usize, height: usize, block_x: usize, block_y: usize) {
@@ -18,6 +21,7 @@ unsafe fn transpose_block<T: Copy>(input: &[T], output: &mut [T], width: usize,
 }
 
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 unsafe fn transpose_endcap_block<T: Copy>(input: &[T], output: &mut [T], width: usize, height: usize, block_x: usize, block_y: usize, block_width: usize, block_height: usize) {
     for inner_x in 0..block_width {
         for inner_y in 0..block_height {
@@ -34,6 +38,24 @@ unsafe fn transpose_endcap_block<T: Copy>(input: &[T], output: &mut [T], width:
 
 /// Given an array of size width * height, representing a flattened 2D array,
 /// transpose the rows and columns of that 2D array into the output
+///
+/// `input` is read as `height` rows of `width` elements each, and `output` is written as `width` rows of `height`
+/// elements each. Both buffers must have length `width * height`. Large matrices are transposed in square blocks, so
+/// that both buffers are accessed in a cache-friendly order.
+///
+/// ~~~
+/// // Transpose a matrix with 2 rows and 3 columns
+/// use rustfft::transpose;
+///
+/// let input = [1, 2, 3,
+///              4, 5, 6];
+/// let mut output = [0; 6];
+/// transpose(3, 2, &input, &mut output);
+///
+/// assert_eq!(output, [1, 4,
+///                     2, 5,
+///                     3, 6]);
+/// ~~~
 // Use "Loop tiling" to improve cache-friendliness
 pub fn transpose<T: Copy>(width: usize, height: usize, input: &[T], output: &mut [T]) {
     verify_length(input, output, width * height);
@@ -107,12 +129,88 @@ pub unsafe fn transpose_small<T: Copy>(width: usize, height: usize, input: &[T],
 }
 
 
+/// Rotates `data` in place by `k` elements: positive values of `k` move elements towards the end of the buffer, and
+/// negative values move them towards the start, with elements that fall off one end wrapping around to the other.
+///
+/// Shifts larger than the buffer wrap around, so shifting by `k` is the same as shifting by `k % data.len()`. The
+/// rotation is done by reversing the whole buffer and then reversing both sides of the split, so it doesn't allocate.
+///
+/// ~~~
+/// use rustfft::circular_shift;
+///
+/// let mut data = [0, 1, 2, 3, 4];
+/// circular_shift(&mut data, 2);
+/// assert_eq!(data, [3, 4, 0, 1, 2]);
+///
+/// circular_shift(&mut data, -3);
+/// assert_eq!(data, [1, 2, 3, 4, 0]);
+/// ~~~
+pub fn circular_shift<T>(data: &mut [T], k: isize) {
+    let len = data.len();
+    if len == 0 {
+        return;
+    }
+
+    // convert the shift into an equivalent shift to the right, in 0..len
+    let shift = k.rem_euclid(len as isize) as usize;
+
+    data.reverse();
+    let (left, right) = data.split_at_mut(shift);
+    left.reverse();
+    right.reverse();
+}
+
+/// Sets every element of `buffer` to zero, for example to clear a scratch buffer between frames
+///
+/// This is a plain loop, which the compiler turns into a `memset` for `f32` and `f64`, since their zero is all zero
+/// bits. Unlike writing the bits directly, it's also correct for scalar types whose zero isn't all zero bits.
+///
+/// ~~~
+/// use rustfft::zero_fill;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex::new(1f32, -2f32); 4];
+/// zero_fill(&mut buffer);
+/// assert_eq!(buffer, vec![Complex::new(0f32, 0f32); 4]);
+/// ~~~
+pub fn zero_fill<T: FFTnum>(buffer: &mut [Complex<T>]) {
+    for element in buffer.iter_mut() {
+        *element = Zero::zero();
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
     use test_utils::random_signal;
-    use num_complex::Complex;
-    use num_traits::Zero;
+
+    #[test]
+    fn test_zero_fill() {
+        let mut buffer = random_signal(100);
+        buffer[0] = Complex::new(-0f32, -0f32);
+        buffer[1] = Complex::new(f32::NAN, f32::INFINITY);
+        buffer[2] = Complex::new(f32::MAX, f32::NEG_INFINITY);
+        zero_fill(&mut buffer);
+
+        // compare the bits, so that negative zero doesn't count as zero
+        assert!(buffer.iter().all(|element| element.re.to_bits() == 0 && element.im.to_bits() == 0));
+
+        zero_fill::<f64>(&mut []);
+    }
+
+    #[test]
+    fn test_transpose_known_values() {
+        // a wide matrix with 3 rows and 4 columns, and a tall matrix with 4 rows and 3 columns
+        let wide: Vec<usize> = (0..12).collect();
+        let mut tall = vec![0; 12];
+        transpose(4, 3, &wide, &mut tall);
+        assert_eq!(tall, vec![0, 4, 8, 1, 5, 9, 2, 6, 10, 3, 7, 11]);
+
+        // transposing back should restore the original
+        let mut round_trip = vec![0; 12];
+        transpose(3, 4, &tall, &mut round_trip);
+        assert_eq!(round_trip, wide);
+    }
 
     #[test]
     fn test_transpose() {
@@ -144,4 +242,36 @@ mod unit_tests {
             }
         }
     }
+
+    #[test]
+    fn test_circular_shift() {
+        let original: Vec<usize> = (0..7).collect();
+        let test_list: Vec<(isize, Vec<usize>)> = vec![
+            (0, vec![0, 1, 2, 3, 4, 5, 6]),
+            (1, vec![6, 0, 1, 2, 3, 4, 5]),
+            (3, vec![4, 5, 6, 0, 1, 2, 3]),
+            (-1, vec![1, 2, 3, 4, 5, 6, 0]),
+            (-3, vec![3, 4, 5, 6, 0, 1, 2]),
+            (7, vec![0, 1, 2, 3, 4, 5, 6]),
+            (10, vec![4, 5, 6, 0, 1, 2, 3]),
+            (-10, vec![3, 4, 5, 6, 0, 1, 2]),
+        ];
+
+        for (k, expected) in test_list {
+            let mut data = original.clone();
+            circular_shift(&mut data, k);
+            assert_eq!(data, expected, "k = {}", k);
+        }
+
+        // empty buffers have nothing to rotate
+        let mut empty: Vec<Complex<f32>> = Vec::new();
+        circular_shift(&mut empty, 3);
+
+        // shifting complex data one way and then back should be a no-op
+        let signal = random_signal(20);
+        let mut shifted = signal.clone();
+        circular_shift(&mut shifted, 13);
+        circular_shift(&mut shifted, -13);
+        assert_eq!(shifted, signal);
+    }
 }
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use num_complex::Complex;
+use num_integer::gcd;
+use num_traits::Zero;
+
+use common::FFTnum;
+
+use algorithm::*;
+use math_utils;
+
+use {FFT, FFTplanner, Direction};
+
+/// Returns the total time taken to run `fft.process` `iters` times
+///
+/// The input is a fixed, deterministic test signal, which is copied into a fresh input buffer before each call, so the
+/// copy is included in the timing. The buffers are allocated before timing starts.
+pub fn time_algorithm<T: FFTnum>(fft: &dyn FFT<T>, iters: usize) -> Duration {
+    let len = fft.len();
+    let signal: Vec<Complex<T>> = (0..len).map(|i| {
+        let phase = i as f64 * 0.1;
+        Complex::new(T::from_f64(phase.sin()).unwrap(), T::from_f64(phase.cos()).unwrap())
+    }).collect();
+
+    let mut input = signal.clone();
+    let mut output = vec![Zero::zero(); len];
+
+    let start = Instant::now();
+    for _ in 0..iters {
+        input.copy_from_slice(&signal);
+        fft.process(&mut input, &mut output);
+    }
+    start.elapsed()
+}
+
+/// Times the FFT that the planner chooses for `len` against the other algorithms that can compute a FFT of that size,
+/// prints a table of the results, and returns them as `(algorithm name, time per FFT)` pairs, with the planner's choice first
+///
+/// The alternatives are `Radix4` for powers of two, `RadersAlgorithm` with and without a padded inner convolution for
+/// primes, `MixedRadix` (and `GoodThomasAlgorithm` when possible) split as close to `sqrt(len)` as the factors allow for
+/// composite sizes, and `DFT` for sizes up to 1024.
+/// Inner FFTs are planned by the planner. The number of iterations is chosen so that each algorithm runs for a similar
+/// amount of work. `direction` is a `Direction`, or a `bool` that's true for inverse FFTs.
+pub fn compare_plans<T: FFTnum, D: Into<Direction>>(len: usize, direction: D) -> Vec<(String, Duration)> {
+    let inverse = direction.into().is_inverse();
+    let mut planner = FFTplanner::new(inverse);
+    let mut candidates: Vec<(String, Arc<dyn FFT<T>>)> = vec![("planner".to_owned(), planner.plan_fft(len))];
+
+    if len >= 2 {
+        if len.is_power_of_two() {
+            candidates.push(("Radix4".to_owned(), Arc::new(Radix4::new(len, inverse))));
+        }
+        if math_utils::is_prime(len as u64) {
+            let inner_fft = planner.plan_fft(len - 1);
+            candidates.push(("RadersAlgorithm".to_owned(), Arc::new(RadersAlgorithm::new(len, inner_fft))));
+            let padded_fft = planner.plan_fft((2 * len - 3).next_power_of_two());
+            candidates.push(("RadersAlgorithm (Bluestein)".to_owned(), Arc::new(RadersAlgorithm::new_with_bluestein_inner(len, padded_fft))));
+        } else {
+            // find the divisor closest to sqrt(len)
+            let width = (1..len + 1).take_while(|width| width * width <= len).filter(|width| len % width == 0).last().unwrap();
+            let height = len / width;
+            if width > 1 {
+                candidates.push((format!("MixedRadix({}x{})", width, height),
+                    Arc::new(MixedRadix::new(planner.plan_fft(width), planner.plan_fft(height)))));
+                if gcd(width, height) == 1 {
+                    candidates.push((format!("GoodThomasAlgorithm({}x{})", width, height),
+                        Arc::new(GoodThomasAlgorithm::new(planner.plan_fft(width), planner.plan_fft(height)))));
+                }
+            }
+        }
+    }
+    if len <= 1024 {
+        candidates.push(("DFT".to_owned(), Arc::new(DFT::new(len, inverse))));
+    }
+
+    let iters = ((1 << 20) / len.max(1)).max(1);
+    let results: Vec<(String, Duration)> = candidates.into_iter()
+        .map(|(name, fft)| (name, time_algorithm(&*fft, iters) / iters as u32))
+        .collect();
+
+    println!("FFT size {}, {} iterations each:", len, iters);
+    println!("{:<40} {:>16}", "algorithm", "time per FFT");
+    for &(ref name, time) in &results {
+        println!("{:<40} {:>16?}", name, time);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_plans() {
+        for &len in &[1, 16, 17, 30] {
+            let results = compare_plans::<f32, _>(len, false);
+            assert_eq!(results[0].0, "planner");
+        }
+    }
+}
@@ -1,12 +1,110 @@
+use std::error::Error;
+use std::fmt;
+
 use num_traits::{FromPrimitive, Signed};
 
 /// Generic floating point number, implemnted for f32 and f64
+///
+/// The FFT algorithms only rely on the bounds of this trait, so it can also be implemented for custom scalar types,
+/// like a higher-precision float. A custom type needs to implement:
+///
+/// - `Copy`, `Send`, `Sync`, and `'static`
+/// - `num_traits::Signed`, which also requires `num_traits::Num` and `std::ops::Neg`. `Num` in turn requires
+///   `PartialEq`, `Zero`, `One`, and the arithmetic operators `+ - * / %`
+/// - `num_traits::FromPrimitive`. Twiddle factors are computed as `f64` and converted with `from_f64`, so make sure to
+///   override `from_f64`: the default implementation goes through `from_i64`, which truncates to an integer
+///
+/// Once those are implemented, `impl FFTnum for MyFloat {}` is enough to use `MyFloat` with the planner and every FFT
+/// algorithm. Some helpers outside of the FFT algorithms, like the ones in the `spectrum` module, additionally require
+/// `num_traits::Float`.
 pub trait FFTnum: Copy + FromPrimitive + Signed + Sync + Send + 'static {}
 
 impl FFTnum for f32 {}
 impl FFTnum for f64 {}
 
+/// Returns the machine epsilon of `T`, IE the difference between 1 and the next larger value of `T`, as a `f64`
+///
+/// This only relies on the arithmetic of `FFTnum`, so it also works for custom scalar types, as long as they round to
+/// a binary float. It gives up after 256 halvings, so types with more precision than that report 2^-256.
+pub fn epsilon<T: FFTnum>() -> f64 {
+	let one = T::one();
+	let half = T::from_f64(0.5).unwrap();
+
+	let mut epsilon = one;
+	let mut result = 1f64;
+	for _ in 0..256 {
+		let half_epsilon = epsilon * half;
+		if one + half_epsilon == one {
+			break;
+		}
+		epsilon = half_epsilon;
+		result *= 0.5;
+	}
+	result
+}
 
+/// Returns `2 * pi`, computed as a `f64` and then converted to `T`, so that every type gets as much precision as it can hold
+#[inline(always)]
+pub fn two_pi<T: FFTnum>() -> T {
+	T::from_f64(2f64 * ::std::f64::consts::PI).unwrap()
+}
+
+/// Returns the number of complex multiply-adds in a radix-2 FFT of size `len`, IE `len * log2(len) / 2`, rounded to the
+/// nearest integer. This is how `FFT::operation_count` counts algorithms that don't count their own operations.
+pub fn radix2_operation_count(len: usize) -> usize {
+	if len < 2 {
+		0
+	} else {
+		(len as f64 * (len as f64).log2() / 2f64).round() as usize
+	}
+}
+
+
+/// Panics if `input` or `output` doesn't have length `expected`
+///
+/// The FFT algorithms in this crate call this at the start of `process`, so custom algorithms can use it to report
+/// buffer size mistakes with the same messages:
+///
+/// ~~~
+/// use std::sync::Arc;
+/// use rustfft::{FFT, Length, IsInverse, verify_length, verify_length_divisible};
+/// use rustfft::num_complex::Complex;
+///
+/// // A size-2 FFT. Forward and inverse size-2 FFTs are the same, so the direction only matters for `is_inverse`
+/// struct MyButterfly2 {
+///     inverse: bool,
+/// }
+///
+/// impl FFT<f32> for MyButterfly2 {
+///     fn process(&self, input: &mut [Complex<f32>], output: &mut [Complex<f32>]) {
+///         verify_length(input, output, self.len());
+///
+///         output[0] = input[0] + input[1];
+///         output[1] = input[0] - input[1];
+///     }
+///     fn process_multi(&self, input: &mut [Complex<f32>], output: &mut [Complex<f32>]) {
+///         verify_length_divisible(input, output, self.len());
+///
+///         for (in_chunk, out_chunk) in input.chunks_mut(2).zip(output.chunks_mut(2)) {
+///             self.process(in_chunk, out_chunk);
+///         }
+///     }
+///     fn inverse_of(&self) -> Arc<dyn FFT<f32>> {
+///         Arc::new(MyButterfly2 { inverse: !self.inverse })
+///     }
+/// }
+/// impl Length for MyButterfly2 {
+///     fn len(&self) -> usize { 2 }
+/// }
+/// impl IsInverse for MyButterfly2 {
+///     fn is_inverse(&self) -> bool { self.inverse }
+/// }
+///
+/// let mut input = vec![Complex::new(1f32, 0f32), Complex::new(2f32, 0f32)];
+/// let mut output = vec![Complex::new(0f32, 0f32); 2];
+/// MyButterfly2 { inverse: false }.process(&mut input, &mut output);
+/// assert_eq!(output, vec![Complex::new(3f32, 0f32), Complex::new(-1f32, 0f32)]);
+/// ~~~
 #[inline(always)]
 pub fn verify_length<T>(input: &[T], output: &[T], expected: usize) {
 	assert_eq!(input.len(), expected, "Input is the wrong length. Expected {}, got {}", expected, input.len());
@@ -14,8 +112,136 @@ pub fn verify_length<T>(input: &[T], output: &[T], expected: usize) {
 }
 
 
+/// Panics if `input` doesn't have a length that's a multiple of `expected`, or if `output` doesn't have the same length
+/// as `input`
+///
+/// This is the check that the FFT algorithms in this crate use for `process_multi`.
 #[inline(always)]
 pub fn verify_length_divisible<T>(input: &[T], output: &[T], expected: usize) {
-	assert_eq!(input.len() % expected, 0, "Input is the wrong length. Expected multiple of {}, got {}", expected, input.len());
+	assert_eq!(input.len() % expected, 0, "Input is the wrong length. Expected multiple of {}, got {}, which has {} elements left over", expected, input.len(), input.len() % expected);
 	assert_eq!(input.len(), output.len(), "Input and output must have the same length. Expected {}, got {}", input.len(), output.len());
 }
+
+
+/// The ways that a pair of buffers can fail a length check
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LengthError {
+	/// The input buffer's length was `actual` instead of `expected`
+	Input { expected: usize, actual: usize },
+	/// The output buffer's length was `actual` instead of `expected`
+	Output { expected: usize, actual: usize },
+	/// The input buffer's length, `actual`, wasn't a multiple of `expected`, leaving `remainder` extra elements
+	InputNotDivisible { expected: usize, actual: usize, remainder: usize },
+	/// The input and output buffers had different lengths
+	Mismatched { input: usize, output: usize },
+}
+
+impl fmt::Display for LengthError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			LengthError::Input { expected, actual } => write!(f, "Input is the wrong length. Expected {}, got {}", expected, actual),
+			LengthError::Output { expected, actual } => write!(f, "Output is the wrong length. Expected {}, got {}", expected, actual),
+			LengthError::InputNotDivisible { expected, actual, remainder } => write!(f, "Input is the wrong length. Expected multiple of {}, got {}, which has {} elements left over", expected, actual, remainder),
+			LengthError::Mismatched { input, output } => write!(f, "Input and output must have the same length. Expected {}, got {}", input, output),
+		}
+	}
+}
+
+impl Error for LengthError {}
+
+
+/// The ways that `FFT::process_checked` can reject its buffers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FFTError {
+	/// The buffers failed a length check
+	Length(LengthError),
+	/// The input held a NaN or infinite value, and `index` is the first element that did
+	NonFinite { index: usize },
+}
+
+impl fmt::Display for FFTError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			FFTError::Length(error) => error.fmt(f),
+			FFTError::NonFinite { index } => write!(f, "Input has a NaN or infinite value at index {}", index),
+		}
+	}
+}
+
+impl Error for FFTError {}
+
+impl From<LengthError> for FFTError {
+	fn from(error: LengthError) -> Self {
+		FFTError::Length(error)
+	}
+}
+
+
+/// Performs the same check as `verify_length`, but returns an error instead of panicking
+#[inline(always)]
+pub fn try_verify_length<T>(input: &[T], output: &[T], expected: usize) -> Result<(), LengthError> {
+	if input.len() != expected {
+		Err(LengthError::Input { expected, actual: input.len() })
+	} else if output.len() != expected {
+		Err(LengthError::Output { expected, actual: output.len() })
+	} else {
+		Ok(())
+	}
+}
+
+
+/// Performs the same check as `verify_length_divisible`, but returns an error instead of panicking
+#[inline(always)]
+pub fn try_verify_length_divisible<T>(input: &[T], output: &[T], expected: usize) -> Result<(), LengthError> {
+	if input.len() % expected != 0 {
+		Err(LengthError::InputNotDivisible { expected, actual: input.len(), remainder: input.len() % expected })
+	} else if input.len() != output.len() {
+		Err(LengthError::Mismatched { input: input.len(), output: output.len() })
+	} else {
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	#[test]
+	fn test_epsilon() {
+		assert_eq!(epsilon::<f32>(), f32::EPSILON as f64);
+		assert_eq!(epsilon::<f64>(), f64::EPSILON);
+	}
+
+	#[test]
+	fn test_radix2_operation_count() {
+		assert_eq!(radix2_operation_count(0), 0);
+		assert_eq!(radix2_operation_count(1), 0);
+		assert_eq!(radix2_operation_count(2), 1);
+		assert_eq!(radix2_operation_count(1024), 5120);
+		assert_eq!(radix2_operation_count(3), 2);
+	}
+
+	#[test]
+	fn test_two_pi() {
+		assert_eq!(two_pi::<f64>(), ::std::f64::consts::PI * 2.0);
+		assert!((two_pi::<f32>() - ::std::f32::consts::PI * 2.0).abs() <= f32::EPSILON);
+	}
+
+	#[test]
+	fn test_try_verify_length() {
+		let buffer = [0u8; 8];
+
+		assert_eq!(try_verify_length(&buffer[..], &buffer[..], 8), Ok(()));
+		assert_eq!(try_verify_length(&buffer[..4], &buffer[..], 8), Err(LengthError::Input { expected: 8, actual: 4 }));
+		assert_eq!(try_verify_length(&buffer[..], &buffer[..4], 8), Err(LengthError::Output { expected: 8, actual: 4 }));
+
+		assert_eq!(try_verify_length_divisible(&buffer[..], &buffer[..], 4), Ok(()));
+		assert_eq!(try_verify_length_divisible(&buffer[..6], &buffer[..6], 4), Err(LengthError::InputNotDivisible { expected: 4, actual: 6, remainder: 2 }));
+		assert_eq!(try_verify_length_divisible(&buffer[..8], &buffer[..4], 4), Err(LengthError::Mismatched { input: 8, output: 4 }));
+
+		assert_eq!(LengthError::Input { expected: 8, actual: 4 }.to_string(), "Input is the wrong length. Expected 8, got 4");
+		assert_eq!(LengthError::InputNotDivisible { expected: 4, actual: 6, remainder: 2 }.to_string(),
+			"Input is the wrong length. Expected multiple of 4, got 6, which has 2 elements left over");
+	}
+}
@@ -0,0 +1,483 @@
+use std::sync::Arc;
+use std::cmp;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use common::{FFTnum, radix2_operation_count};
+use number_theory::next_fast_len;
+
+use {FFT, FFTplanner};
+
+/// Computes the circular convolutions of a signal with each of a fixed set of kernels
+///
+/// The spectra of the kernels are computed once, when the filter bank is created. Each call to `process` computes one
+/// forward FFT of the signal, multiplies its spectrum by each kernel's spectrum, and computes the inverse FFTs of all
+/// the products with a single `process_multi` call. The kernel spectra are scaled by `1 / len` ahead of time, so the
+/// results are the convolutions themselves, not `len` times the convolutions.
+///
+/// ~~~
+/// // Convolve a signal of size 8 with two kernels
+/// use rustfft::convolution::FilterBank;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let identity = vec![Complex::new(1f32, 0f32)];
+/// let delay = vec![Complex::zero(), Complex::new(1f32, 0f32)];
+/// let filter_bank = FilterBank::new(8, &[identity, delay]);
+///
+/// let signal: Vec<Complex<f32>> = (0..8).map(|i| Complex::new(i as f32, 0f32)).collect();
+/// let outputs = filter_bank.process(&signal);
+///
+/// assert_eq!(outputs.len(), 2);
+/// assert!((outputs[1][0].re - 7f32).abs() < 1e-4); // the delay wraps the last element around to the front
+/// ~~~
+pub struct FilterBank<T> {
+    forward_fft: Arc<dyn FFT<T>>,
+    inverse_fft: Arc<dyn FFT<T>>,
+    kernel_spectra: Box<[Complex<T>]>,
+    num_kernels: usize,
+}
+
+impl<T: FFTnum> FilterBank<T> {
+    /// Precomputes the spectra of `kernels`, for convolving signals of size `len`
+    ///
+    /// Each kernel must have at most `len` elements. Shorter kernels are zero-padded to `len`.
+    pub fn new(len: usize, kernels: &[Vec<Complex<T>>]) -> Self {
+        let forward_fft = FFTplanner::new(false).plan_fft(len);
+        let inverse_fft = FFTplanner::new(true).plan_fft(len);
+
+        let mut padded_kernels = vec![Zero::zero(); len * kernels.len()];
+        for (kernel, padded_kernel) in kernels.iter().zip(padded_kernels.chunks_mut(len.max(1))) {
+            assert!(kernel.len() <= len, "Kernels must not be longer than the signal. Expected at most {}, got {}", len, kernel.len());
+            padded_kernel[..kernel.len()].copy_from_slice(kernel);
+        }
+
+        let mut kernel_spectra = vec![Zero::zero(); len * kernels.len()];
+        if len > 0 {
+            forward_fft.process_multi(&mut padded_kernels, &mut kernel_spectra);
+        }
+
+        // fold the 1 / len normalization of the inverse FFT into the kernel spectra
+        let scale = T::one() / T::from_usize(len.max(1)).unwrap();
+        for element in kernel_spectra.iter_mut() {
+            *element = *element * scale;
+        }
+
+        FilterBank {
+            forward_fft,
+            inverse_fft,
+            kernel_spectra: kernel_spectra.into_boxed_slice(),
+            num_kernels: kernels.len(),
+        }
+    }
+
+    /// Returns the circular convolution of `signal` with each kernel, in the order the kernels were passed to `new`
+    ///
+    /// `signal` must have the length the filter bank was created with.
+    pub fn process(&self, signal: &[Complex<T>]) -> Vec<Vec<Complex<T>>> {
+        let len = self.forward_fft.len();
+        assert_eq!(signal.len(), len, "Input is the wrong length. Expected {}, got {}", len, signal.len());
+
+        if len == 0 {
+            return vec![Vec::new(); self.num_kernels];
+        }
+
+        let mut input = signal.to_vec();
+        let mut signal_spectrum = vec![Zero::zero(); len];
+        self.forward_fft.process(&mut input, &mut signal_spectrum);
+
+        let mut products: Vec<Complex<T>> = self.kernel_spectra.chunks(len)
+            .flat_map(|kernel_spectrum| kernel_spectrum.iter().zip(signal_spectrum.iter()).map(|(&k, &s)| k * s))
+            .collect();
+        let mut outputs = vec![Zero::zero(); products.len()];
+        self.inverse_fft.process_multi(&mut products, &mut outputs);
+
+        outputs.chunks(len).map(|output| output.to_vec()).collect()
+    }
+
+    /// The number of kernels in this filter bank
+    pub fn num_kernels(&self) -> usize {
+        self.num_kernels
+    }
+
+    /// The size of the signals this filter bank convolves
+    pub fn len(&self) -> usize {
+        self.forward_fft.len()
+    }
+
+    /// Returns true if this filter bank convolves signals of size 0
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Filters signals by multiplying their spectra by a fixed frequency response, IE computes `ifft(response * fft(signal))`
+///
+/// The forward and inverse FFTs are planned once, when the filter is created, and the frequency response is scaled by
+/// `1 / len` ahead of time, so a response of all ones passes signals through unchanged. The filter owns the scratch
+/// buffers it needs, including any extra scratch space its FFTs need (see `FFT::extra_scratch_len`), so processing
+/// doesn't allocate.
+///
+/// ~~~
+/// // Remove everything but the DC component of a signal of size 4
+/// use rustfft::convolution::FFTfilter;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let response = vec![Complex::new(1f32, 0f32), Complex::zero(), Complex::zero(), Complex::zero()];
+/// let mut filter = FFTfilter::new(response);
+///
+/// let signal = vec![Complex::new(1f32, 0f32), Complex::new(3f32, 0f32), Complex::new(1f32, 0f32), Complex::new(3f32, 0f32)];
+/// let mut output = vec![Complex::zero(); 4];
+/// filter.process(&signal, &mut output);
+///
+/// assert!(output.iter().all(|element| (element.re - 2f32).abs() < 1e-5));
+/// ~~~
+pub struct FFTfilter<T> {
+    forward_fft: Arc<dyn FFT<T>>,
+    inverse_fft: Arc<dyn FFT<T>>,
+    response: Box<[Complex<T>]>,
+    scratch: Vec<Complex<T>>,
+    extra_scratch: Vec<Complex<T>>,
+}
+
+impl<T: FFTnum> FFTfilter<T> {
+    /// Creates a filter for signals of size `response.len()`, which multiplies bin `k` of their spectrum by `response[k]`
+    pub fn new(mut response: Vec<Complex<T>>) -> Self {
+        let len = response.len();
+        let forward_fft = FFTplanner::new(false).plan_fft(len);
+        let inverse_fft = forward_fft.inverse_of();
+
+        // fold the 1 / len normalization of the inverse FFT into the response
+        let scale = T::one() / T::from_usize(len.max(1)).unwrap();
+        for element in response.iter_mut() {
+            *element = *element * scale;
+        }
+
+        let extra_scratch_len = cmp::max(forward_fft.extra_scratch_len(), inverse_fft.extra_scratch_len());
+        FFTfilter {
+            forward_fft,
+            inverse_fft,
+            response: response.into_boxed_slice(),
+            scratch: vec![Zero::zero(); len],
+            extra_scratch: vec![Zero::zero(); extra_scratch_len],
+        }
+    }
+
+    /// Filters `input`, and places the result in `output`
+    ///
+    /// Both buffers must have the length of the frequency response. Unlike `FFT::process`, this doesn't modify `input`.
+    pub fn process(&mut self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        let len = self.len();
+        assert_eq!(input.len(), len, "Input is the wrong length. Expected {}, got {}", len, input.len());
+        assert_eq!(output.len(), len, "Output is the wrong length. Expected {}, got {}", len, output.len());
+
+        // process_multi_with_scratch can't divide an empty buffer into chunks of size 0
+        if len == 0 {
+            return;
+        }
+
+        self.scratch.copy_from_slice(input);
+        self.forward_fft.process_multi_with_scratch(&mut self.scratch, output, &mut self.extra_scratch);
+
+        for (bin, &response) in output.iter_mut().zip(self.response.iter()) {
+            *bin = *bin * response;
+        }
+
+        self.inverse_fft.process_multi_with_scratch(output, &mut self.scratch, &mut self.extra_scratch);
+        output.copy_from_slice(&self.scratch);
+    }
+
+    /// The size of the signals this filter processes
+    pub fn len(&self) -> usize {
+        self.response.len()
+    }
+
+    /// Returns true if this filter processes signals of size 0
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Computes linear convolutions of signals of any length with a fixed kernel, using the overlap-add method
+///
+/// The signal is split into blocks of `block_len()` elements. Each block is zero-padded to `fft_len()`, multiplied by
+/// the kernel's spectrum, and transformed back, and the results are added together at the offsets of their blocks. The
+/// kernel spectrum is computed once, when the convolver is created, and scaled by `1 / fft_len` ahead of time.
+///
+/// Choosing the FFT size is a tradeoff: small FFTs waste most of each block on the overlap with the next one, and large
+/// FFTs cost more per element. `auto` makes that choice based on the kernel's length.
+///
+/// ~~~
+/// // Convolve a signal of size 100 with a 3-tap moving sum
+/// use rustfft::convolution::OverlapAdd;
+/// use rustfft::num_complex::Complex;
+///
+/// let kernel = vec![Complex::new(1f32, 0f32); 3];
+/// let convolver = OverlapAdd::auto(&kernel);
+///
+/// let signal = vec![Complex::new(1f32, 0f32); 100];
+/// let output = convolver.process(&signal);
+///
+/// assert_eq!(output.len(), 102);
+/// assert!((output[50].re - 3f32).abs() < 1e-4);
+/// ~~~
+pub struct OverlapAdd<T> {
+    forward_fft: Arc<dyn FFT<T>>,
+    inverse_fft: Arc<dyn FFT<T>>,
+    kernel_spectrum: Box<[Complex<T>]>,
+    kernel_len: usize,
+}
+
+impl<T: FFTnum> OverlapAdd<T> {
+    /// Precomputes the spectrum of `kernel`, for convolving with FFTs of size `fft_len`
+    ///
+    /// `kernel` must not be empty, and `fft_len` must be at least `kernel.len()`. Each FFT consumes
+    /// `fft_len - kernel.len() + 1` elements of the signal.
+    pub fn new(kernel: &[Complex<T>], fft_len: usize) -> Self {
+        assert!(!kernel.is_empty(), "The kernel must not be empty");
+        assert!(fft_len >= kernel.len(), "The FFT is too short for the kernel. Expected at least {}, got {}", kernel.len(), fft_len);
+
+        let mut planner = FFTplanner::new(false);
+        let forward_fft = planner.plan_fft(fft_len);
+        let inverse_fft = forward_fft.inverse_of();
+
+        let mut padded_kernel = vec![Zero::zero(); fft_len];
+        padded_kernel[..kernel.len()].copy_from_slice(kernel);
+        let mut kernel_spectrum = vec![Zero::zero(); fft_len];
+        forward_fft.process(&mut padded_kernel, &mut kernel_spectrum);
+
+        // fold the 1 / len normalization of the inverse FFT into the kernel spectrum
+        let scale = T::one() / T::from_usize(fft_len).unwrap();
+        for element in kernel_spectrum.iter_mut() {
+            *element = *element * scale;
+        }
+
+        OverlapAdd {
+            forward_fft,
+            inverse_fft,
+            kernel_spectrum: kernel_spectrum.into_boxed_slice(),
+            kernel_len: kernel.len(),
+        }
+    }
+
+    /// Precomputes the spectrum of `kernel`, with the FFT size that `overlap_add_fft_len` estimates to be cheapest
+    pub fn auto(kernel: &[Complex<T>]) -> Self {
+        Self::new(kernel, overlap_add_fft_len(kernel.len()))
+    }
+
+    /// Returns the linear convolution of `signal` with the kernel, which has `signal.len() + kernel_len - 1` elements,
+    /// or none if `signal` is empty
+    pub fn process(&self, signal: &[Complex<T>]) -> Vec<Complex<T>> {
+        if signal.is_empty() {
+            return Vec::new();
+        }
+
+        let fft_len = self.fft_len();
+        let mut output = vec![Zero::zero(); signal.len() + self.kernel_len - 1];
+        let mut buffer = vec![Zero::zero(); fft_len];
+        let mut spectrum = vec![Zero::zero(); fft_len];
+
+        for (block_index, block) in signal.chunks(self.block_len()).enumerate() {
+            buffer[..block.len()].copy_from_slice(block);
+            for element in buffer[block.len()..].iter_mut() {
+                *element = Zero::zero();
+            }
+            self.forward_fft.process(&mut buffer, &mut spectrum);
+
+            for (bin, &kernel_bin) in spectrum.iter_mut().zip(self.kernel_spectrum.iter()) {
+                *bin = *bin * kernel_bin;
+            }
+            self.inverse_fft.process(&mut spectrum, &mut buffer);
+
+            // only the first block.len() + kernel_len - 1 elements of the result are nonzero
+            let start = block_index * self.block_len();
+            let overlap = &mut output[start..start + block.len() + self.kernel_len - 1];
+            for (sum, &element) in overlap.iter_mut().zip(buffer.iter()) {
+                *sum = *sum + element;
+            }
+        }
+        output
+    }
+
+    /// The size of the FFTs used to convolve each block
+    pub fn fft_len(&self) -> usize {
+        self.forward_fft.len()
+    }
+
+    /// The number of signal elements convolved by each FFT, IE `fft_len() - kernel_len() + 1`
+    pub fn block_len(&self) -> usize {
+        self.fft_len() - self.kernel_len + 1
+    }
+
+    /// The number of elements in the kernel
+    pub fn kernel_len(&self) -> usize {
+        self.kernel_len
+    }
+}
+
+/// Returns the FFT size that minimizes the estimated cost per output element of overlap-add convolution with a kernel
+/// of `kernel_len` elements
+///
+/// The candidates are the sizes from `2 * kernel_len - 1` up to `64 * kernel_len` whose prime factors are all 2, 3, 5,
+/// or 7. Each FFT of size `n` costs two FFTs, estimated with `radix2_operation_count`, plus `n` multiplications by the
+/// kernel spectrum, and produces `n - kernel_len + 1` elements of output. For kernels of more than a few elements,
+/// this picks a size between 4 and 16 times the kernel length, growing slowly with the kernel.
+///
+/// ~~~
+/// use rustfft::convolution::overlap_add_fft_len;
+///
+/// let fft_len = overlap_add_fft_len(100);
+/// assert!(fft_len >= 400 && fft_len <= 1600);
+/// ~~~
+pub fn overlap_add_fft_len(kernel_len: usize) -> usize {
+    if kernel_len == 0 {
+        return 0;
+    }
+
+    let cost = |fft_len: usize| {
+        let operations = 2 * radix2_operation_count(fft_len) + fft_len;
+        operations as f64 / (fft_len - kernel_len + 1) as f64
+    };
+
+    let mut best_len = next_fast_len(2 * kernel_len - 1);
+    let mut candidate = best_len;
+    while candidate <= 64 * kernel_len {
+        if cost(candidate) < cost(best_len) {
+            best_len = candidate;
+        }
+        candidate = next_fast_len(candidate + 1);
+    }
+    best_len
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::{random_signal, compare_vectors};
+
+    fn naive_circular_convolution(signal: &[Complex<f32>], kernel: &[Complex<f32>]) -> Vec<Complex<f32>> {
+        let len = signal.len();
+        (0..len).map(|n| {
+            kernel.iter().enumerate().fold(Zero::zero(), |sum: Complex<f32>, (k, &tap)| sum + tap * signal[(n + len - k) % len])
+        }).collect()
+    }
+
+    #[test]
+    fn test_filter_bank() {
+        for &len in &[1, 8, 30, 97] {
+            let kernels: Vec<Vec<Complex<f32>>> = (1..6).map(|kernel_len| random_signal(kernel_len.min(len))).collect();
+            let filter_bank = FilterBank::new(len, &kernels);
+            assert_eq!(filter_bank.num_kernels(), kernels.len());
+
+            let signal = random_signal(len);
+            let outputs = filter_bank.process(&signal);
+            assert_eq!(outputs.len(), kernels.len());
+
+            for (kernel, output) in kernels.iter().zip(outputs.iter()) {
+                let expected = naive_circular_convolution(&signal, kernel);
+                assert!(compare_vectors(&expected, output), "len = {}, kernel len = {}", len, kernel.len());
+            }
+        }
+
+        let empty_bank = FilterBank::<f32>::new(0, &[Vec::new(), Vec::new()]);
+        assert_eq!(empty_bank.process(&[]), vec![Vec::new(), Vec::new()]);
+    }
+
+    #[test]
+    fn test_fft_filter_low_pass() {
+        let len = 64;
+        let cutoff = 8;
+
+        // a brick-wall low-pass filter, which keeps the bins whose frequency is below the cutoff, positive or negative
+        let response: Vec<Complex<f32>> = (0..len).map(|bin| {
+            let frequency = if bin > len / 2 { len - bin } else { bin };
+            if frequency < cutoff { Complex::new(1f32, 0f32) } else { Zero::zero() }
+        }).collect();
+        let mut filter = FFTfilter::new(response);
+
+        // a low tone at bin 3, plus a high tone at bin 20
+        let tone = |bin: usize, amplitude: f32| -> Vec<Complex<f32>> {
+            (0..len).map(|i| {
+                let phase = 2f32 * ::std::f32::consts::PI * (bin * i) as f32 / len as f32;
+                Complex::new(amplitude * phase.cos(), 0f32)
+            }).collect()
+        };
+        let low = tone(3, 1f32);
+        let signal: Vec<Complex<f32>> = low.iter().zip(tone(20, 0.5f32)).map(|(&a, b)| a + b).collect();
+
+        let mut output = vec![Zero::zero(); len];
+        filter.process(&signal, &mut output);
+        assert!(compare_vectors(&low, &output));
+
+        // processing again with the same filter gives the same result
+        let mut second_output = vec![Zero::zero(); len];
+        filter.process(&signal, &mut second_output);
+        assert_eq!(output, second_output);
+    }
+
+    #[test]
+    fn test_fft_filter_all_pass() {
+        for &len in &[0, 1, 7, 30] {
+            let mut filter = FFTfilter::new(vec![Complex::new(1f32, 0f32); len]);
+            let signal = random_signal(len);
+
+            let mut output = vec![Zero::zero(); len];
+            filter.process(&signal, &mut output);
+            assert!(len == 0 || compare_vectors(&signal, &output), "len = {}", len);
+        }
+    }
+
+    fn naive_linear_convolution(signal: &[Complex<f32>], kernel: &[Complex<f32>]) -> Vec<Complex<f32>> {
+        let mut output = vec![Zero::zero(); signal.len() + kernel.len() - 1];
+        for (i, &sample) in signal.iter().enumerate() {
+            for (k, &tap) in kernel.iter().enumerate() {
+                output[i + k] += sample * tap;
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn test_overlap_add() {
+        for &kernel_len in &[1, 2, 5, 33, 100] {
+            let kernel = random_signal(kernel_len);
+            let convolver = OverlapAdd::auto(&kernel);
+
+            // the FFT size is smooth, and large enough that most of each FFT goes to new output
+            let fft_len = convolver.fft_len();
+            assert_eq!(next_fast_len(fft_len), fft_len, "kernel len = {}", kernel_len);
+            assert!(fft_len >= 2 * kernel_len - 1 && fft_len <= 64 * kernel_len, "kernel len = {}, fft len = {}", kernel_len, fft_len);
+            assert!(kernel_len == 1 || convolver.block_len() >= fft_len / 2, "kernel len = {}, fft len = {}", kernel_len, fft_len);
+
+            // signals shorter than one block, exactly one block, and spanning several blocks with a partial last block
+            for &signal_len in &[1, convolver.block_len(), 3 * convolver.block_len() + 7] {
+                let signal = random_signal(signal_len);
+                let expected = naive_linear_convolution(&signal, &kernel);
+                let actual = convolver.process(&signal);
+                assert!(compare_vectors(&expected, &actual), "kernel len = {}, signal len = {}", kernel_len, signal_len);
+            }
+            assert!(convolver.process(&[]).is_empty());
+        }
+
+        // an explicit FFT size gives the same result, up to rounding
+        let kernel = random_signal(10);
+        let signal = random_signal(200);
+        let convolver = OverlapAdd::new(&kernel, 16);
+        assert_eq!(convolver.block_len(), 7);
+        assert!(compare_vectors(&naive_linear_convolution(&signal, &kernel), &convolver.process(&signal)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overlap_add_fft_too_short() {
+        OverlapAdd::new(&random_signal(10), 9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_filter_bank_kernel_too_long() {
+        FilterBank::new(4, &[random_signal(5)]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+use std::cmp;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use common::FFTnum;
+use array_utils;
+
+use {FFT, FFTplanner, Direction};
+
+/// Computes two-dimensional FFTs of images stored in row-major order, IE `height` rows of `width` elements each
+///
+/// A 2D FFT is a FFT of every row, followed by a FFT of every column. The columns are made contiguous by transposing
+/// the image into a scratch buffer, which the `FFT2D` instance owns along with any extra scratch space the row and column
+/// FFTs need (see `FFT::extra_scratch_len`), so that processing many images of the same size doesn't allocate. Use `process_batch` to transform a stack of images, like the frames of a video, with one call.
+///
+/// ~~~
+/// // Computes a forward 2D FFT of a 480x640 image
+/// use rustfft::FFT2D;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut image: Vec<Complex<f32>> = vec![Zero::zero(); 480 * 640];
+///
+/// let mut fft = FFT2D::new(640, 480, false);
+/// fft.process(&mut image);
+/// ~~~
+pub struct FFT2D<T> {
+    width: usize,
+    height: usize,
+    row_fft: Arc<dyn FFT<T>>,
+    column_fft: Arc<dyn FFT<T>>,
+    scratch: Vec<Complex<T>>,
+    extra_scratch: Vec<Complex<T>>,
+}
+
+impl<T: FFTnum> FFT2D<T> {
+    /// Plans the row and column FFTs for images with `height` rows of `width` elements each
+    ///
+    /// `direction` is the direction of the FFTs this will compute: a `Direction`, or a `bool` that's
+    /// true for inverse FFTs.
+    pub fn new<D: Into<Direction>>(width: usize, height: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        let mut planner = FFTplanner::new(inverse);
+        let row_fft = planner.plan_fft(width);
+        let column_fft = planner.plan_fft(height);
+        let extra_scratch_len = cmp::max(row_fft.extra_scratch_len(), column_fft.extra_scratch_len());
+        FFT2D {
+            width,
+            height,
+            row_fft,
+            column_fft,
+            scratch: vec![Zero::zero(); width * height],
+            extra_scratch: vec![Zero::zero(); extra_scratch_len],
+        }
+    }
+
+    /// The number of elements in each row of the image
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows in the image
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Computes the 2D FFT of `image` in place. `image` must have length `width * height`
+    pub fn process(&mut self, image: &mut [Complex<T>]) {
+        self.process_batch(image, 1);
+    }
+
+    /// Computes the 2D FFT of each of the `frame_count` images stored contiguously in `data`, in place
+    ///
+    /// `data` must have length `frame_count * width * height`. Every frame is transformed with the same row and column
+    /// FFTs and the same scratch buffer.
+    pub fn process_batch(&mut self, data: &mut [Complex<T>], frame_count: usize) {
+        let frame_len = self.width * self.height;
+        let expected_len = frame_count * frame_len;
+        assert_eq!(data.len(), expected_len, "Data is the wrong length. Expected {}, got {}", expected_len, data.len());
+
+        if frame_len == 0 {
+            return;
+        }
+
+        for frame in data.chunks_mut(frame_len) {
+            self.perform_fft(frame);
+        }
+    }
+
+    fn perform_fft(&mut self, frame: &mut [Complex<T>]) {
+        // the row FFTs use the frame as scratch, and leave their output in our scratch buffer
+        self.row_fft.process_multi_with_scratch(frame, &mut self.scratch, &mut self.extra_scratch);
+
+        // transpose so that the columns are contiguous, then do the same for the column FFTs
+        array_utils::transpose(self.width, self.height, &self.scratch, frame);
+        self.column_fft.process_multi_with_scratch(frame, &mut self.scratch, &mut self.extra_scratch);
+
+        // transpose back to row-major order
+        array_utils::transpose(self.height, self.width, &self.scratch, frame);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::{random_signal, compare_vectors};
+    use twiddles;
+
+    fn naive_fft2d(width: usize, height: usize, image: &[Complex<f32>], inverse: bool) -> Vec<Complex<f32>> {
+        let mut output = vec![Zero::zero(); width * height];
+        for v in 0..height {
+            for u in 0..width {
+                for y in 0..height {
+                    for x in 0..width {
+                        let twiddle = twiddles::single_twiddle::<f32, _>(u * x, width, inverse)
+                            * twiddles::single_twiddle::<f32, _>(v * y, height, inverse);
+                        output[v * width + u] += image[y * width + x] * twiddle;
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn test_fft2d() {
+        for &(width, height) in &[(1, 1), (4, 3), (3, 4), (6, 5), (8, 8)] {
+            for &inverse in &[false, true] {
+                let image = random_signal(width * height);
+
+                let mut actual = image.clone();
+                FFT2D::new(width, height, inverse).process(&mut actual);
+
+                let expected = naive_fft2d(width, height, &image, inverse);
+                assert!(compare_vectors(&expected, &actual), "width = {}, height = {}, inverse = {}", width, height, inverse);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fft2d_batch() {
+        let (width, height) = (5, 3);
+        let frame_len = width * height;
+        let mut fft = FFT2D::new(width, height, false);
+
+        let data = random_signal(2 * frame_len);
+        let mut batch = data.clone();
+        fft.process_batch(&mut batch, 2);
+
+        for (frame, actual) in data.chunks(frame_len).zip(batch.chunks(frame_len)) {
+            let mut expected = frame.to_vec();
+            fft.process(&mut expected);
+            assert_eq!(expected, actual);
+        }
+
+        // an empty batch is fine, as long as the data is empty too
+        fft.process_batch(&mut [], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fft2d_batch_wrong_length() {
+        let mut data = random_signal(2 * 15 - 1);
+        FFT2D::new(5, 3, false).process_batch(&mut data, 2);
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+
+use common::FFTnum;
+
+use {FFT, FFTplanner, Direction};
+
+/// A FFT whose size `N` is known at compile time
+///
+/// `process` takes arrays of exactly `N` elements, so passing a buffer of the wrong size is a compile error instead of a
+/// panic. Since the lengths can't be wrong, `process` skips the runtime length check, by calling the planned FFT's
+/// `FFT::process_unchecked`. The planned FFT is still an `Arc<dyn FFT<T>>`, so the call is still dispatched
+/// dynamically, and the inner FFTs of composite algorithms still check their own lengths.
+///
+/// ~~~
+/// // Computes a forward FFT of size 1024, where the size is part of the type
+/// use rustfft::FFTConst;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut input:  [Complex<f32>; 1024] = [Zero::zero(); 1024];
+/// let mut output: [Complex<f32>; 1024] = [Zero::zero(); 1024];
+///
+/// let fft = FFTConst::<1024, f32>::new(false);
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct FFTConst<const N: usize, T> {
+    fft: Arc<dyn FFT<T>>,
+}
+
+impl<const N: usize, T: FFTnum> FFTConst<N, T> {
+    /// Plans a FFT of size `N` with a new `FFTplanner`
+    ///
+    /// `direction` is the direction of the FFTs this will compute: a `Direction`, or a `bool` that's
+    /// true for inverse FFTs.
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        FFTConst { fft: FFTplanner::new(inverse).plan_fft(N) }
+    }
+
+    /// Wraps a FFT that has already been planned, so that it can share internal data with other FFTs from the same
+    /// planner. `fft.len()` must be `N`
+    pub fn from_fft(fft: Arc<dyn FFT<T>>) -> Self {
+        assert_eq!(fft.len(), N, "The FFT is the wrong length. Expected {}, got {}", N, fft.len());
+        FFTConst { fft }
+    }
+
+    /// Computes a FFT of `input` and stores the result in `output`. Like `FFT::process`, `input` is used as scratch
+    /// space, so its contents should be considered garbage afterwards
+    pub fn process(&self, input: &mut [Complex<T>; N], output: &mut [Complex<T>; N]) {
+        // both constructors make sure that `self.fft.len()` is `N`, and both arrays have `N` elements
+        unsafe { self.fft.process_unchecked(input, output) };
+    }
+
+    /// Returns the FFT that this wrapper computes with
+    pub fn fft(&self) -> &Arc<dyn FFT<T>> {
+        &self.fft
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use test_utils::random_signal;
+
+    #[test]
+    fn test_fft_const() {
+        let signal = random_signal(8);
+
+        let mut expected = vec![Zero::zero(); 8];
+        FFTplanner::new(false).plan_fft(8).process(&mut signal.clone(), &mut expected);
+
+        let fft = FFTConst::<8, f32>::new(false);
+        let mut input = [Zero::zero(); 8];
+        let mut output = [Zero::zero(); 8];
+        input.copy_from_slice(&signal);
+        fft.process(&mut input, &mut output);
+        assert_eq!(&expected[..], &output[..]);
+    }
+
+    #[test]
+    fn test_fft_const_composite() {
+        // 1200 is planned as a composite algorithm rather than a butterfly
+        let signal = random_signal(1200);
+
+        let mut expected = vec![Zero::zero(); 1200];
+        FFTplanner::new(true).plan_fft(1200).process(&mut signal.clone(), &mut expected);
+
+        let fft = FFTConst::<1200, f32>::new(true);
+        let mut input = [Zero::zero(); 1200];
+        let mut output = [Zero::zero(); 1200];
+        input.copy_from_slice(&signal);
+        fft.process(&mut input, &mut output);
+        assert_eq!(&expected[..], &output[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fft_const_wrong_length() {
+        FFTConst::<8, f32>::from_fft(FFTplanner::new(false).plan_fft(16));
+    }
+}
@@ -1,5 +1,3 @@
-#![cfg_attr(all(feature = "bench", test), feature(test))]
-
 //! RustFFT allows users to compute arbitrary-sized FFTs in O(nlogn) time.
 //!
 //! The recommended way to use RustFFT is to create a [`FFTplanner`](struct.FFTplanner.html) instance and then call its
@@ -50,25 +48,92 @@
 pub extern crate num_complex;
 pub extern crate num_traits;
 extern crate num_integer;
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+
+/// The complex number type that every FFT in this crate reads and writes
+///
+/// This is `Complex` from the version of `num-complex` that RustFFT depends on, which is also re-exported in full as
+/// `rustfft::num_complex`. If a project depends on a different version of `num-complex`, its `Complex` is a different
+/// type as far as the compiler is concerned, even though the two have the same fields. Use this re-export to name the
+/// type that RustFFT expects, and convert at the boundary with `rustfft::Complex::new(value.re, value.im)`.
+///
+/// ```
+/// // Build the input with the re-exported type, so it matches whatever version of num-complex RustFFT uses
+/// use rustfft::{Complex, FFTplanner};
+///
+/// let mut input:  Vec<Complex<f32>> = (0..16).map(|i| Complex::new(i as f32, 0f32)).collect();
+/// let mut output: Vec<Complex<f32>> = vec![Complex::new(0f32, 0f32); 16];
+///
+/// let fft = FFTplanner::new(false).plan_fft(16);
+/// fft.process(&mut input, &mut output);
+/// assert_eq!(output[0], Complex::new(120f32, 0f32));
+/// ```
+pub use num_complex::Complex;
 
 
 
 /// Individual FFT algorithms
 pub mod algorithm;
+/// Helpers for analyzing the output of a FFT
+pub mod spectrum;
+/// Aggregate features of the spectrum of a real signal, like its spectral centroid and flatness
+pub mod spectrum_stats;
+/// Signal analysis built on top of the FFT, like autocorrelation
+pub mod analysis;
+/// FFT-based convolution
+pub mod convolution;
+/// Short-time Fourier transforms, for computing the spectra of a signal over time
+pub mod stft;
+/// Number-theoretic helpers, like the ones used by Rader's Algorithm
+pub mod number_theory;
+/// Number-theoretic transforms, for exact convolution of integer sequences
+pub mod ntt;
+/// Timing helpers for comparing FFT algorithms on the current machine. Requires the `bench` feature
+#[cfg(feature = "bench")]
+pub mod benchmark;
 mod math_utils;
 mod array_utils;
 mod plan;
+mod plan_builder;
 mod twiddles;
 mod common;
+mod reconfigurable;
+mod scaled;
+mod real_fft;
+mod pool;
+mod fft2d;
+mod fft_const;
+
+use std::sync::Arc;
+use std::mem::MaybeUninit;
+use std::slice;
+
+use num_traits::{Zero, Float};
 
-use num_complex::Complex;
+use common::{epsilon, radix2_operation_count};
+use spectrum::Spectrum;
 
-pub use plan::FFTplanner;
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayView1, ArrayViewMut1, ArrayViewMut2, Axis};
+
+pub use plan::{FFTplanner, Factorizer, DecisionLog, PlannerDecision};
+pub use plan_builder::{PlanBuilder, PlanError};
 pub use common::FFTnum;
+pub use common::{verify_length, verify_length_divisible, try_verify_length, try_verify_length_divisible, LengthError, FFTError};
+pub use reconfigurable::ReconfigurableFFT;
+pub use scaled::{ScaledFFT, Scaling};
+pub use real_fft::{RealFFT, HalfLengthRealFFT, FullLengthRealFFT, Symmetry, symmetric_fft, is_hermitian};
+pub use pool::FFTpool;
+pub use fft2d::FFT2D;
+pub use fft_const::FFTConst;
+pub use array_utils::{transpose, circular_shift, zero_fill};
+pub use twiddles::single_twiddle;
 
 
 
 /// A trait that allows FFT algorithms to report their expected input/output size
+#[allow(clippy::len_without_is_empty)]
 pub trait Length {
     /// The FFT size that this algorithm can process
     fn len(&self) -> usize;
@@ -78,24 +143,1118 @@ pub trait Length {
 pub trait IsInverse {
     /// Returns false if this instance computes forward FFTs, true for inverse FFTs
     fn is_inverse(&self) -> bool;
+
+    /// Returns the direction of the FFTs this instance computes
+    fn direction(&self) -> Direction {
+        Direction::from(self.is_inverse())
+    }
+}
+
+/// The direction of a FFT
+///
+/// The constructors of the FFT algorithms and the planner accept anything that converts into a `Direction`. A bare
+/// `bool` still works, with `true` meaning inverse, but `FFTplanner::new(Direction::Inverse)` says what it does at the
+/// call site, where `FFTplanner::new(true)` doesn't.
+///
+/// ~~~
+/// use rustfft::{FFTplanner, Direction, IsInverse};
+///
+/// let fft = FFTplanner::<f32>::new(Direction::Inverse).plan_fft(64);
+/// assert_eq!(fft.direction(), Direction::Inverse);
+/// assert!(fft.is_inverse());
+/// ~~~
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Computes `X[k] = sum(x[n] * exp(-2 * pi * i * k * n / len))`
+    Forward,
+    /// Computes `x[n] = sum(X[k] * exp(2 * pi * i * k * n / len))`, without the `1 / len` normalization
+    Inverse,
+}
+
+impl Direction {
+    /// Returns true for `Direction::Inverse`
+    pub fn is_inverse(self) -> bool {
+        self == Direction::Inverse
+    }
+
+    /// Returns the opposite direction
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Forward => Direction::Inverse,
+            Direction::Inverse => Direction::Forward,
+        }
+    }
+}
+
+impl From<bool> for Direction {
+    /// Converts the `inverse` flag used throughout the crate: `true` is `Direction::Inverse`
+    fn from(inverse: bool) -> Self {
+        if inverse { Direction::Inverse } else { Direction::Forward }
+    }
+}
+
+impl From<Direction> for bool {
+    fn from(direction: Direction) -> Self {
+        direction.is_inverse()
+    }
 }
 
 /// An umbrella trait for all available FFT algorithms
+///
+/// `process` and `process_multi` take their input as `&mut`, and overwrite it: the composite algorithms need a second
+/// buffer of the same size for their transposes, and borrowing the input buffer for it means processing never has to
+/// allocate. If the input needs to survive, either process a copy of it, or use one of the methods that take their
+/// input by shared reference, like `process_with_sink` or `process_planar`, which make the copy internally.
 pub trait FFT<T: FFTnum>: Length + IsInverse + Sync + Send {
     /// Computes an FFT on the `input` buffer and places the result in the `output` buffer.
     ///
     /// This method uses the `input` buffer as scratch space, so the contents of `input` should be considered garbage
-    /// after calling
+    /// after calling. This is part of the contract, not an implementation detail: callers sharing buffers (over FFI,
+    /// for example) must not rely on `input` being unchanged, or on it holding any particular value afterwards.
     fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]);
 
+    /// Same as `process`, but without checking the lengths of `input` and `output`
+    ///
+    /// Every algorithm in this crate implements `process` as a length check followed by this method, so when the
+    /// lengths are already known to be right, for example because they're fixed at compile time like in `FFTConst`,
+    /// this skips the check. The default implementation calls `process`.
+    ///
+    /// # Safety
+    ///
+    /// `input` and `output` must both have length `self.len()`. Otherwise, the behavior is undefined: the hardcoded
+    /// butterflies read and write their buffers without bounds checks.
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.process(input, output);
+    }
+
     /// Divides the `input` and `output` buffers into chunks of length self.len(), then computes an FFT on each chunk.
     ///
     /// This method uses the `input` buffer as scratch space, so the contents of `input` should be considered garbage
     /// after calling
     fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]);
+
+    /// Computes an FFT of `buffer` in place. This is the single-buffer counterpart to `process`.
+    ///
+    /// `buffer` must have length `self.len()`. This is `process_multi_in_place` with a single chunk, so the hardcoded
+    /// butterflies transform `buffer` directly, and every other algorithm uses a scratch buffer the size of `buffer`.
+    /// That includes `Radix4`, whose first step copies the input into the output in digit-reversed order, so it can't
+    /// transform a single buffer without one.
+    fn process_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length(buffer, buffer, self.len());
+
+        // process_multi_in_place can't divide an empty buffer into chunks of size 0
+        if !buffer.is_empty() {
+            self.process_multi_in_place(buffer);
+        }
+    }
+
+    /// Computes an FFT of `signal` into `spectrum`, which doesn't need to be initialized, and returns `spectrum` as an
+    /// initialized slice holding the result.
+    ///
+    /// Both buffers must have length `self.len()`. Most algorithms use their output buffer as scratch space and read it
+    /// before writing the final result, so it isn't sound to pass uninitialized memory to `process` directly. Instead,
+    /// this method initializes each element of `spectrum` exactly once, by copying `signal` into it, and then transforms
+    /// it in place with `process_in_place`. This replaces zero-filling a freshly allocated output buffer, and unlike
+    /// `process`, it doesn't modify `signal`.
+    fn process_uninit<'a>(&self, signal: &[Complex<T>], spectrum: &'a mut [MaybeUninit<Complex<T>>]) -> &'a mut [Complex<T>] {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+        assert_eq!(spectrum.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+
+        for (element, &sample) in spectrum.iter_mut().zip(signal.iter()) {
+            *element = MaybeUninit::new(sample);
+        }
+
+        // every element was written by the loop above, and MaybeUninit<Complex<T>> has the same layout as Complex<T>
+        let spectrum = unsafe { slice::from_raw_parts_mut(spectrum.as_mut_ptr() as *mut Complex<T>, spectrum.len()) };
+        self.process_in_place(spectrum);
+        spectrum
+    }
+
+    /// Computes an FFT of `signal` into `spectrum`, where both hold complex numbers as interleaved pairs of real and
+    /// imaginary parts, IE `[re, im, re, im, ...]`
+    ///
+    /// Both buffers must have length `2 * self.len()`. This is the layout C libraries and many audio libraries use for
+    /// complex data, and it's the layout of a slice of `Complex<T>`, since `Complex<T>` is `repr(C)`. So this method
+    /// reinterprets both buffers as slices of `Complex<T>` without copying them, then copies `signal` into `spectrum` and
+    /// transforms it in place with `process_in_place`. Unlike `process`, it doesn't modify `signal`.
+    ///
+    /// ~~~
+    /// use rustfft::FFTplanner;
+    ///
+    /// let signal = [1f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32];
+    /// let mut spectrum = [0f32; 8];
+    ///
+    /// let fft = FFTplanner::new(false).plan_fft(4);
+    /// fft.process_interleaved(&signal, &mut spectrum);
+    ///
+    /// // an impulse has a flat spectrum
+    /// assert_eq!(spectrum, [1f32, 0f32, 1f32, 0f32, 1f32, 0f32, 1f32, 0f32]);
+    /// ~~~
+    fn process_interleaved(&self, signal: &[T], spectrum: &mut [T]) {
+        let expected_len = 2 * self.len();
+        assert_eq!(signal.len(), expected_len, "Input is the wrong length. Expected {}, got {}", expected_len, signal.len());
+        assert_eq!(spectrum.len(), expected_len, "Output is the wrong length. Expected {}, got {}", expected_len, spectrum.len());
+
+        // Complex<T> is repr(C), so it's laid out exactly like two consecutive elements of T, with the same alignment
+        let signal = unsafe { slice::from_raw_parts(signal.as_ptr() as *const Complex<T>, self.len()) };
+        let spectrum = unsafe { slice::from_raw_parts_mut(spectrum.as_mut_ptr() as *mut Complex<T>, self.len()) };
+
+        spectrum.copy_from_slice(signal);
+        self.process_in_place(spectrum);
+    }
+
+    /// Computes an FFT of the elements of `source` at `indices`, in that order, and stores the result in `spectrum`
+    ///
+    /// `indices` and `spectrum` must have length `self.len()`, and every index must be in bounds for `source`. The same
+    /// index may appear more than once. This is equivalent to collecting `source[indices[i]]` into a buffer and calling
+    /// `process`, but the elements are gathered directly into `spectrum`, which is then transformed in place with
+    /// `process_in_place`, so no intermediate buffer is needed. Unlike `process`, it doesn't modify `source`.
+    fn process_gather(&self, source: &[Complex<T>], indices: &[usize], spectrum: &mut [Complex<T>]) {
+        assert_eq!(indices.len(), self.len(), "Indices are the wrong length. Expected {}, got {}", self.len(), indices.len());
+        assert_eq!(spectrum.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+
+        for (element, &index) in spectrum.iter_mut().zip(indices) {
+            *element = source[index];
+        }
+        self.process_in_place(spectrum);
+    }
+
+    /// Computes an FFT of `signal` and adds it to the existing contents of `spectrum`, instead of overwriting them
+    ///
+    /// Both buffers must have length `self.len()`. Since the FFT is linear, calling this once for each of several
+    /// signals leaves the FFT of their sum in `spectrum`, which is how per-sensor spectra are combined in beamforming,
+    /// for example. `signal` is copied into a scratch buffer and transformed into a second scratch buffer, which is then
+    /// added to `spectrum`, so unlike `process`, it doesn't modify `signal`.
+    ///
+    /// ~~~
+    /// use rustfft::FFTplanner;
+    /// use rustfft::num_complex::Complex;
+    /// use rustfft::num_traits::Zero;
+    ///
+    /// let sensors = vec![vec![Complex::new(1f32, 0f32); 8], vec![Complex::new(0f32, 1f32); 8]];
+    /// let mut spectrum = vec![Complex::zero(); 8];
+    ///
+    /// let fft = FFTplanner::new(false).plan_fft(8);
+    /// for signal in &sensors {
+    ///     fft.process_add(signal, &mut spectrum);
+    /// }
+    ///
+    /// assert_eq!(spectrum[0], Complex::new(8f32, 8f32));
+    /// ~~~
+    fn process_add(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>]) {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+        assert_eq!(spectrum.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+
+        let mut input = signal.to_vec();
+        let mut output = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut output);
+
+        for (sum, bin) in spectrum.iter_mut().zip(output) {
+            *sum = *sum + bin;
+        }
+    }
+
+    /// Divides `buffer` into chunks of length self.len(), then computes an FFT on each chunk, in place.
+    ///
+    /// The hardcoded butterflies, which the planner uses for sizes 2, 3, 4, 5, 6, 7, 8, 10, 14, 15, 16, and 32, transform
+    /// each chunk directly in `buffer`. Every other algorithm needs a separate output buffer, so for them, this copies
+    /// `buffer` into a scratch buffer of the same size and processes from the scratch buffer back into `buffer`.
+    fn process_multi_in_place(&self, buffer: &mut [Complex<T>]) {
+        verify_length_divisible(buffer, buffer, self.len());
+
+        let mut scratch = buffer.to_vec();
+        self.process_multi(&mut scratch, buffer);
+    }
+
+    /// Performs the same FFT as `process`, but first checks that both buffers have length `self.len()`, and that every
+    /// element of `input` is finite, returning an error instead of panicking or transforming.
+    ///
+    /// A single NaN or infinity in the input spreads to every bin of the output, so this makes it possible to find where
+    /// bad data entered a pipeline. Scanning the input costs an extra pass over it, which is why `process` doesn't check.
+    fn process_checked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) -> Result<(), FFTError> where T: Float {
+        try_verify_length(input, output, self.len())?;
+
+        if let Some(index) = input.iter().position(|element| !element.re.is_finite() || !element.im.is_finite()) {
+            return Err(FFTError::NonFinite { index });
+        }
+
+        self.process(input, output);
+        Ok(())
+    }
+
+    /// Computes an FFT of `signal` in the precision of `T`, and stores the result in `spectrum`, rounded to `f32`
+    ///
+    /// Both buffers must have length `self.len()`. This is meant for FFTs of `f64`, where spectra are stored as `f32` to
+    /// save memory or bandwidth: rounding error accumulates through every pass of a FFT, so a native `f32` FFT of size
+    /// `n` has errors of around `log2(n)` times `f32` precision, relative to the magnitude of the spectrum, while
+    /// computing in `f64` and rounding once at the end has an error of at most half an `f32` ulp in each element. `signal`
+    /// is copied into a scratch buffer of the same size and transformed in place, so unlike `process`, it isn't
+    /// modified.
+    ///
+    /// ~~~
+    /// use rustfft::FFTplanner;
+    /// use rustfft::num_complex::Complex;
+    /// use rustfft::num_traits::Zero;
+    ///
+    /// let signal = vec![Complex::new(0.1f64, 0f64); 1024];
+    /// let mut spectrum: Vec<Complex<f32>> = vec![Zero::zero(); 1024];
+    ///
+    /// let fft = FFTplanner::new(false).plan_fft(1024);
+    /// fft.process_downcast(&signal, &mut spectrum);
+    ///
+    /// assert_eq!(spectrum[0], Complex::new((0.1f64 * 1024f64) as f32, 0f32));
+    /// ~~~
+    fn process_downcast(&self, signal: &[Complex<T>], spectrum: &mut [Complex<f32>]) where T: Float {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+        assert_eq!(spectrum.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+
+        let mut scratch = signal.to_vec();
+        self.process_in_place(&mut scratch);
+
+        for (element, bin) in spectrum.iter_mut().zip(scratch) {
+            *element = Complex::new(bin.re.to_f32().unwrap(), bin.im.to_f32().unwrap());
+        }
+    }
+
+    /// Returns the number of elements of scratch space this algorithm needs beyond its input and output buffers
+    ///
+    /// This is 0 for every algorithm in this crate except `RadersAlgorithm` with a padded convolution, which needs
+    /// two buffers the size of its inner FFT, and the composite algorithms that contain one. `process` and
+    /// `process_multi` allocate this much on every call, while `process_multi_with_scratch` takes it from the caller.
+    fn extra_scratch_len(&self) -> usize {
+        0
+    }
+
+    /// Same as `process_multi`, but takes the scratch space the algorithm needs beyond `input` and `output` from
+    /// `scratch`, which must have at least `self.extra_scratch_len()` elements, instead of allocating it
+    ///
+    /// Algorithms that don't need any extra scratch space ignore `scratch` and call `process_multi`.
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], _scratch: &mut [Complex<T>]) {
+        self.process_multi(input, output);
+    }
+
+    /// Returns the minimum length of the `scratch` buffer passed to `process_with_scratch`, which is `self.len()` for
+    /// a copy of the signal, plus `self.extra_scratch_len()`
+    fn required_scratch_len(&self) -> usize {
+        self.len() + self.extra_scratch_len()
+    }
+
+    /// Computes an FFT of `signal` and places the result in `spectrum`, using `scratch` as scratch space instead of
+    /// overwriting `signal`.
+    ///
+    /// `signal` and `spectrum` must have length `self.len()`, and `scratch` must have at least
+    /// `self.required_scratch_len()` elements, or this method panics. Every algorithm in this crate uses its input
+    /// buffer as its scratch space, so the signal is copied into the start of `scratch`, and processed from there with
+    /// `process_multi_with_scratch`, which gets the rest. Neither step allocates for any algorithm in this crate, so
+    /// with a scratch buffer from the caller's own pool, this is safe to call from a real-time thread.
+    fn process_with_scratch(&self, signal: &[Complex<T>], spectrum: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        let scratch_len = self.required_scratch_len();
+        assert!(scratch.len() >= scratch_len, "Scratch is the wrong length. Expected at least {}, got {}", scratch_len, scratch.len());
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+        assert_eq!(spectrum.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+
+        // process_multi_with_scratch can't divide an empty buffer into chunks of size 0
+        if signal.is_empty() {
+            return;
+        }
+
+        let (input, extra_scratch) = scratch[..scratch_len].split_at_mut(self.len());
+        input.copy_from_slice(signal);
+        self.process_multi_with_scratch(input, spectrum, extra_scratch);
+    }
+
+    /// Returns a FFT instance of the same size that computes the opposite direction of this one.
+    ///
+    /// The algorithms in this crate derive the returned instance from this instance's precomputed data, by conjugating
+    /// twiddle factors, copying index maps, and inverting their inner FFTs, rather than computing it from scratch. The
+    /// default implementation, for FFTs that don't override it, plans a FFT of the same size in the opposite direction
+    /// with a new `FFTplanner`.
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        FFTplanner::new(!self.is_inverse()).plan_fft(self.len())
+    }
+
+    /// Returns the number of bytes of heap-allocated precomputed data (twiddle factors, index maps, etc) owned by this
+    /// instance, including the data owned by any inner FFTs.
+    ///
+    /// Inner FFTs are shared via `Arc`, so if several instances share the same inner FFT, its data will be counted once
+    /// for each of them. Algorithms whose data is stored inline, like the butterflies, report 0.
+    fn memory_footprint(&self) -> usize {
+        0
+    }
+
+    /// Returns a rough estimate of the relative error that this instance introduces, IE the RMS error of the output
+    /// divided by the RMS of the output, for a typical input.
+    ///
+    /// The estimate is a multiple of the machine epsilon of `T`, and it's only meant for comparing plans and choosing
+    /// between `f32` and `f64`, not as a guarantee. FFT algorithms accumulate error once per stage, so by default, the
+    /// estimate is `epsilon * log2(len)`. Composite algorithms add up the estimates of their inner FFTs, plus one
+    /// epsilon for each step that multiplies by twiddle factors, and `DFT` sums `len` products for each output, so its
+    /// estimate is `epsilon * sqrt(len)`.
+    fn error_bound_estimate(&self) -> f64 {
+        epsilon::<T>() * (self.len() as f64).log2().max(1f64)
+    }
+
+    /// Returns the number of complex multiply-adds that one call to `process` performs, counted statically from the plan
+    ///
+    /// Composite algorithms add up the counts of their inner FFTs, times the number of times they call each one, plus
+    /// one multiply for each element in each pass of twiddle factors or precomputed data. `DFT` computes `len` products
+    /// for each of its `len` outputs, so it counts `len * len`. Every other algorithm, including the butterflies, counts
+    /// like a radix-2 FFT: `len * log2(len) / 2`, rounded to the nearest integer. The count is meant for comparing plans
+    /// and for capacity planning, not as an exact instruction count.
+    fn operation_count(&self) -> usize {
+        radix2_operation_count(self.len())
+    }
+
+    /// Returns a description of the algorithm tree that this instance computes, like
+    /// `MixedRadix(Radix4(1024), GoodThomasAlgorithmDoubleButterfly(Butterfly3, Butterfly7))`
+    ///
+    /// Each algorithm is described by its name, followed by the descriptions of its inner FFTs in parentheses. Algorithms
+    /// without inner FFTs list their size instead, and butterflies are described by their name alone. Algorithms from
+    /// outside this crate are described as `FFT(len)` unless they override this method.
+    fn describe(&self) -> String {
+        format!("FFT({})", self.len())
+    }
+
+    /// Computes an FFT on a signal stored in split-complex ("planar") form, where the real parts and imaginary parts
+    /// are stored in separate buffers, and places the result in planar form in `re_output` and `im_output`.
+    ///
+    /// All four buffers must have length `self.len()`. Unlike `process`, this method does not modify its inputs: the
+    /// signal is currently interleaved into an internal scratch buffer before being processed.
+    fn process_planar(&self, re_input: &[T], im_input: &[T], re_output: &mut [T], im_output: &mut [T]) {
+        verify_length(re_input, re_output, self.len());
+        verify_length(im_input, im_output, self.len());
+
+        let mut input: Vec<Complex<T>> = re_input.iter().zip(im_input.iter()).map(|(&re, &im)| Complex::new(re, im)).collect();
+        let mut output = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut output);
+
+        for ((re, im), element) in re_output.iter_mut().zip(im_output.iter_mut()).zip(output.iter()) {
+            *re = element.re;
+            *im = element.im;
+        }
+    }
+
+    /// Computes an FFT on an interleaved `signal`, and places the result in split-complex ("planar") form in `re_output`
+    /// and `im_output`.
+    ///
+    /// All three buffers must have length `self.len()`. Unlike `process`, this method does not modify its input: the
+    /// signal is copied into an internal scratch buffer, and the result is deinterleaved from a second one.
+    fn process_to_planar(&self, signal: &[Complex<T>], re_output: &mut [T], im_output: &mut [T]) {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+        verify_length(re_output, im_output, self.len());
+
+        let mut input = signal.to_vec();
+        let mut output = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut output);
+
+        for ((re, im), element) in re_output.iter_mut().zip(im_output.iter_mut()).zip(output.iter()) {
+            *re = element.re;
+            *im = element.im;
+        }
+    }
+
+    /// Computes an inverse FFT of a spectrum whose inverse is known to be real, and places the real parts of the result
+    /// in `output`, discarding the imaginary parts.
+    ///
+    /// This instance must compute inverse FFTs, and both buffers must have length `self.len()`. The spectrum of a real
+    /// signal is Hermitian, IE `spectrum[k]` is the conjugate of `spectrum[len - k]`, which makes the imaginary parts of
+    /// the inverse zero, apart from rounding error. In debug builds, this method panics if the discarded imaginary parts
+    /// hold more than a millionth of the energy of the result, since that means the spectrum wasn't Hermitian.
+    ///
+    /// Unlike `process`, this method does not modify its input: the spectrum is copied into an internal scratch buffer
+    /// before being processed.
+    fn process_inverse_real(&self, spectrum: &[Complex<T>], output: &mut [T]) where T: PartialOrd {
+        assert!(self.is_inverse(), "process_inverse_real() requires an inverse FFT instance");
+        assert_eq!(spectrum.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), spectrum.len());
+        assert_eq!(output.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), output.len());
+
+        let mut input = spectrum.to_vec();
+        let mut scratch = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut scratch);
+
+        if cfg!(debug_assertions) {
+            let real_energy = scratch.iter().fold(T::zero(), |sum, element| sum + element.re * element.re);
+            let imaginary_energy = scratch.iter().fold(T::zero(), |sum, element| sum + element.im * element.im);
+            let threshold = T::from_f64(1e-6).unwrap();
+            assert!(imaginary_energy <= threshold * (real_energy + imaginary_energy),
+                "process_inverse_real() was given a spectrum that isn't Hermitian, so its inverse isn't real");
+        }
+
+        for (out, element) in output.iter_mut().zip(scratch.iter()) {
+            *out = element.re;
+        }
+    }
+
+    /// Computes an FFT of `signal`, then calls `sink(bin_index, value)` for each bin of the spectrum, in order.
+    ///
+    /// `signal` must have length `self.len()`. The whole spectrum is still computed before the first call to `sink`, in
+    /// an internal scratch buffer, but the caller doesn't need to provide an output buffer, and can process each bin as
+    /// it's handed over. The sink is a trait object so that this method can be called on `Arc<dyn FFT<T>>`. Unlike
+    /// `process`, this method does not modify its input.
+    fn process_with_sink(&self, signal: &[Complex<T>], sink: &mut dyn FnMut(usize, Complex<T>)) {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+
+        let mut input = signal.to_vec();
+        let mut output = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut output);
+
+        for (bin_index, &value) in output.iter().enumerate() {
+            sink(bin_index, value);
+        }
+    }
+
+    /// Computes an FFT of `signal`, and wraps the result in a `Spectrum` that knows the signal was sampled at
+    /// `sample_rate` samples per unit of time
+    ///
+    /// `signal` must have length `self.len()`. Unlike `process`, this method does not modify its input.
+    fn process_to_spectrum(&self, signal: &[Complex<T>], sample_rate: f64) -> Spectrum<T> {
+        assert_eq!(signal.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), signal.len());
+
+        let mut input = signal.to_vec();
+        let mut output = vec![Zero::zero(); self.len()];
+        self.process(&mut input, &mut output);
+
+        Spectrum::new(output, sample_rate)
+    }
+
+    /// Computes an FFT on each row of `data`, which is a row-major matrix with `width` columns and `height` rows, and
+    /// replaces each row with its transform.
+    ///
+    /// `self.len()` must be equal to `width`, and `data` must have length `width * height`. This method allocates a
+    /// scratch buffer the size of `data`.
+    fn process_rows(&self, data: &mut [Complex<T>], width: usize, height: usize) {
+        assert_eq!(data.len(), width * height, "Data is the wrong length. Expected {}, got {}", width * height, data.len());
+        assert_eq!(width, self.len(), "Row length must be equal to the FFT length. Expected {}, got {}", self.len(), width);
+
+        let mut scratch = data.to_vec();
+        self.process_multi(&mut scratch, data);
+    }
+
+    /// Computes an FFT on each column of `data`, which is a row-major matrix with `width` columns and `height` rows, and
+    /// replaces each column with its transform.
+    ///
+    /// `self.len()` must be equal to `height`, and `data` must have length `width * height`. The columns are transposed
+    /// into rows so that they can be processed with `process_multi`, then transposed back, using a scratch buffer the
+    /// size of `data`. With the `ndarray` feature, `process_ndarray2` transforms along either axis of an `ndarray`
+    /// array of any layout.
+    fn process_columns(&self, data: &mut [Complex<T>], width: usize, height: usize) {
+        assert_eq!(data.len(), width * height, "Data is the wrong length. Expected {}, got {}", width * height, data.len());
+        assert_eq!(height, self.len(), "Column length must be equal to the FFT length. Expected {}, got {}", self.len(), height);
+
+        let mut scratch = vec![Zero::zero(); data.len()];
+        array_utils::transpose(width, height, data, &mut scratch);
+        self.process_multi(&mut scratch, data);
+        array_utils::transpose(height, width, data, &mut scratch);
+        data.copy_from_slice(&scratch);
+    }
+
+    /// Computes a FFT of the `ndarray` view `input`, and places the result in `output`. Requires the `ndarray` feature
+    ///
+    /// Both views must have length `self.len()`. `input` isn't modified: it's copied into a scratch buffer, which is
+    /// processed with `process`. If `output` isn't contiguous, the spectrum is computed into a second buffer and
+    /// copied into it.
+    ///
+    /// ~~~
+    /// # extern crate ndarray;
+    /// # extern crate rustfft;
+    /// # fn main() {
+    /// use ndarray::Array1;
+    /// use rustfft::FFTplanner;
+    /// use rustfft::num_complex::Complex;
+    ///
+    /// let signal = Array1::from_elem(8, Complex::new(1f32, 0f32));
+    /// let mut spectrum = Array1::from_elem(8, Complex::new(0f32, 0f32));
+    ///
+    /// let fft = FFTplanner::new(false).plan_fft(8);
+    /// fft.process_ndarray(signal.view(), spectrum.view_mut());
+    /// assert_eq!(spectrum[0], Complex::new(8f32, 0f32));
+    /// # }
+    /// ~~~
+    #[cfg(feature = "ndarray")]
+    fn process_ndarray(&self, input: ArrayView1<Complex<T>>, mut output: ArrayViewMut1<Complex<T>>) {
+        assert_eq!(input.len(), self.len(), "Input is the wrong length. Expected {}, got {}", self.len(), input.len());
+        assert_eq!(output.len(), self.len(), "Output is the wrong length. Expected {}, got {}", self.len(), output.len());
+
+        let mut scratch = input.to_vec();
+        if let Some(output) = output.as_slice_mut() {
+            self.process(&mut scratch, output);
+            return;
+        }
+
+        let mut spectrum = vec![Zero::zero(); self.len()];
+        self.process(&mut scratch, &mut spectrum);
+        output.assign(&ArrayView1::from(&spectrum[..]));
+    }
+
+    /// Computes a FFT along `axis` of the 2D `ndarray` view `data`, replacing each lane with its transform. Requires
+    /// the `ndarray` feature
+    ///
+    /// `data.len_of(axis)` must be equal to `self.len()`. `Axis(1)` transforms each row, and `Axis(0)` transforms each
+    /// column. The lanes are copied into a scratch buffer, processed with `process_multi`, and copied back, so this
+    /// works for views of any layout, and allocates two buffers the size of `data`.
+    #[cfg(feature = "ndarray")]
+    fn process_ndarray2(&self, mut data: ArrayViewMut2<Complex<T>>, axis: Axis) {
+        assert_eq!(data.len_of(axis), self.len(), "Axis {} is the wrong length. Expected {}, got {}", axis.index(), self.len(), data.len_of(axis));
+
+        let mut scratch: Vec<Complex<T>> = Vec::with_capacity(data.len());
+        for lane in data.lanes(axis) {
+            scratch.extend(lane.iter().cloned());
+        }
+
+        let mut spectrum = vec![Zero::zero(); data.len()];
+        self.process_multi(&mut scratch, &mut spectrum);
+
+        for (mut lane, chunk) in data.lanes_mut(axis).into_iter().zip(spectrum.chunks(self.len())) {
+            lane.assign(&ArrayView1::from(chunk));
+        }
+    }
+}
+
+/// Computes a FFT of `signal`, and returns the result in the same `Vec`, reusing its allocation for the output
+///
+/// `direction` is a `Direction`, or a `bool` that's true for an inverse FFT. The FFT is
+/// planned with a new `FFTplanner` and computed with `process_in_place`, so butterflies transform the buffer without
+/// allocating anything else, and other algorithms allocate only their scratch space. To compute many FFTs of the same
+/// size, plan the FFT once and call `process_in_place` instead.
+///
+/// ~~~
+/// use rustfft::fft_owned;
+/// use rustfft::num_complex::Complex;
+///
+/// let signal = vec![Complex::new(1f32, 0f32), Complex::new(1f32, 0f32), Complex::new(1f32, 0f32), Complex::new(1f32, 0f32)];
+/// let spectrum = fft_owned(signal, false);
+/// assert_eq!(spectrum[0], Complex::new(4f32, 0f32));
+/// ~~~
+pub fn fft_owned<T: FFTnum, D: Into<Direction>>(mut signal: Vec<Complex<T>>, direction: D) -> Vec<Complex<T>> {
+    FFTplanner::new(direction).plan_fft(signal.len()).process_in_place(&mut signal);
+    signal
 }
 
 #[cfg(test)]
 extern crate rand;
 #[cfg(test)]
 mod test_utils;
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::{random_signal, compare_vectors, check_fft_algorithm};
+    use algorithm::{DFT, Radix4, MixedRadix};
+
+    #[test]
+    fn test_process_planar() {
+        for &len in &[1, 4, 6, 17, 64] {
+            let mut planner = FFTplanner::new(false);
+            let fft = planner.plan_fft(len);
+
+            let signal = random_signal(len);
+            let re_input: Vec<f32> = signal.iter().map(|c| c.re).collect();
+            let im_input: Vec<f32> = signal.iter().map(|c| c.im).collect();
+            let mut re_output = vec![0f32; len];
+            let mut im_output = vec![0f32; len];
+            fft.process_planar(&re_input, &im_input, &mut re_output, &mut im_output);
+
+            let mut input = signal.clone();
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut input, &mut expected);
+
+            let actual: Vec<Complex<f32>> = re_output.iter().zip(im_output.iter()).map(|(&re, &im)| Complex::new(re, im)).collect();
+            assert!(compare_vectors(&expected, &actual), "process_planar() failed, length = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_default_inverse_of() {
+        // a FFT that only implements the required methods, like a downstream implementation would
+        struct Wrapper(DFT<f32>);
+        impl FFT<f32> for Wrapper {
+            fn process(&self, input: &mut [Complex<f32>], output: &mut [Complex<f32>]) {
+                self.0.process(input, output)
+            }
+            fn process_multi(&self, input: &mut [Complex<f32>], output: &mut [Complex<f32>]) {
+                self.0.process_multi(input, output)
+            }
+        }
+        impl Length for Wrapper {
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+        }
+        impl IsInverse for Wrapper {
+            fn is_inverse(&self) -> bool {
+                self.0.is_inverse()
+            }
+        }
+
+        for &inverse in &[false, true] {
+            let inverse_fft = Wrapper(DFT::new(30, inverse)).inverse_of();
+            check_fft_algorithm(&*inverse_fft, 30, !inverse);
+        }
+    }
+
+    #[test]
+    fn test_operation_count() {
+        let radix4: Radix4<f32> = Radix4::new(1024, false);
+        assert_eq!(radix4.operation_count(), 1024 * 10 / 2);
+
+        let dft: DFT<f32> = DFT::new(100, false);
+        assert_eq!(dft.operation_count(), 100 * 100);
+
+        // a mixed radix plan counts its inner FFTs once per row or column, plus the twiddle factors
+        let mixed_radix = MixedRadix::new(Arc::new(Radix4::new(64, false)) as Arc<dyn FFT<f32>>, Arc::new(DFT::new(5, false)) as Arc<dyn FFT<f32>>);
+        assert_eq!(mixed_radix.operation_count(), 5 * 192 + 64 * 25 + 320);
+
+        // the planned FFT of a prime size should be far cheaper than its DFT, but more expensive than a power of two
+        let planned = FFTplanner::<f32>::new(false).plan_fft(1009);
+        assert!(planned.operation_count() < DFT::<f32>::new(1009, false).operation_count() / 10);
+        assert!(planned.operation_count() > radix4.operation_count());
+    }
+
+    #[test]
+    fn test_error_bound_estimate() {
+        let radix4: Radix4<f32> = Radix4::new(1024, false);
+        let dft: DFT<f32> = DFT::new(1024, false);
+        assert!(radix4.error_bound_estimate() < dft.error_bound_estimate());
+
+        // the same plan in f64 should be many orders of magnitude more accurate
+        let planned_f32 = FFTplanner::<f32>::new(false).plan_fft(1201);
+        let planned_f64 = FFTplanner::<f64>::new(false).plan_fft(1201);
+        assert!(planned_f64.error_bound_estimate() * 1e6 < planned_f32.error_bound_estimate());
+
+        // the estimate should be in the right ballpark of the actual error
+        for &len in &[64, 1000, 1201] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len);
+            let mut actual = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut actual);
+
+            let signal64: Vec<Complex<f64>> = signal.iter().map(|c| Complex::new(c.re as f64, c.im as f64)).collect();
+            let mut expected = vec![Zero::zero(); len];
+            FFTplanner::new(false).plan_fft(len).process(&mut signal64.clone(), &mut expected);
+
+            let error: f64 = actual.iter().zip(expected.iter()).map(|(a, e)| (Complex::new(a.re as f64, a.im as f64) - e).norm_sqr()).sum();
+            let magnitude: f64 = expected.iter().map(|e| e.norm_sqr()).sum();
+            let relative_error = (error / magnitude).sqrt();
+
+            let estimate = fft.error_bound_estimate();
+            assert!(relative_error < estimate * 10f64 && relative_error > estimate / 100f64,
+                "len = {}, relative error = {}, estimate = {}", len, relative_error, estimate);
+        }
+    }
+
+    #[test]
+    fn test_process_to_planar() {
+        for &len in &[1, 4, 6, 17, 64] {
+            let mut planner = FFTplanner::new(true);
+            let fft = planner.plan_fft(len);
+
+            let signal = random_signal(len);
+            let mut re_output = vec![0f32; len];
+            let mut im_output = vec![0f32; len];
+            fft.process_to_planar(&signal, &mut re_output, &mut im_output);
+
+            let mut input = signal.clone();
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut input, &mut expected);
+
+            let actual: Vec<Complex<f32>> = re_output.iter().zip(im_output.iter()).map(|(&re, &im)| Complex::new(re, im)).collect();
+            assert_eq!(expected, actual, "process_to_planar() failed, length = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_process_inverse_real() {
+        for &len in &[1, 2, 5, 16, 30] {
+            let signal: Vec<f32> = random_signal(len).iter().map(|c| c.re).collect();
+
+            let mut input: Vec<Complex<f32>> = signal.iter().map(|&re| Complex::new(re, 0f32)).collect();
+            let mut spectrum = vec![Zero::zero(); len];
+            FFTplanner::new(false).plan_fft(len).process(&mut input, &mut spectrum);
+
+            let mut output = vec![0f32; len];
+            FFTplanner::new(true).plan_fft(len).process_inverse_real(&spectrum, &mut output);
+
+            for (&expected, &actual) in signal.iter().zip(output.iter()) {
+                assert!((expected - actual / len as f32).abs() < 1e-3, "len = {}: expected {}, got {}", len, expected, actual / len as f32);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_process_inverse_real_non_hermitian() {
+        let spectrum = random_signal(8);
+        let mut output = vec![0f32; 8];
+        FFTplanner::new(true).plan_fft(8).process_inverse_real(&spectrum, &mut output);
+    }
+
+    #[test]
+    fn test_process_with_sink() {
+        for &len in &[1, 5, 16, 30] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len);
+
+            let mut sink_calls = Vec::new();
+            fft.process_with_sink(&signal, &mut |bin_index, value| sink_calls.push((bin_index, value)));
+
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut expected);
+
+            let indexes: Vec<usize> = sink_calls.iter().map(|&(bin_index, _)| bin_index).collect();
+            let values: Vec<Complex<f32>> = sink_calls.iter().map(|&(_, value)| value).collect();
+            assert_eq!(indexes, (0..len).collect::<Vec<usize>>());
+            assert_eq!(values, expected, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_process_in_place() {
+        for &len in &[0, 1, 2, 5, 10, 16, 30, 64, 97] {
+            for &inverse in &[false, true] {
+                let fft = FFTplanner::new(inverse).plan_fft(len);
+                let signal = random_signal(len);
+
+                let mut expected = vec![Zero::zero(); len];
+                fft.process(&mut signal.clone(), &mut expected);
+
+                let mut actual = signal.clone();
+                fft.process_in_place(&mut actual);
+                assert_eq!(expected, actual, "len = {}, inverse = {}", len, inverse);
+            }
+        }
+    }
+
+    #[test]
+    fn test_in_place_names_with_butterfly_trait() {
+        // with both traits in scope, method calls on a butterfly must resolve without naming the trait
+        use algorithm::butterflies::{Butterfly4, FFTButterfly};
+
+        let butterfly = Butterfly4::new(false);
+        let signal = random_signal(8);
+
+        let mut expected = signal.clone();
+        unsafe { butterfly.process_multi_inplace(&mut expected) };
+        let mut actual = signal.clone();
+        butterfly.process_multi_in_place(&mut actual);
+        assert_eq!(expected, actual);
+
+        let mut expected = signal[..4].to_vec();
+        unsafe { butterfly.process_inplace(&mut expected) };
+        let mut actual = signal[..4].to_vec();
+        butterfly.process_in_place(&mut actual);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected multiple of 16, got 35, which has 3 elements left over")]
+    fn test_process_multi_remainder() {
+        let fft = FFTplanner::new(false).plan_fft(16);
+        let mut input: Vec<Complex<f32>> = vec![Zero::zero(); 35];
+        let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 35];
+        fft.process_multi(&mut input, &mut output);
+    }
+
+    #[test]
+    fn test_process_checked() {
+        let fft = FFTplanner::new(false).plan_fft(16);
+        let signal = random_signal(16);
+
+        let mut expected = vec![Zero::zero(); 16];
+        fft.process(&mut signal.clone(), &mut expected);
+        let mut actual = vec![Zero::zero(); 16];
+        assert_eq!(fft.process_checked(&mut signal.clone(), &mut actual), Ok(()));
+        assert_eq!(expected, actual);
+
+        let mut nan_signal = signal.clone();
+        nan_signal[5].im = f32::NAN;
+        nan_signal[9].re = f32::INFINITY;
+        assert_eq!(fft.process_checked(&mut nan_signal, &mut actual), Err(FFTError::NonFinite { index: 5 }));
+
+        let mut short_signal = signal[..8].to_vec();
+        assert_eq!(fft.process_checked(&mut short_signal, &mut actual), Err(FFTError::Length(LengthError::Input { expected: 16, actual: 8 })));
+    }
+
+    #[test]
+    fn test_process_with_scratch() {
+        for &len in &[1, 2, 5, 10, 16, 30, 64, 97] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len);
+
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut expected);
+
+            // both an exactly-sized and an oversized scratch buffer should work
+            for &scratch_len in &[fft.required_scratch_len(), fft.required_scratch_len() + 3] {
+                let mut scratch = vec![Zero::zero(); scratch_len];
+                let mut actual = vec![Zero::zero(); len];
+                fft.process_with_scratch(&signal, &mut actual, &mut scratch);
+                assert_eq!(expected, actual, "len = {}, scratch_len = {}", len, scratch_len);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Scratch is the wrong length. Expected at least 16, got 15")]
+    fn test_process_with_scratch_undersized() {
+        let fft = FFTplanner::new(false).plan_fft(16);
+        let signal: Vec<Complex<f32>> = vec![Zero::zero(); 16];
+        let mut spectrum = vec![Zero::zero(); 16];
+        let mut scratch = vec![Zero::zero(); 15];
+        fft.process_with_scratch(&signal, &mut spectrum, &mut scratch);
+    }
+
+    // computes the FFT into the uninitialized spare capacity of a new vector, then takes ownership of the result
+    fn process_into_new_vec(fft: &dyn FFT<f32>, signal: &[Complex<f32>]) -> Vec<Complex<f32>> {
+        let mut spectrum = Vec::with_capacity(signal.len());
+        fft.process_uninit(signal, &mut spectrum.spare_capacity_mut()[..signal.len()]);
+
+        // process_uninit initialized every element of the spare capacity
+        unsafe { spectrum.set_len(signal.len()) };
+        spectrum
+    }
+
+    #[test]
+    fn test_process_uninit() {
+        for &len in &[0, 1, 2, 5, 10, 16, 30, 64, 97, 1000] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len);
+
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut expected);
+
+            assert_eq!(expected, process_into_new_vec(&*fft, &signal), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_fft_owned() {
+        for &len in &[0, 1, 5, 16, 30, 97] {
+            for &inverse in &[false, true] {
+                let signal = random_signal(len);
+                let mut expected = vec![Zero::zero(); len];
+                FFTplanner::new(inverse).plan_fft(len).process(&mut signal.clone(), &mut expected);
+
+                // leave some spare capacity, to make sure the result is the same allocation and not just the same size
+                let mut owned = Vec::with_capacity(len + 3);
+                owned.extend_from_slice(&signal);
+                let (pointer, capacity) = (owned.as_ptr(), owned.capacity());
+
+                let actual = fft_owned(owned, inverse);
+                assert_eq!(actual.as_ptr(), pointer, "len = {}, inverse = {}", len, inverse);
+                assert_eq!(actual.capacity(), capacity, "len = {}, inverse = {}", len, inverse);
+                assert_eq!(actual, expected, "len = {}, inverse = {}", len, inverse);
+            }
+        }
+    }
+
+    #[test]
+    fn test_process_interleaved() {
+        for &len in &[0, 1, 2, 5, 16, 30, 97] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len);
+
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut expected);
+
+            let interleaved_signal: Vec<f32> = signal.iter().flat_map(|element| vec![element.re, element.im]).collect();
+            let mut interleaved_spectrum = vec![0f32; 2 * len];
+            fft.process_interleaved(&interleaved_signal, &mut interleaved_spectrum);
+
+            let actual: Vec<Complex<f32>> = interleaved_spectrum.chunks(2).map(|pair| Complex::new(pair[0], pair[1])).collect();
+            assert_eq!(expected, actual, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_process_gather() {
+        for &len in &[1, 2, 5, 16, 30, 97] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let source = random_signal(3 * len);
+
+            // a deterministic shuffle of every third element
+            let mut indices: Vec<usize> = (0..len).map(|i| 3 * i + 1).collect();
+            for i in (1..len).rev() {
+                indices.swap(i, (i * 7 + 3) % (i + 1));
+            }
+
+            let mut gathered: Vec<Complex<f32>> = indices.iter().map(|&index| source[index]).collect();
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut gathered, &mut expected);
+
+            let mut actual = vec![Zero::zero(); len];
+            fft.process_gather(&source, &indices, &mut actual);
+            assert_eq!(expected, actual, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_process_downcast() {
+        for &len in &[1, 2, 5, 16, 30, 97, 1024] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal: Vec<Complex<f64>> = random_signal(len).iter().map(|element| Complex::new(element.re as f64, element.im as f64)).collect();
+
+            let mut spectrum = vec![Zero::zero(); len];
+            fft.process(&mut signal.clone(), &mut spectrum);
+            let expected: Vec<Complex<f32>> = spectrum.iter().map(|bin| Complex::new(bin.re as f32, bin.im as f32)).collect();
+
+            let mut actual = vec![Zero::zero(); len];
+            fft.process_downcast(&signal, &mut actual);
+            assert_eq!(expected, actual, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_direction() {
+        use algorithm::butterflies::Butterfly3;
+
+        for &(direction, inverse) in &[(Direction::Forward, false), (Direction::Inverse, true)] {
+            assert_eq!(Direction::from(inverse), direction);
+            assert_eq!(bool::from(direction), inverse);
+            assert_eq!(direction.opposite().is_inverse(), !inverse);
+
+            // constructors given a Direction compute the same FFTs as the ones given the equivalent bool
+            for &len in &[3, 16, 30, 97] {
+                let fft = FFTplanner::new(direction).plan_fft(len);
+                assert_eq!(fft.direction(), direction);
+                assert_eq!(fft.is_inverse(), inverse);
+
+                let signal = random_signal(len);
+                let mut expected = vec![Zero::zero(); len];
+                DFT::new(len, inverse).process(&mut signal.clone(), &mut expected);
+
+                let mut actual = vec![Zero::zero(); len];
+                fft.process(&mut signal.clone(), &mut actual);
+                assert!(compare_vectors(&expected, &actual), "len = {}, direction = {:?}", len, direction);
+            }
+
+            let butterfly = Butterfly3::new(direction);
+            assert_eq!(butterfly.direction(), direction);
+            check_fft_algorithm(&butterfly, 3, inverse);
+        }
+    }
+
+    #[test]
+    fn test_process_add() {
+        for &len in &[1, 2, 5, 16, 30, 97] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let first = random_signal(len);
+            let second = random_signal(len);
+
+            // by linearity, the sum of the spectra is the spectrum of the sum
+            let mut sum: Vec<Complex<f32>> = first.iter().zip(second.iter()).map(|(&a, &b)| a + b).collect();
+            let mut expected = vec![Zero::zero(); len];
+            fft.process(&mut sum, &mut expected);
+
+            let mut actual = vec![Zero::zero(); len];
+            fft.process_add(&first, &mut actual);
+            fft.process_add(&second, &mut actual);
+            assert!(compare_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_process_gather_out_of_bounds() {
+        let fft = FFTplanner::new(false).plan_fft(4);
+        fft.process_gather(&random_signal(4), &[0, 1, 2, 4], &mut [Zero::zero(); 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_process_interleaved_odd_length() {
+        let fft = FFTplanner::new(false).plan_fft(4);
+        fft.process_interleaved(&[0f32; 7], &mut [0f32; 8]);
+    }
+
+    #[test]
+    fn test_process_multi_in_place() {
+        // covers both the butterflies, which transform in place, and the algorithms that use the default implementation
+        for &len in &[1, 2, 3, 4, 5, 6, 7, 8, 10, 14, 15, 16, 32, 12, 17, 64, 100] {
+            let fft = FFTplanner::new(false).plan_fft(len);
+            let signal = random_signal(len * 3);
+
+            let mut expected = vec![Zero::zero(); len * 3];
+            fft.process_multi(&mut signal.clone(), &mut expected);
+
+            let mut actual = signal.clone();
+            fft.process_multi_in_place(&mut actual);
+            assert_eq!(expected, actual, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_process_rows_and_columns() {
+        let (width, height) = (3, 4);
+        let matrix = random_signal(width * height);
+
+        // transform each column as a separate 1D FFT
+        let column_fft = FFTplanner::new(false).plan_fft(height);
+        let mut expected_columns = matrix.clone();
+        for x in 0..width {
+            let mut column: Vec<Complex<f32>> = (0..height).map(|y| matrix[x + y * width]).collect();
+            let mut spectrum = vec![Zero::zero(); height];
+            column_fft.process(&mut column, &mut spectrum);
+
+            for y in 0..height {
+                expected_columns[x + y * width] = spectrum[y];
+            }
+        }
+
+        let mut actual_columns = matrix.clone();
+        column_fft.process_columns(&mut actual_columns, width, height);
+        assert!(compare_vectors(&expected_columns, &actual_columns), "process_columns() failed");
+
+        // transform each row as a separate 1D FFT
+        let row_fft = FFTplanner::new(false).plan_fft(width);
+        let mut expected_rows = matrix.clone();
+        for (row, expected_row) in matrix.chunks(width).zip(expected_rows.chunks_mut(width)) {
+            row_fft.process(&mut row.to_vec(), expected_row);
+        }
+
+        let mut actual_rows = matrix.clone();
+        row_fft.process_rows(&mut actual_rows, width, height);
+        assert!(compare_vectors(&expected_rows, &actual_rows), "process_rows() failed");
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_process_ndarray() {
+        use ndarray::{Array2, Axis};
+
+        let (width, height) = (3, 4);
+        let matrix = random_signal(width * height);
+        let array = Array2::from_shape_vec((height, width), matrix.clone()).unwrap();
+
+        // the expected row spectra come from process_multi on the row-major matrix
+        let row_fft = FFTplanner::new(false).plan_fft(width);
+        let mut expected_rows = vec![Zero::zero(); width * height];
+        row_fft.process_multi(&mut matrix.clone(), &mut expected_rows);
+
+        let mut actual_rows = array.clone();
+        row_fft.process_ndarray2(actual_rows.view_mut(), Axis(1));
+        assert!(compare_vectors(&expected_rows, actual_rows.as_slice().unwrap()), "process_ndarray2() failed for rows");
+
+        // a row is contiguous, so process_ndarray writes straight into it
+        let mut actual_row = Array2::zeros((1, width));
+        row_fft.process_ndarray(array.row(2), actual_row.row_mut(0));
+        assert!(compare_vectors(&expected_rows[2 * width..3 * width], actual_row.as_slice().unwrap()), "process_ndarray() failed for a row");
+
+        // the expected column spectra come from process_multi on the transposed matrix
+        let column_fft = FFTplanner::new(false).plan_fft(height);
+        let mut transposed = vec![Zero::zero(); width * height];
+        transpose(width, height, &matrix, &mut transposed);
+        let mut expected_columns = vec![Zero::zero(); width * height];
+        column_fft.process_multi(&mut transposed, &mut expected_columns);
+
+        let mut actual_columns = array.clone();
+        column_fft.process_ndarray2(actual_columns.view_mut(), Axis(0));
+        let actual_columns: Vec<Complex<f32>> = actual_columns.t().iter().cloned().collect();
+        assert!(compare_vectors(&expected_columns, &actual_columns), "process_ndarray2() failed for columns");
+
+        // a column isn't contiguous, so process_ndarray has to copy into it
+        let mut actual_column = Array2::zeros((height, width));
+        column_fft.process_ndarray(array.column(1), actual_column.column_mut(1));
+        assert!(compare_vectors(&expected_columns[height..2 * height], &actual_column.column(1).to_vec()), "process_ndarray() failed for a column");
+    }
+}
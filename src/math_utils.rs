@@ -9,8 +9,9 @@ pub fn primitive_root(prime: u64) -> Option<u64> {
         .collect();
     'next: for potential_root in 2..prime {
         // for each distinct factor, if potential_root^(p-1)/factor mod p is 1, reject it
+        // the multiplications can overflow u64 for primes above 2^32, so do them in u128
         for exp in &test_exponents {
-            if modular_exponent(potential_root, *exp, prime) == 1 {
+            if modular_exponent(potential_root as u128, *exp as u128, prime as u128) == 1 {
                 continue 'next;
             }
         }
@@ -97,6 +98,43 @@ pub fn extended_euclidean_algorithm<T: PrimInt + Signed + FromPrimitive>(a: T,
     (r_old, s_old, t_old)
 }
 
+/// Returns true if n is prime. Uses the Miller-Rabin test with a set of bases that's known to give the correct
+/// answer for every 64-bit integer, so the result is exact, not probabilistic.
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for &base in &BASES {
+        if n % base == 0 {
+            return n == base;
+        }
+    }
+
+    // write n - 1 as d * 2^s, with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    // the multiplications can overflow u64, so do them in u128
+    let multiply = |a: u64, b: u64| (a as u128 * b as u128 % n as u128) as u64;
+
+    'next_base: for &base in &BASES {
+        let mut x = modular_exponent(base as u128, d as u128, n as u128) as u64;
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = multiply(x, x);
+            if x == n - 1 {
+                continue 'next_base;
+            }
+        }
+        return false;
+    }
+    true
+}
+
 /// return all of the prime factors of n, but omit duplicate prime factors
 pub fn distinct_prime_factors(mut n: u64) -> Vec<u64> {
     let mut result = Vec::new();
@@ -135,6 +173,33 @@ pub fn distinct_prime_factors(mut n: u64) -> Vec<u64> {
     result
 }
 
+/// returns true if n is a power of three, including 3^0 = 1
+pub fn is_power_of_three(mut n: usize) -> bool {
+    if n == 0 {
+        return false;
+    }
+    while n % 3 == 0 {
+        n /= 3;
+    }
+    n == 1
+}
+
+/// Computes the largest integer whose square is at most n
+///
+/// A floating-point square root is only an estimate for large n: f32 only has 24 bits of precision, so its result can
+/// be off by more than one once n passes 2^48, and even f64 can round up past the true root near usize::MAX, where
+/// squaring the result would overflow. This corrects the estimate using only comparisons that can't overflow.
+pub fn integer_sqrt(n: usize) -> usize {
+    let mut root = (n as f64).sqrt() as usize;
+    while root > 0 && root > n / root {
+        root -= 1;
+    }
+    while root < n / (root + 1) {
+        root += 1;
+    }
+    root
+}
+
 /// Factors an integer into its prime factors.
 pub fn prime_factors(mut n: usize) -> Vec<usize> {
     let mut result = Vec::new();
@@ -145,7 +210,7 @@ pub fn prime_factors(mut n: usize) -> Vec<usize> {
     }
     if n > 1 {
         let mut divisor = 3;
-        let mut limit = (n as f32).sqrt() as usize + 1;
+        let mut limit = integer_sqrt(n) + 1;
         while divisor < limit {
             while n % divisor == 0 {
                 n /= divisor;
@@ -153,7 +218,7 @@ pub fn prime_factors(mut n: usize) -> Vec<usize> {
             }
 
             // recalculate the limit to reduce the amount of other factors we need to check
-            limit = (n as f32).sqrt() as usize + 1;
+            limit = integer_sqrt(n) + 1;
             divisor += 2;
         }
 
@@ -244,6 +309,27 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<u64> = vec![2, 3, 5, 7, 11, 13, 37, 41, 97, 7919, 65537, 2147483647, 4294967291, 2305843009213693951, 18446744073709551557];
+        for n in primes {
+            assert!(is_prime(n), "n = {}", n);
+        }
+
+        // includes carmichael numbers, and strong pseudoprimes to small bases
+        let composites: Vec<u64> = vec![0, 1, 4, 9, 15, 561, 1105, 1729, 2465, 2821, 6601, 8911, 41041, 825265, 3215031751,
+            4294967291 * 4294967279, 18446744073709551615];
+        for n in composites {
+            assert!(!is_prime(n), "n = {}", n);
+        }
+
+        // compare against trial division for small numbers
+        for n in 0..2000u64 {
+            let trial_division = n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0);
+            assert_eq!(is_prime(n), trial_division, "n = {}", n);
+        }
+    }
+
     #[test]
     fn test_prime_factors() {
         let test_list = vec![
@@ -259,4 +345,30 @@ mod unit_tests {
             assert_eq!(factors, expected);
         }
     }
+
+    #[test]
+    fn test_integer_sqrt() {
+        for n in 0..1000 {
+            let root = integer_sqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "n = {}", n);
+        }
+
+        assert_eq!(integer_sqrt((1 << 40) - 1), (1 << 20) - 1);
+        assert_eq!(integer_sqrt(usize::MAX), (1 << (usize::MAX.count_ones() / 2)) - 1);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_prime_factors_of_large_square() {
+        // a f32 square root of this square of a prime rounds down to the prime itself, which used to stop the search
+        // one divisor short and report the square as prime
+        let prime = 16777259;
+        assert_eq!(prime_factors(prime * prime), vec![prime, prime]);
+    }
+
+    #[test]
+    fn test_is_power_of_three() {
+        let powers: Vec<usize> = (0..100).filter(|&n| is_power_of_three(n)).collect();
+        assert_eq!(powers, vec![1, 3, 9, 27, 81]);
+    }
 }
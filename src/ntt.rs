@@ -0,0 +1,222 @@
+use common::verify_length;
+use math_utils;
+
+use ::{Length, IsInverse, Direction};
+
+/// A prime that supports number-theoretic transforms of every power-of-two size up to 2^23
+///
+/// It's `119 * 2^23 + 1`, and its smallest primitive root is 3.
+pub const NTT_PRIME: u64 = 998_244_353;
+
+/// Number-theoretic transform of power-of-two sizes, over the integers modulo a prime
+///
+/// This computes `output[k] = sum(input[n] * w^(n * k)) mod modulus`, where `w` is a primitive `len`-th root of unity
+/// modulo `modulus` (or the inverse of one, for inverse transforms). Since all arithmetic is exact, convolving
+/// integer sequences with a NTT gives exact results, as long as the true results are less than the modulus.
+///
+/// Like the FFT algorithms in this crate, the transform isn't normalized: a forward transform followed by an inverse
+/// transform multiplies each element by `len`, modulo `modulus`.
+///
+/// ~~~
+/// // Compute the NTT of a sequence of size 8
+/// use rustfft::ntt::{Ntt, NTT_PRIME};
+///
+/// let mut input:  Vec<u64> = vec![1, 2, 3, 4, 0, 0, 0, 0];
+/// let mut output: Vec<u64> = vec![0; 8];
+///
+/// let ntt = Ntt::new(8, NTT_PRIME, false);
+/// ntt.process(&mut input, &mut output);
+/// ~~~
+pub struct Ntt {
+    twiddles: Box<[u64]>,
+    modulus: u64,
+    len: usize,
+    inverse: bool,
+}
+
+impl Ntt {
+    /// Precomputes the roots of unity needed to compute NTTs of size `len` modulo `modulus`
+    ///
+    /// `len` must be a power of two that divides `modulus - 1`, and `modulus` must be a prime less than 2^63.
+    /// `direction` is a `Direction`, or a `bool` that's true for inverse NTTs.
+    pub fn new<D: Into<Direction>>(len: usize, modulus: u64, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        assert!(len.is_power_of_two(), "Ntt requires a power-of-two size. Got {}", len);
+        assert!(modulus < 1 << 63, "Ntt requires a modulus less than 2^63. Got {}", modulus);
+        assert!(modulus > 1 && (modulus - 1) % len as u64 == 0, "Ntt size {} must divide modulus - 1 = {}", len, modulus.saturating_sub(1));
+
+        assert!(math_utils::is_prime(modulus), "Ntt requires a prime modulus. Got {}", modulus);
+        let generator = math_utils::primitive_root(modulus).unwrap();
+
+        // a primitive root raised to (modulus - 1) / len is a primitive len-th root of unity
+        let mut root = exponent_mod(generator, (modulus - 1) / len as u64, modulus);
+        if inverse {
+            root = inverse_mod(root, modulus);
+        }
+
+        let mut twiddles = Vec::with_capacity(len / 2);
+        let mut twiddle = 1;
+        for _ in 0..len / 2 {
+            twiddles.push(twiddle);
+            twiddle = multiply_mod(twiddle, root, modulus);
+        }
+
+        Ntt {
+            twiddles: twiddles.into_boxed_slice(),
+            modulus,
+            len,
+            inverse,
+        }
+    }
+
+    /// The modulus that this instance computes NTTs over
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Computes a NTT of the `input` buffer and places the result in the `output` buffer
+    ///
+    /// Every element of `input` must be less than `self.modulus()`. Unlike the FFT algorithms, this method doesn't
+    /// modify `input`.
+    pub fn process(&self, input: &[u64], output: &mut [u64]) {
+        verify_length(input, output, self.len());
+        debug_assert!(input.iter().all(|&element| element < self.modulus), "Ntt inputs must be less than the modulus {}", self.modulus);
+
+        if self.len < 2 {
+            output.copy_from_slice(input);
+            return;
+        }
+
+        // copy the input in bit-reversed order, so that each layer of butterflies can work on contiguous chunks
+        let shift = (0usize).count_zeros() - self.len.trailing_zeros();
+        for (i, &element) in input.iter().enumerate() {
+            output[i.reverse_bits() >> shift] = element;
+        }
+
+        // perform the radix-2 butterflies, one layer at a time
+        let mut half_size = 1;
+        while half_size < self.len {
+            let twiddle_stride = self.len / (half_size * 2);
+
+            for chunk in output.chunks_mut(half_size * 2) {
+                let (evens, odds) = chunk.split_at_mut(half_size);
+                for (i, (even, odd)) in evens.iter_mut().zip(odds.iter_mut()).enumerate() {
+                    let twiddled_odd = multiply_mod(*odd, self.twiddles[i * twiddle_stride], self.modulus);
+
+                    *odd = (*even + self.modulus - twiddled_odd) % self.modulus;
+                    *even = (*even + twiddled_odd) % self.modulus;
+                }
+            }
+
+            half_size *= 2;
+        }
+    }
+}
+impl Length for Ntt {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl IsInverse for Ntt {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+}
+
+#[inline(always)]
+fn multiply_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+// computes the inverse of a modulo the prime modulus, in u128 for the same reason
+fn inverse_mod(a: u64, modulus: u64) -> u64 {
+    math_utils::multiplicative_inverse(a as u128, modulus as u128) as u64
+}
+
+// computes base^exponent % modulus. like multiply_mod, the products can overflow u64, so they're computed in u128
+fn exponent_mod(base: u64, exponent: u64, modulus: u64) -> u64 {
+    math_utils::modular_exponent(base as u128, exponent as u128, modulus as u128) as u64
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn naive_ntt(input: &[u64], modulus: u64, inverse: bool) -> Vec<u64> {
+        let len = input.len();
+        let generator = math_utils::primitive_root(modulus).unwrap();
+        let mut root = exponent_mod(generator, (modulus - 1) / len as u64, modulus);
+        if inverse {
+            root = inverse_mod(root, modulus);
+        }
+
+        (0..len).map(|k| {
+            input.iter().enumerate().fold(0, |sum, (n, &element)| {
+                let twiddle = exponent_mod(root, (n * k % len) as u64, modulus);
+                (sum + multiply_mod(element, twiddle, modulus)) % modulus
+            })
+        }).collect()
+    }
+
+    #[test]
+    fn test_ntt() {
+        // 4179340454199820289 = 29 * 2^57 + 1 is close to the 2^63 limit, so its products overflow u64
+        for &modulus in &[NTT_PRIME, 17, 97, 4179340454199820289] {
+            for pow in 0..5 {
+                let len = 1 << pow;
+                if (modulus - 1) % len as u64 != 0 {
+                    continue;
+                }
+                let input: Vec<u64> = (0..len as u64).map(|i| (i * i * 31 + 7) % modulus).collect();
+
+                for &inverse in &[false, true] {
+                    let mut output = vec![0; len];
+                    Ntt::new(len, modulus, inverse).process(&input, &mut output);
+
+                    assert_eq!(output, naive_ntt(&input, modulus, inverse), "len = {}, modulus = {}, inverse = {}", len, modulus, inverse);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ntt_convolution() {
+        let a: Vec<u64> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5];
+        let b: Vec<u64> = vec![2, 7, 1, 8, 2, 8, 1, 8, 2, 8, 4, 5, 9];
+
+        let mut expected = vec![0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                expected[i + j] += x * y;
+            }
+        }
+
+        // zero-pad both sequences to a power of two that's big enough to hold the whole convolution
+        let len = expected.len().next_power_of_two();
+        let mut padded_a = a.clone();
+        let mut padded_b = b.clone();
+        padded_a.resize(len, 0);
+        padded_b.resize(len, 0);
+
+        let forward = Ntt::new(len, NTT_PRIME, false);
+        let inverse = Ntt::new(len, NTT_PRIME, true);
+
+        let mut spectrum_a = vec![0; len];
+        let mut spectrum_b = vec![0; len];
+        forward.process(&padded_a, &mut spectrum_a);
+        forward.process(&padded_b, &mut spectrum_b);
+
+        let product: Vec<u64> = spectrum_a.iter().zip(spectrum_b.iter()).map(|(&x, &y)| multiply_mod(x, y, NTT_PRIME)).collect();
+        let mut convolution = vec![0; len];
+        inverse.process(&product, &mut convolution);
+
+        // undo the factor of len from the round trip
+        let len_inverse = math_utils::multiplicative_inverse(len as u64, NTT_PRIME);
+        let actual: Vec<u64> = convolution.iter().map(|&x| multiply_mod(x, len_inverse, NTT_PRIME)).collect();
+
+        assert_eq!(&actual[..expected.len()], &expected[..]);
+        assert!(actual[expected.len()..].iter().all(|&x| x == 0));
+    }
+}
@@ -0,0 +1,223 @@
+use num_integer::gcd;
+
+use math_utils;
+use plan::MIN_RADERS_BLUESTEIN_FACTOR;
+
+/// Returns the smallest primitive root modulo `prime`, or `None` if `prime` isn't prime
+///
+/// A primitive root `g` generates every nonzero residue: the powers `g^0, g^1, ..., g^(prime - 2)` are all distinct
+/// modulo `prime`. This is what Rader's Algorithm uses to turn a prime-sized FFT into a cyclic convolution.
+///
+/// ~~~
+/// use rustfft::number_theory::primitive_root;
+///
+/// assert_eq!(primitive_root(7), Some(3));
+/// assert_eq!(primitive_root(8), None);
+/// ~~~
+pub fn primitive_root(prime: usize) -> Option<usize> {
+    if !is_prime(prime) {
+        None
+    } else if prime == 2 {
+        Some(1)
+    } else {
+        math_utils::primitive_root(prime as u64).map(|root| root as usize)
+    }
+}
+
+/// Returns the `x` in `0..modulus` such that `a * x % modulus == 1`, or `None` if `a` and `modulus` aren't coprime
+///
+/// Every value is congruent to 0 modulo 1, so no `x` satisfies this for a `modulus` of 0 or 1, and those return `None`.
+///
+/// ~~~
+/// use rustfft::number_theory::multiplicative_inverse;
+///
+/// assert_eq!(multiplicative_inverse(3, 7), Some(5));
+/// assert_eq!(multiplicative_inverse(4, 8), None);
+/// ~~~
+pub fn multiplicative_inverse(a: usize, modulus: usize) -> Option<usize> {
+    if modulus <= 1 || gcd(a, modulus) != 1 {
+        None
+    } else {
+        // the extended euclidean algorithm's products can overflow u64 for large moduli, so compute it in u128
+        Some(math_utils::multiplicative_inverse((a % modulus) as u128, modulus as u128) as usize)
+    }
+}
+
+/// Returns true if `n` is prime
+///
+/// The FFT planner computes prime sizes with Rader's Algorithm, which is several times slower than computing a nearby
+/// composite size, so this is useful for steering clear of prime FFT sizes. The result is exact for every `usize`.
+///
+/// ~~~
+/// use rustfft::number_theory::is_prime;
+///
+/// assert!(is_prime(1201));
+/// assert!(!is_prime(1200));
+/// ~~~
+pub fn is_prime(n: usize) -> bool {
+    math_utils::is_prime(n as u64)
+}
+
+/// Returns the smallest FFT size that's at least `n` and whose prime factors are all 2, 3, 5, or 7
+///
+/// FFTs of these sizes are computed entirely with the hardcoded butterflies and the fast composite algorithms, so
+/// zero-padding a signal up to this length is usually faster than computing a FFT of its original length.
+///
+/// ~~~
+/// use rustfft::number_theory::next_fast_len;
+///
+/// assert_eq!(next_fast_len(1201), 1215);
+/// assert_eq!(next_fast_len(1024), 1024);
+/// ~~~
+pub fn next_fast_len(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    (n..).find(|&candidate| is_7_smooth(candidate)).unwrap()
+}
+
+/// Returns true if the planner can compute a FFT of size `len` without falling back to a poorly conditioned algorithm
+///
+/// Every size is computed with an FFT algorithm, but not all of them are equally well behaved. Prime factors of 2, 3,
+/// 5, and 7 are handled by the hardcoded butterflies, and a larger prime factor `p` is handled by Rader's Algorithm,
+/// which turns it into a convolution of size `p - 1`. When `p - 1` only has factors of 2, 3, 5, and 7, that
+/// convolution is computed with a cheap and accurate FFT of size `p - 1`. When `p - 1` has a prime factor above 128,
+/// the planner zero-pads the convolution to a power of two instead, which is also fast and accurate. In between, when
+/// the largest prime factor of `p - 1` is from 11 to 128, Rader's Algorithm has to recurse into another Rader's
+/// Algorithm, and each level of recursion adds overhead and rounding error, so this returns false.
+///
+/// Applications can use this to warn about sizes that are worth avoiding. Use `next_fast_len` to find a nearby size.
+///
+/// ~~~
+/// use rustfft::number_theory::is_fast;
+///
+/// assert!(is_fast(1024));
+/// assert!(is_fast(1201)); // 1200 = 2^4 * 3 * 5^2
+/// assert!(is_fast(1019)); // 1018 = 2 * 509, so the convolution is padded to 2048
+/// assert!(!is_fast(1031)); // 1030 = 2 * 5 * 103
+/// ~~~
+pub fn is_fast(len: usize) -> bool {
+    if len < 2 {
+        return true;
+    }
+    math_utils::prime_factors(len).into_iter().all(|factor| {
+        factor <= 7 || is_7_smooth(factor - 1) || largest_prime_factor(factor - 1) > MIN_RADERS_BLUESTEIN_FACTOR
+    })
+}
+
+fn largest_prime_factor(n: usize) -> usize {
+    math_utils::prime_factors(n).into_iter().max().unwrap_or(1)
+}
+
+fn is_7_smooth(mut n: usize) -> bool {
+    for &factor in &[2, 3, 5, 7] {
+        while n % factor == 0 {
+            n /= factor;
+        }
+    }
+    n == 1
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_primitive_root() {
+        let test_list = vec![(2, Some(1)), (3, Some(2)), (7, Some(3)), (11, Some(2)), (47, Some(5)), (7919, Some(7)),
+            (0, None), (1, None), (4, None), (15, None)];
+
+        for (input, expected) in test_list {
+            assert_eq!(primitive_root(input), expected, "input = {}", input);
+        }
+
+        // check that the root actually generates every nonzero residue
+        for &prime in &[2, 3, 5, 7, 11, 13, 101] {
+            let root = primitive_root(prime).unwrap();
+
+            let mut seen = vec![false; prime];
+            let mut power = 1;
+            for _ in 0..prime - 1 {
+                assert!(!seen[power], "prime = {}, root = {}", prime, root);
+                seen[power] = true;
+                power = power * root % prime;
+            }
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_primitive_root_above_2_32() {
+        // 4294967311 - 1 = 2 * 3^2 * 5 * 131 * 364289, and squaring residues this large overflows u64
+        let prime = 4294967311;
+        assert_eq!(primitive_root(prime), Some(3));
+        for &factor in &[2, 3, 5, 131, 364289] {
+            let power = math_utils::modular_exponent(3u128, ((prime - 1) / factor) as u128, prime as u128);
+            assert!(power != 1, "factor = {}", factor);
+        }
+
+        assert_eq!(multiplicative_inverse(3, prime), Some(2863311541));
+        assert_eq!(multiplicative_inverse(prime - 2, prime), Some((prime - 1) / 2));
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<usize> = (0..100).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97]);
+
+        // carmichael numbers
+        for &n in &[561, 1105, 1729, 2465, 2821, 6601, 8911] {
+            assert!(!is_prime(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_next_fast_len() {
+        let test_list = vec![(0, 0), (1, 1), (2, 2), (11, 12), (13, 14), (17, 18), (97, 98), (1000, 1000), (1201, 1215), (65537, 65610)];
+
+        for (input, expected) in test_list {
+            assert_eq!(next_fast_len(input), expected, "input = {}", input);
+        }
+    }
+
+    #[test]
+    fn test_is_fast() {
+        // powers of two and smooth composites are computed with butterflies, Radix4, and the composite algorithms
+        for &len in &[0, 1, 2, 1024, 1 << 20, 360, 1000, 30030 / 13] {
+            assert!(is_fast(len), "len = {}", len);
+        }
+
+        // primes are fast when p - 1 is smooth, because Rader's Algorithm only needs a single level of recursion
+        for &len in &[11, 97, 1201, 65537, 11 * 1201] {
+            assert!(is_fast(len), "len = {}", len);
+        }
+
+        // primes are also fast when p - 1 has a prime factor above 128, because the convolution is padded instead.
+        // 1018 = 2 * 509
+        for &len in &[1019, 2 * 1019] {
+            assert!(is_fast(len), "len = {}", len);
+        }
+
+        // 1030 = 2 * 5 * 103, 2002 = 2 * 7 * 11 * 13, and 46 = 2 * 23
+        for &len in &[1031, 2 * 1031, 2003 * 1024, 47] {
+            assert!(!is_fast(len), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_inverse() {
+        for &modulus in &[2, 7, 9, 10, 29] {
+            for a in 0..modulus * 2 {
+                match multiplicative_inverse(a, modulus) {
+                    Some(inverse) => {
+                        assert!(inverse < modulus);
+                        assert_eq!(a * inverse % modulus, 1, "a = {}, modulus = {}", a, modulus);
+                    }
+                    None => assert!(gcd(a, modulus) != 1, "a = {}, modulus = {}", a, modulus),
+                }
+            }
+        }
+        assert_eq!(multiplicative_inverse(3, 0), None);
+        assert_eq!(multiplicative_inverse(3, 1), None);
+    }
+}
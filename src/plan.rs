@@ -4,7 +4,7 @@ use num_integer::gcd;
 
 use common::FFTnum;
 
-use FFT;
+use ::{FFT, ScaledFFT, Scaling, RealFFT, HalfLengthRealFFT, FullLengthRealFFT, Direction};
 use algorithm::*;
 use algorithm::butterflies::*;
 
@@ -13,8 +13,11 @@ use math_utils;
 
 const MIN_RADIX4_BITS: u32 = 5; // smallest size to consider radix 4 an option is 2^5 = 32
 const MAX_RADIX4_BITS: u32 = 16; // largest size to consider radix 4 an option is 2^16 = 65536
-const BUTTERFLIES: [usize; 9] = [2, 3, 4, 5, 6, 7, 8, 16, 32];
-const COMPOSITE_BUTTERFLIES: [usize; 5] = [4, 6, 8, 16, 32];
+pub const BUTTERFLIES: [usize; 12] = [2, 3, 4, 5, 6, 7, 8, 10, 14, 15, 16, 32];
+const COMPOSITE_BUTTERFLIES: [usize; 8] = [4, 6, 8, 10, 14, 15, 16, 32];
+// if len - 1 has a prime factor larger than this, Rader's Algorithm pads its inner convolution to a power of two
+// instead of planning a FFT of size len - 1, which would need Rader's Algorithm again for that factor
+pub const MIN_RADERS_BLUESTEIN_FACTOR: usize = 128;
 
 /// The FFT planner is used to make new FFT algorithm instances.
 ///
@@ -48,34 +51,266 @@ const COMPOSITE_BUTTERFLIES: [usize; 5] = [4, 6, 8, 16, 32];
 /// safe to drop the planner after creating FFT instances.
 pub struct FFTplanner<T> {
     inverse: bool,
-    algorithm_cache: HashMap<usize, Arc<FFT<T>>>,
-    butterfly_cache: HashMap<usize, Arc<FFTButterfly<T>>>,
+    max_depth: Option<usize>,
+    algorithm_cache: HashMap<(usize, Option<usize>), Arc<dyn FFT<T>>>,
+    butterfly_cache: HashMap<usize, Arc<dyn FFTButterfly<T>>>,
+    opposite_direction_cache: HashMap<usize, Arc<dyn FFT<T>>>,
+    factorizer: Option<Box<Factorizer>>,
+    decision_log: Option<Box<DecisionLog>>,
+}
+
+/// A function that returns the prime factorization of a size, as `(prime, exponent)` pairs. See
+/// `FFTplanner::set_factorizer`
+pub type Factorizer = dyn Fn(usize) -> Vec<(usize, usize)> + Send + Sync;
+
+/// A function that the planner calls with each decision it makes while choosing a plan. See
+/// `FFTplanner::set_decision_log`
+pub type DecisionLog = dyn Fn(PlannerDecision) + Send + Sync;
+
+/// A decision that the planner made while choosing the plan for a size
+///
+/// The variants that split a size explain why the planner divided it the way it did, and are followed by the decisions
+/// for the algorithm that combines the two halves, then the decisions for each half, left first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlannerDecision {
+    /// Used the hardcoded butterfly for this size
+    Butterfly(usize),
+    /// Used `Radix4` for this power of two
+    Radix4(usize),
+    /// Used `Radix3` for this power of three
+    Radix3(usize),
+    /// Used `RadersAlgorithm` for this prime, and will plan an inner FFT of size `len - 1`
+    Raders(usize),
+    /// Used `RadersAlgorithm` for this prime, but because `len - 1` has a large prime factor, padded its inner
+    /// convolution to the power of two `inner_len`, and will plan an inner FFT of that size
+    RadersBluestein { len: usize, inner_len: usize },
+    /// Fell back to a `DFT` for this size, because it's 0 or 1, or because the depth limit was reached
+    DFT(usize),
+    /// Reached the planner's maximum depth at this size, so it will be planned without any inner FFTs
+    DepthLimitReached(usize),
+    /// Split all of the factors of 2 off of `len`, into a power of two and an odd remainder
+    SplitPowerOfTwo { len: usize, power_of_two: usize },
+    /// Split the perfect square `len` into its square root, twice
+    SplitSquare { len: usize, sqrt: usize },
+    /// Split `len` into two sizes as close as possible to its square root
+    SplitBalanced { len: usize, left: usize, right: usize },
+    /// Combined the two halves of a split with `MixedRadix`
+    MixedRadix { left: usize, right: usize },
+    /// Combined two butterflies whose sizes aren't coprime with `MixedRadixDoubleButterfly`
+    MixedRadixDoubleButterfly { left: usize, right: usize },
+    /// Combined two butterflies whose sizes are coprime with `GoodThomasAlgorithmDoubleButterfly`
+    GoodThomasDoubleButterfly { left: usize, right: usize },
 }
 
 impl<T: FFTnum> FFTplanner<T> {
     /// Creates a new FFT planner.
     ///
-    /// If `inverse` is false, this planner will plan forward FFTs. If `inverse` is true, it will plan inverse FFTs.
-    pub fn new(inverse: bool) -> Self {
+    /// `direction` is the direction of the FFTs this planner will plan: a `Direction`, or a `bool` that's
+    /// true for inverse FFTs.
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
         FFTplanner {
-            inverse: inverse,
+            inverse,
+            max_depth: None,
             algorithm_cache: HashMap::new(),
             butterfly_cache: HashMap::new(),
+            opposite_direction_cache: HashMap::new(),
+            factorizer: None,
+            decision_log: None,
+        }
+    }
+
+    /// Replaces the trial division that this planner uses to factor sizes with `factorizer`
+    ///
+    /// The planner factors every size it plans, plus the inner sizes it chooses along the way, like `len - 1` for the
+    /// inner FFT of Rader's Algorithm. Trial division takes time proportional to the square root of the second-largest
+    /// prime factor, so sizes with two large prime factors can be slow to plan. If you already know the factorizations
+    /// of the sizes you plan, or have a faster factoring algorithm, pass it here.
+    ///
+    /// `factorizer` must return the prime factors of its argument, as `(prime, exponent)` pairs in any order. Planning
+    /// with an incorrect factorization will produce FFTs that compute garbage, so in debug builds, the planner asserts
+    /// that the product of the factors is the size it asked for. `factorizer` is only called for sizes of 2 and up.
+    ///
+    /// ~~~
+    /// // Plan a size whose factorization is already known, without factoring it again
+    /// use rustfft::FFTplanner;
+    ///
+    /// let mut planner = FFTplanner::<f32>::new(false);
+    /// planner.set_factorizer(Box::new(|len| match len {
+    ///     1000 => vec![(2, 3), (5, 3)],
+    ///     _ => panic!("unexpected size {}", len),
+    /// }));
+    /// assert_eq!(planner.plan_fft(1000).len(), 1000);
+    /// ~~~
+    pub fn set_factorizer(&mut self, factorizer: Box<Factorizer>) {
+        self.factorizer = Some(factorizer);
+    }
+
+    /// Calls `log` with each decision this planner makes while choosing a plan, for tuning and debugging the planner
+    ///
+    /// Decisions are reported in the order they're made: each algorithm before the algorithms inside it. Only sizes that
+    /// the planner actually chooses a plan for are reported, so a size that's already in this planner's cache reports
+    /// nothing. `describe_plan` always chooses a plan, so it always reports its decisions.
+    ///
+    /// ~~~
+    /// use std::sync::{Arc, Mutex};
+    /// use rustfft::{FFTplanner, PlannerDecision};
+    ///
+    /// let decisions = Arc::new(Mutex::new(Vec::new()));
+    /// let log = Arc::clone(&decisions);
+    ///
+    /// let mut planner = FFTplanner::<f32>::new(false);
+    /// planner.set_decision_log(Box::new(move |decision| log.lock().unwrap().push(decision)));
+    /// planner.plan_fft(11);
+    ///
+    /// assert_eq!(*decisions.lock().unwrap(), vec![PlannerDecision::Raders(11), PlannerDecision::Butterfly(10)]);
+    /// ~~~
+    pub fn set_decision_log(&mut self, log: Box<DecisionLog>) {
+        self.decision_log = Some(log);
+    }
+
+    /// Creates a new FFT planner whose plans are never more than `max_depth` algorithms deep.
+    ///
+    /// Each composite algorithm, like `MixedRadix` or `RadersAlgorithm`, adds a level to the plan, and computing a FFT
+    /// recurses once per level. For most sizes the plans are shallow, but some sizes, like large powers of large primes,
+    /// can produce deep plans. When the plan reaches `max_depth` levels, the remaining FFTs are computed with algorithms
+    /// that don't recurse: a butterfly if the size has one, `Radix4` for powers of two, `Radix3` for powers of three, and
+    /// `DFT` otherwise. This bounds the stack usage of planning and computing FFTs of untrusted sizes, at the cost of
+    /// speed when the cap is reached.
+    /// With a `max_depth` of 0, every FFT is computed with a single algorithm.
+    ///
+    /// `direction` is the direction of the FFTs this planner will plan: a `Direction`, or a `bool` that's
+    /// true for inverse FFTs.
+    pub fn with_max_depth<D: Into<Direction>>(direction: D, max_depth: usize) -> Self {
+        let inverse = direction.into().is_inverse();
+        FFTplanner {
+            max_depth: Some(max_depth),
+            ..Self::new(inverse)
         }
     }
 
     /// Returns a FFT instance which processes signals of size `len`
     /// If this is called multiple times, it will attempt to re-use internal data between instances
-    pub fn plan_fft(&mut self, len: usize) -> Arc<FFT<T>> {
+    ///
+    /// Sizes 0 and 1 are planned as a `DFT`. A FFT of size 1 is the identity, so it copies its input to its output, and a
+    /// FFT of size 0 does nothing.
+    ///
+    /// Any `len` up to `usize::MAX` can be planned: the planner's size arithmetic is exact, and never overflows. In
+    /// practice, the largest size is limited by memory, since most algorithms precompute `len` twiddle factors. Sizes
+    /// with a very large prime factor are also slow to plan, since factoring them takes time proportional to the square
+    /// root of that factor.
+    pub fn plan_fft(&mut self, len: usize) -> Arc<dyn FFT<T>> {
         if len < 2 {
-            Arc::new(DFT::new(len, self.inverse)) as Arc<FFT<T>>
+            self.log_decision(PlannerDecision::DFT(len));
+            Arc::new(DFT::new(len, self.inverse)) as Arc<dyn FFT<T>>
         } else {
-            let factors = math_utils::prime_factors(len);
+            let factors = self.factors_of(len);
             self.plan_fft_with_factors(len, &factors)
         }
     }
 
-    fn plan_butterfly(&mut self, len: usize) -> Arc<FFTButterfly<T>> {
+    /// Returns a description of the plan that `plan_fft(len)` would return, without constructing it
+    ///
+    /// This runs the same selection logic as `plan_fft`, but stops before computing any twiddle factors or other
+    /// precomputed data, so it's cheap even for sizes whose plans would be large. The description has the same format
+    /// as `FFT::describe`, and matches what `plan_fft(len).describe()` would report.
+    ///
+    /// ~~~
+    /// use rustfft::FFTplanner;
+    ///
+    /// let planner = FFTplanner::<f32>::new(false);
+    /// assert_eq!(planner.describe_plan(192), "MixedRadix(Radix4(64), Butterfly3)");
+    /// ~~~
+    pub fn describe_plan(&self, len: usize) -> String {
+        if len < 2 {
+            self.log_decision(PlannerDecision::DFT(len));
+            PlanNode::DFT(len).describe()
+        } else {
+            self.choose_plan(len, &self.factors_of(len)).describe()
+        }
+    }
+
+    /// Returns a FFT instance which processes signals of size `len`, using a factorization of `len` that the caller
+    /// has already computed, instead of factoring `len` again
+    ///
+    /// `factors` must contain the prime factors of `len` as `(prime, exponent)` pairs in any order, the same format
+    /// that a `Factorizer` returns. For example, the factors of 360 are `[(2, 3), (3, 2), (5, 1)]`, and the factors of 1
+    /// are `[]`. The factorization is trusted: in debug builds, this method asserts that the product of the factors is
+    /// `len`, but passing an incorrect factorization in release builds will produce an FFT that computes garbage.
+    pub fn plan_fft_from_factors(&mut self, len: usize, factors: &[(usize, usize)]) -> Arc<dyn FFT<T>> {
+        if len < 2 {
+            self.log_decision(PlannerDecision::DFT(len));
+            Arc::new(DFT::new(len, self.inverse)) as Arc<dyn FFT<T>>
+        } else {
+            let factors = expand_factors(len, factors);
+            self.plan_fft_with_factors(len, &factors)
+        }
+    }
+
+    /// Returns a FFT instance which processes signals of size `len`, and multiplies its output by the factor chosen by
+    /// `scaling`
+    ///
+    /// The unscaled FFT is planned with `plan_fft`, so it shares internal data with other instances from this planner.
+    pub fn plan_fft_with_scale(&mut self, len: usize, scaling: Scaling) -> Arc<dyn FFT<T>> {
+        let fft = self.plan_fft(len);
+        match scaling {
+            Scaling::None => fft,
+            _ => Arc::new(ScaledFFT::new(fft, scaling)) as Arc<dyn FFT<T>>,
+        }
+    }
+
+    /// Returns a FFT instance for each `(len, direction)` pair in `specs`, in the same order. The directions are
+    /// `Direction`s, or `bool`s that are true for inverse FFTs
+    ///
+    /// Every instance is cached, so repeated specs, and later calls to `plan_fft`, return the same instance. Instances
+    /// in this planner's direction are planned with `plan_fft`, so they share inner FFTs and twiddle factors with each
+    /// other. Instances in the opposite direction are built with `inverse_of` from the instance of the same size in
+    /// this planner's direction. That reuses its plan without choosing one again, and derives the precomputed data by
+    /// conjugating or copying it, but the new instance owns its own copy of that data, and its inner FFTs are new
+    /// instances too. So opposite-direction instances share nothing with each other, or with the instances in this
+    /// planner's direction. When many inverse FFTs are needed, a second planner in the opposite direction shares their
+    /// inner FFTs instead.
+    ///
+    /// ~~~
+    /// // Plan the forward and inverse FFTs of size 512, and the forward FFT of size 1024
+    /// use rustfft::FFTplanner;
+    ///
+    /// let mut planner = FFTplanner::<f32>::new(false);
+    /// let ffts = planner.plan_many(&[(512, false), (1024, false), (512, true)]);
+    /// assert!(ffts[2].is_inverse());
+    /// ~~~
+    pub fn plan_many<D: Into<Direction> + Copy>(&mut self, specs: &[(usize, D)]) -> Vec<Arc<dyn FFT<T>>> {
+        specs.iter().map(|&(len, direction)| {
+            if direction.into().is_inverse() == self.inverse {
+                self.plan_fft(len)
+            } else {
+                self.plan_opposite_direction(len)
+            }
+        }).collect()
+    }
+
+    fn plan_opposite_direction(&mut self, len: usize) -> Arc<dyn FFT<T>> {
+        if let Some(instance) = self.opposite_direction_cache.get(&len) {
+            return Arc::clone(instance);
+        }
+        let instance = self.plan_fft(len).inverse_of();
+        self.opposite_direction_cache.insert(len, Arc::clone(&instance));
+        instance
+    }
+
+    /// Returns a FFT instance which processes real signals of size `len`. See `RealFFT` for the lengths of its buffers.
+    ///
+    /// Even sizes are computed with a complex FFT of size `len / 2`, planned with `plan_fft`, so it shares internal data
+    /// with other instances from this planner. Odd sizes are computed with a complex FFT of size `len`.
+    pub fn plan_real_fft(&mut self, len: usize) -> Arc<dyn RealFFT<T>> {
+        if len % 2 == 0 && len > 0 {
+            Arc::new(HalfLengthRealFFT::new(self.plan_fft(len / 2))) as Arc<dyn RealFFT<T>>
+        } else {
+            Arc::new(FullLengthRealFFT::new(self.plan_fft(len))) as Arc<dyn RealFFT<T>>
+        }
+    }
+
+    fn plan_butterfly(&mut self, len: usize) -> Arc<dyn FFTButterfly<T>> {
         let inverse = self.inverse;
         let instance = self.butterfly_cache.entry(len).or_insert_with(|| 
             match len {
@@ -86,6 +321,9 @@ impl<T: FFTnum> FFTplanner<T> {
                 6 => Arc::new(Butterfly6::new(inverse)),
                 7 => Arc::new(Butterfly7::new(inverse)),
                 8 => Arc::new(Butterfly8::new(inverse)),
+                10 => Arc::new(Butterfly10::new(inverse)),
+                14 => Arc::new(Butterfly14::new(inverse)),
+                15 => Arc::new(Butterfly15::new(inverse)),
                 16 => Arc::new(Butterfly16::new(inverse)),
                 32 => Arc::new(Butterfly32::new(inverse)),
                 _ => panic!("Invalid butterfly size: {}", len),
@@ -94,124 +332,764 @@ impl<T: FFTnum> FFTplanner<T> {
         Arc::clone(instance)
     }
     
-    fn plan_fft_with_factors(&mut self, len: usize, factors: &[usize]) -> Arc<FFT<T>> {
-        if self.algorithm_cache.contains_key(&len) {
-            Arc::clone(self.algorithm_cache.get(&len).unwrap())
+    fn plan_fft_with_factors(&mut self, len: usize, factors: &[usize]) -> Arc<dyn FFT<T>> {
+        if let Some(instance) = self.algorithm_cache.get(&(len, self.max_depth)) {
+            return Arc::clone(instance);
+        }
+
+        let plan = self.choose_plan(len, factors);
+        self.build_plan(&plan, self.max_depth)
+    }
+
+    fn choose_plan(&self, len: usize, factors: &[usize]) -> PlanNode {
+        let context = PlanContext {
+            factorize: &|inner_len| self.factors_of(inner_len),
+            log: &|decision| self.log_decision(decision),
+        };
+        choose_plan(len, factors, self.max_depth, &context)
+    }
+
+    fn log_decision(&self, decision: PlannerDecision) {
+        if let Some(ref log) = self.decision_log {
+            log(decision);
+        }
+    }
+
+    // returns the prime factors of `len` in ascending order, with repeated factors listed once per occurrence
+    fn factors_of(&self, len: usize) -> Vec<usize> {
+        match self.factorizer {
+            Some(ref factorizer) => expand_factors(len, &factorizer(len)),
+            None => math_utils::prime_factors(len),
+        }
+    }
+
+    // constructs the algorithms in `plan`, reusing any that this planner has already constructed
+    fn build_plan(&mut self, plan: &PlanNode, remaining_depth: Option<usize>) -> Arc<dyn FFT<T>> {
+        // a plan that was made with fewer levels remaining would also fit here, but a plan that was made with more
+        // levels remaining might not, so when the depth is capped, plans are cached separately for each remaining depth
+        let cache_key = (plan.len(), remaining_depth);
+        if let Some(instance) = self.algorithm_cache.get(&cache_key) {
+            return Arc::clone(instance);
+        }
+
+        let inner_depth = remaining_depth.map(|depth| depth.saturating_sub(1));
+        let instance = match *plan {
+            PlanNode::DFT(len) => Arc::new(DFT::new(len, self.inverse)) as Arc<dyn FFT<T>>,
+            PlanNode::Butterfly(len) => self.construct_butterfly(len),
+            PlanNode::Radix3(len) => Arc::new(Radix3::new(len, self.inverse)) as Arc<dyn FFT<T>>,
+            PlanNode::Radix4(len) => Arc::new(Radix4::new(len, self.inverse)) as Arc<dyn FFT<T>>,
+            PlanNode::Raders(ref inner_plan) => {
+                let inner_fft = self.build_plan(inner_plan, inner_depth);
+                Arc::new(RadersAlgorithm::new(plan.len(), inner_fft)) as Arc<dyn FFT<T>>
+            }
+            PlanNode::RadersBluestein(len, ref inner_plan) => {
+                let inner_fft = self.build_plan(inner_plan, inner_depth);
+                Arc::new(RadersAlgorithm::new_with_bluestein_inner(len, inner_fft)) as Arc<dyn FFT<T>>
+            }
+            PlanNode::MixedRadix(ref left_plan, ref right_plan) => {
+                let left_fft = self.build_plan(left_plan, inner_depth);
+                let right_fft = self.build_plan(right_plan, inner_depth);
+                Arc::new(MixedRadix::new(left_fft, right_fft)) as Arc<dyn FFT<T>>
+            }
+            PlanNode::MixedRadixDoubleButterfly(left_len, right_len) => {
+                Arc::new(MixedRadixDoubleButterfly::new(self.plan_butterfly(left_len), self.plan_butterfly(right_len))) as Arc<dyn FFT<T>>
+            }
+            PlanNode::GoodThomasDoubleButterfly(left_len, right_len) => {
+                Arc::new(GoodThomasAlgorithmDoubleButterfly::new(self.plan_butterfly(left_len), self.plan_butterfly(right_len))) as Arc<dyn FFT<T>>
+            }
+        };
+
+        self.algorithm_cache.insert(cache_key, Arc::clone(&instance));
+        instance
+    }
+
+    fn construct_butterfly(&self, len: usize) -> Arc<dyn FFT<T>> {
+        match len {
+            2 => Arc::new(butterflies::Butterfly2::new(self.inverse)) as Arc<dyn FFT<T>>,
+            3 => Arc::new(butterflies::Butterfly3::new(self.inverse)) as Arc<dyn FFT<T>>,
+            4 => Arc::new(butterflies::Butterfly4::new(self.inverse)) as Arc<dyn FFT<T>>,
+            5 => Arc::new(butterflies::Butterfly5::new(self.inverse)) as Arc<dyn FFT<T>>,
+            6 => Arc::new(butterflies::Butterfly6::new(self.inverse)) as Arc<dyn FFT<T>>,
+            7 => Arc::new(butterflies::Butterfly7::new(self.inverse)) as Arc<dyn FFT<T>>,
+            8 => Arc::new(butterflies::Butterfly8::new(self.inverse)) as Arc<dyn FFT<T>>,
+            10 => Arc::new(butterflies::Butterfly10::new(self.inverse)) as Arc<dyn FFT<T>>,
+            14 => Arc::new(butterflies::Butterfly14::new(self.inverse)) as Arc<dyn FFT<T>>,
+            15 => Arc::new(butterflies::Butterfly15::new(self.inverse)) as Arc<dyn FFT<T>>,
+            16 => Arc::new(butterflies::Butterfly16::new(self.inverse)) as Arc<dyn FFT<T>>,
+            32 => Arc::new(butterflies::Butterfly32::new(self.inverse)) as Arc<dyn FFT<T>>,
+            _ => panic!("Invalid butterfly size: {}", len),
+        }
+    }
+}
+
+// The algorithms that the planner has chosen for a FFT, before any of them have been constructed. Choosing a plan only
+// looks at the size, so `describe_plan` can report a plan without computing any twiddle factors.
+#[allow(clippy::upper_case_acronyms)]
+enum PlanNode {
+    DFT(usize),
+    Butterfly(usize),
+    Radix3(usize),
+    Radix4(usize),
+    Raders(Box<PlanNode>),
+    RadersBluestein(usize, Box<PlanNode>),
+    MixedRadix(Box<PlanNode>, Box<PlanNode>),
+    MixedRadixDoubleButterfly(usize, usize),
+    GoodThomasDoubleButterfly(usize, usize),
+}
+
+impl PlanNode {
+    fn len(&self) -> usize {
+        match *self {
+            PlanNode::DFT(len) | PlanNode::Butterfly(len) | PlanNode::Radix3(len) | PlanNode::Radix4(len) |
+            PlanNode::RadersBluestein(len, _) => len,
+            PlanNode::Raders(ref inner_plan) => inner_plan.len() + 1,
+            PlanNode::MixedRadix(ref left_plan, ref right_plan) => checked_product(left_plan.len(), right_plan.len()),
+            PlanNode::MixedRadixDoubleButterfly(left_len, right_len) |
+            PlanNode::GoodThomasDoubleButterfly(left_len, right_len) => checked_product(left_len, right_len),
+        }
+    }
+
+    // matches what `FFT::describe` reports for the constructed algorithms
+    fn describe(&self) -> String {
+        match *self {
+            PlanNode::DFT(len) => format!("DFT({})", len),
+            PlanNode::Butterfly(len) => format!("Butterfly{}", len),
+            PlanNode::Radix3(len) => format!("Radix3({})", len),
+            PlanNode::Radix4(len) => format!("Radix4({})", len),
+            PlanNode::Raders(ref inner_plan) => format!("RadersAlgorithm({})", inner_plan.describe()),
+            PlanNode::RadersBluestein(len, ref inner_plan) => format!("RadersAlgorithm({}, Bluestein({}))", len, inner_plan.describe()),
+            PlanNode::MixedRadix(ref left_plan, ref right_plan) => format!("MixedRadix({}, {})", left_plan.describe(), right_plan.describe()),
+            PlanNode::MixedRadixDoubleButterfly(left_len, right_len) => format!("MixedRadixDoubleButterfly(Butterfly{}, Butterfly{})", left_len, right_len),
+            PlanNode::GoodThomasDoubleButterfly(left_len, right_len) => format!("GoodThomasAlgorithmDoubleButterfly(Butterfly{}, Butterfly{})", left_len, right_len),
+        }
+    }
+}
+
+// every split of a size is a factorization of it, so the product of the two halves can only overflow if the split
+// was computed wrong. fail loudly rather than building a plan for a wrapped size
+fn checked_product(left_len: usize, right_len: usize) -> usize {
+    left_len.checked_mul(right_len).unwrap_or_else(|| panic!("Plan size {} * {} overflowed usize", left_len, right_len))
+}
+
+// the callbacks that choosing a plan uses: one to factor the inner sizes it chooses, in the same format as
+// `prime_factors`, and one to report each decision
+struct PlanContext<'a> {
+    factorize: &'a dyn Fn(usize) -> Vec<usize>,
+    log: &'a dyn Fn(PlannerDecision),
+}
+
+// `remaining_depth` is the number of levels that the plan may still add, or None if the depth isn't capped
+fn choose_plan(len: usize, factors: &[usize], remaining_depth: Option<usize>, context: &PlanContext) -> PlanNode {
+    if remaining_depth == Some(0) {
+        (context.log)(PlannerDecision::DepthLimitReached(len));
+        choose_plan_without_recursion(len, context)
+    } else {
+        choose_plan_recursive(len, factors, remaining_depth.map(|depth| depth - 1), context)
+    }
+}
+
+fn choose_plan_without_recursion(len: usize, context: &PlanContext) -> PlanNode {
+    if BUTTERFLIES.contains(&len) {
+        (context.log)(PlannerDecision::Butterfly(len));
+        PlanNode::Butterfly(len)
+    } else if len.is_power_of_two() {
+        (context.log)(PlannerDecision::Radix4(len));
+        PlanNode::Radix4(len)
+    } else if math_utils::is_power_of_three(len) {
+        (context.log)(PlannerDecision::Radix3(len));
+        PlanNode::Radix3(len)
+    } else {
+        (context.log)(PlannerDecision::DFT(len));
+        PlanNode::DFT(len)
+    }
+}
+
+fn choose_plan_recursive(len: usize, factors: &[usize], inner_depth: Option<usize>, context: &PlanContext) -> PlanNode {
+    if factors.len() == 1 || COMPOSITE_BUTTERFLIES.contains(&len) {
+        choose_plan_single_factor(len, inner_depth, context)
+
+    } else if math_utils::is_power_of_three(len) {
+        (context.log)(PlannerDecision::Radix3(len));
+        PlanNode::Radix3(len)
+
+    } else if len.trailing_zeros() <= MAX_RADIX4_BITS && len.trailing_zeros() >= MIN_RADIX4_BITS {
+        //the number of trailing zeroes in len is the number of `2` factors
+        //ie if len = 2048 * n, len.trailing_zeros() will equal 11 because 2^11 == 2048
+
+        if len.is_power_of_two() {
+            (context.log)(PlannerDecision::Radix4(len));
+            PlanNode::Radix4(len)
         } else {
-            let result = if factors.len() == 1 || COMPOSITE_BUTTERFLIES.contains(&len) {
-                self.plan_fft_single_factor(len)
+            let left_len = 1 << len.trailing_zeros();
+            let right_len = len / left_len;
 
-            } else if len.trailing_zeros() <= MAX_RADIX4_BITS && len.trailing_zeros() >= MIN_RADIX4_BITS {
-                //the number of trailing zeroes in len is the number of `2` factors
-                //ie if len = 2048 * n, len.trailing_zeros() will equal 11 because 2^11 == 2048
+            // all of the factors of 2 went to the left side, so the right side is odd, and the two are coprime
+            debug_assert!(gcd(left_len, right_len) == 1, "Power-of-two split {} * {} isn't coprime", left_len, right_len);
 
-                if len.is_power_of_two() {
-                    Arc::new(Radix4::new(len, self.inverse))
-                } else {
-                    let left_len = 1 << len.trailing_zeros();
-                    let right_len = len / left_len;
+            let (left_factors, right_factors) = factors.split_at(len.trailing_zeros() as usize);
 
-                    let (left_factors, right_factors) = factors.split_at(len.trailing_zeros() as usize);
+            (context.log)(PlannerDecision::SplitPowerOfTwo { len, power_of_two: left_len });
+            choose_plan_mixed_radix(left_len, left_factors, right_len, right_factors, inner_depth, context)
+        }
 
-                    self.plan_mixed_radix(left_len, left_factors, right_len, right_factors)
-                }
+    } else {
+        let sqrt = math_utils::integer_sqrt(len);
+        if sqrt * sqrt == len {
+            // since len is a perfect square, each of its prime factors is duplicated.
+            // since we know they're sorted, we can loop through them in chunks of 2 and keep one out of each chunk
+            // if the stride iterator ever becomes stabilized, it'll be cleaner to use that instead of chunks
+            let mut sqrt_factors = Vec::with_capacity(factors.len() / 2);
+            for chunk in factors.chunks(2) {
+                sqrt_factors.push(chunk[0]);
+            }
 
-            } else {
-                let sqrt = (len as f32).sqrt() as usize;
-                if sqrt * sqrt == len {
-                    // since len is a perfect square, each of its prime factors is duplicated.
-                    // since we know they're sorted, we can loop through them in chunks of 2 and keep one out of each chunk
-                    // if the stride iterator ever becomes stabilized, it'll be cleaner to use that instead of chunks
-                    let mut sqrt_factors = Vec::with_capacity(factors.len() / 2);
-                    for chunk in factors.chunks(2) {
-                        sqrt_factors.push(chunk[0]);
-                    }
-
-                    self.plan_mixed_radix(sqrt, &sqrt_factors, sqrt, &sqrt_factors)
+            (context.log)(PlannerDecision::SplitSquare { len, sqrt });
+            choose_plan_mixed_radix(sqrt, &sqrt_factors, sqrt, &sqrt_factors, inner_depth, context)
+        } else {
+            //len isn't a perfect square. greedily take factors from the list until both sides are as close as possible to sqrt(len)
+            //TODO: We can probably make this more optimal by using a more sophisticated non-greedy algorithm
+            let mut product = 1;
+            let mut second_half_index = 1;
+            for (i, factor) in factors.iter().enumerate() {
+                if product * *factor > sqrt {
+                    second_half_index = i;
+                    break;
                 } else {
-                    //len isn't a perfect square. greedily take factors from the list until both sides are as close as possible to sqrt(len)
-                    //TODO: We can probably make this more optimal by using a more sophisticated non-greedy algorithm
-                    let mut product = 1;
-                    let mut second_half_index = 1;
-                    for (i, factor) in factors.iter().enumerate() {
-                        if product * *factor > sqrt {
-                            second_half_index = i;
-                            break;
-                        } else {
-                            product = product * *factor;
-                        }
-                    }
-
-                    //we now know that product is the largest it can be without being greater than len / product
-                    //there's one more thing we can try to make them closer together -- if product * factors[index] < len / product,
-                    if product * factors[second_half_index] < len / product {
-                        product = product * factors[second_half_index];
-                        second_half_index = second_half_index + 1;
-                    }
-
-                    //we now have our two FFT sizes: product and product / len
-                    let (left_factors, right_factors) = factors.split_at(second_half_index);
-                    self.plan_mixed_radix(product, left_factors, len / product, right_factors)
+                    product *= *factor;
                 }
-            };
-            self.algorithm_cache.insert(len, Arc::clone(&result));
-            result
+            }
+
+            //we now know that product is the largest it can be without being greater than len / product
+            //there's one more thing we can try to make them closer together -- if product * factors[index] < len / product,
+            if product * factors[second_half_index] < len / product {
+                product *= factors[second_half_index];
+                second_half_index += 1;
+            }
+
+            //we now have our two FFT sizes: product and product / len
+            let (left_factors, right_factors) = factors.split_at(second_half_index);
+            (context.log)(PlannerDecision::SplitBalanced { len, left: product, right: len / product });
+            choose_plan_mixed_radix(product, left_factors, len / product, right_factors, inner_depth, context)
         }
     }
+}
 
-    fn plan_mixed_radix(&mut self,
-                        left_len: usize,
-                        left_factors: &[usize],
-                        right_len: usize,
-                        right_factors: &[usize])
-                        -> Arc<FFT<T>> {
+fn choose_plan_mixed_radix(left_len: usize,
+                           left_factors: &[usize],
+                           right_len: usize,
+                           right_factors: &[usize],
+                           inner_depth: Option<usize>,
+                           context: &PlanContext)
+                           -> PlanNode {
 
-        let left_is_butterfly = BUTTERFLIES.contains(&left_len);
-        let right_is_butterfly = BUTTERFLIES.contains(&right_len);
+    let left_is_butterfly = BUTTERFLIES.contains(&left_len);
+    let right_is_butterfly = BUTTERFLIES.contains(&right_len);
 
-        //if both left_len and right_len are butterflies, use a mixed radix implementation specialized for butterfly sub-FFTs
-        if left_is_butterfly && right_is_butterfly {
-            let left_fft = self.plan_butterfly(left_len);
-            let right_fft = self.plan_butterfly(right_len);
+    //if both left_len and right_len are butterflies, use a mixed radix implementation specialized for butterfly sub-FFTs
+    if left_is_butterfly && right_is_butterfly {
+        // for butterflies, if gcd is 1, we always want to use good-thomas
+        if gcd(left_len, right_len) == 1 {
+            (context.log)(PlannerDecision::GoodThomasDoubleButterfly { left: left_len, right: right_len });
+            PlanNode::GoodThomasDoubleButterfly(left_len, right_len)
+        } else {
+            (context.log)(PlannerDecision::MixedRadixDoubleButterfly { left: left_len, right: right_len });
+            PlanNode::MixedRadixDoubleButterfly(left_len, right_len)
+        }
+    } else {
+        //neither size is a butterfly, so go with the normal algorithm
+        (context.log)(PlannerDecision::MixedRadix { left: left_len, right: right_len });
+        let left_plan = choose_plan(left_len, left_factors, inner_depth, context);
+        let right_plan = choose_plan(right_len, right_factors, inner_depth, context);
 
-            // for butterflies, if gcd is 1, we always want to use good-thomas
-            if gcd(left_len, right_len) == 1 {
-                Arc::new(GoodThomasAlgorithmDoubleButterfly::new(left_fft, right_fft)) as Arc<FFT<T>>
-            } else {
-                Arc::new(MixedRadixDoubleButterfly::new(left_fft, right_fft)) as Arc<FFT<T>>
+        PlanNode::MixedRadix(Box::new(left_plan), Box::new(right_plan))
+    }
+}
+
+fn choose_plan_single_factor(len: usize, inner_depth: Option<usize>, context: &PlanContext) -> PlanNode {
+    if len < 2 {
+        (context.log)(PlannerDecision::DFT(len));
+        PlanNode::DFT(len)
+    } else if BUTTERFLIES.contains(&len) {
+        (context.log)(PlannerDecision::Butterfly(len));
+        PlanNode::Butterfly(len)
+    } else {
+        // prime sizes use Rader's Algorithm, with an inner FFT of size len - 1
+        let inner_len = len - 1;
+        let inner_factors = (context.factorize)(inner_len);
+
+        // the factors are sorted, so the last one is the largest. the padded size can only overflow for sizes that are
+        // far too large to compute anyway, but planning them shouldn't panic
+        let has_large_factor = *inner_factors.last().unwrap() > MIN_RADERS_BLUESTEIN_FACTOR;
+        let padded_len = len.checked_mul(2).and_then(|double_len| (double_len - 3).checked_next_power_of_two());
+        match padded_len {
+            Some(padded_len) if has_large_factor => {
+                (context.log)(PlannerDecision::RadersBluestein { len, inner_len: padded_len });
+                let padded_factors = vec![2; padded_len.trailing_zeros() as usize];
+                PlanNode::RadersBluestein(len, Box::new(choose_plan(padded_len, &padded_factors, inner_depth, context)))
             }
-        } else {
-            //neither size is a butterfly, so go with the normal algorithm
-            let left_fft = self.plan_fft_with_factors(left_len, left_factors);
-            let right_fft = self.plan_fft_with_factors(right_len, right_factors);
+            _ => {
+                (context.log)(PlannerDecision::Raders(len));
+                PlanNode::Raders(Box::new(choose_plan(inner_len, &inner_factors, inner_depth, context)))
+            }
+        }
+    }
+}
 
-            Arc::new(MixedRadix::new(left_fft, right_fft)) as Arc<FFT<T>>
+// expands `(prime, exponent)` pairs into the ascending list of prime factors that the plan selection uses, IE
+// `[(3, 2), (2, 3), (5, 1)]` becomes `[2, 2, 2, 3, 3, 5]`
+fn expand_factors(len: usize, powers: &[(usize, usize)]) -> Vec<usize> {
+    let mut factors: Vec<usize> = powers.iter()
+        .flat_map(|&(prime, exponent)| ::std::iter::repeat(prime).take(exponent))
+        .collect();
+    factors.sort_unstable();
+    debug_assert_eq!(factors.iter().product::<usize>(), len, "The factors {:?} must multiply to {}", powers, len);
+    factors
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_complex::Complex;
+    use num_traits::Zero;
+    use test_utils::{random_signal, check_fft_algorithm, compare_vectors};
+
+    #[test]
+    fn test_planned_radix4_below_blocked_reorder_threshold() {
+        // the docs for DEFAULT_BLOCKED_REORDER_THRESHOLD promise that planned FFTs never use the blocked reordering
+        let planner = FFTplanner::<f32>::new(false);
+        for pow in 0..24 {
+            let description = planner.describe_plan(1 << pow);
+            for radix4_len in description.split("Radix4(").skip(1) {
+                let radix4_len: usize = radix4_len.split(')').next().unwrap().parse().unwrap();
+                assert!(radix4_len < DEFAULT_BLOCKED_REORDER_THRESHOLD, "{}", description);
+            }
         }
     }
 
+    #[test]
+    fn test_plan_power_of_two_uses_radix4() {
+        for pow in MIN_RADIX4_BITS + 1..MAX_RADIX4_BITS + 1 {
+            let len = 1 << pow;
 
-    fn plan_fft_single_factor(&mut self, len: usize) -> Arc<FFT<T>> {
-        match len {
-            0...1 => Arc::new(DFT::new(len, self.inverse)) as Arc<FFT<T>>,
-            2 => Arc::new(butterflies::Butterfly2::new(self.inverse)) as Arc<FFT<T>>,
-            3 => Arc::new(butterflies::Butterfly3::new(self.inverse)) as Arc<FFT<T>>,
-            4 => Arc::new(butterflies::Butterfly4::new(self.inverse)) as Arc<FFT<T>>,
-            5 => Arc::new(butterflies::Butterfly5::new(self.inverse)) as Arc<FFT<T>>,
-            6 => Arc::new(butterflies::Butterfly6::new(self.inverse)) as Arc<FFT<T>>,
-            7 => Arc::new(butterflies::Butterfly7::new(self.inverse)) as Arc<FFT<T>>,
-            8 => Arc::new(butterflies::Butterfly8::new(self.inverse)) as Arc<FFT<T>>,
-            16 => Arc::new(butterflies::Butterfly16::new(self.inverse)) as Arc<FFT<T>>,
-            32 => Arc::new(butterflies::Butterfly32::new(self.inverse)) as Arc<FFT<T>>,
-            _ => self.plan_prime(len),
+            let mut planner = FFTplanner::new(false);
+            let planned_fft = planner.plan_fft(len);
+            let radix4 = Radix4::new(len, false);
+
+            // if the planner chose Radix4, the output will be bit-for-bit identical
+            let mut planned_input = random_signal(len);
+            let mut radix4_input = planned_input.clone();
+            let mut planned_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+            let mut radix4_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+            planned_fft.process(&mut planned_input, &mut planned_output);
+            radix4.process(&mut radix4_input, &mut radix4_output);
+            assert_eq!(planned_output, radix4_output, "length = {}", len);
         }
     }
 
-    fn plan_prime(&mut self, len: usize) -> Arc<FFT<T>> {
-        let inner_fft_len = len - 1;
-        let factors = math_utils::prime_factors(inner_fft_len);
+    #[test]
+    fn test_plan_uses_butterfly10() {
+        let mut planner = FFTplanner::new(false);
+        let butterfly10 = Arc::new(Butterfly10::new(false)) as Arc<dyn FFTButterfly<f32>>;
+        let expected_ffts = [
+            (10, Arc::new(Butterfly10::new(false)) as Arc<dyn FFT<f32>>),
+            (100, Arc::new(MixedRadixDoubleButterfly::new(Arc::clone(&butterfly10), Arc::clone(&butterfly10))) as Arc<dyn FFT<f32>>),
+        ];
+
+        for &(len, ref expected_fft) in &expected_ffts {
+            let planned_fft = planner.plan_fft(len);
+
+            // if the planner chose the same algorithms, the output will be bit-for-bit identical
+            let mut planned_input = random_signal(len);
+            let mut expected_input = planned_input.clone();
+            let mut planned_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+            let mut expected_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+            planned_fft.process(&mut planned_input, &mut planned_output);
+            expected_fft.process(&mut expected_input, &mut expected_output);
+            assert_eq!(planned_output, expected_output, "length = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_plan_smooth_size_uses_butterflies() {
+        // 210 = 2 * 3 * 5 * 7 should be split into 30 * 7, and then 30 into 6 * 5, with a butterfly at every stage
+        let len = 210;
+        let mut planner = FFTplanner::new(false);
+        let planned_fft = planner.plan_fft(len);
+        check_fft_algorithm(&*planned_fft, len, false);
+
+        let inner_fft = Arc::new(GoodThomasAlgorithmDoubleButterfly::new(
+            Arc::new(Butterfly6::new(false)) as Arc<dyn FFTButterfly<f32>>,
+            Arc::new(Butterfly5::new(false)) as Arc<dyn FFTButterfly<f32>>,
+        )) as Arc<dyn FFT<f32>>;
+        let butterfly_fft = MixedRadix::new(inner_fft, Arc::new(Butterfly7::new(false)) as Arc<dyn FFT<f32>>);
+
+        // if the planner chose the same algorithms, the output will be bit-for-bit identical
+        let mut planned_input = random_signal(len);
+        let mut butterfly_input = planned_input.clone();
+        let mut planned_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+        let mut butterfly_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+        planned_fft.process(&mut planned_input, &mut planned_output);
+        butterfly_fft.process(&mut butterfly_input, &mut butterfly_output);
+        assert_eq!(planned_output, butterfly_output);
+    }
+
+    #[test]
+    fn test_plan_with_max_depth() {
+        // 101^2 is normally 101 x 101 mixed radix, with Rader's Algorithm for each 101, and 10 x 10 for each inner 100
+        let len = 101 * 101;
+        let mut planner = FFTplanner::with_max_depth(false, 1);
+        let capped_fft = planner.plan_fft(len);
+
+        // with a depth of 1, the 101s can't recurse into Rader's Algorithm, so they should be DFTs
+        let expected_fft = MixedRadix::new(
+            Arc::new(DFT::new(101, false)) as Arc<dyn FFT<f32>>,
+            Arc::new(DFT::new(101, false)) as Arc<dyn FFT<f32>>,
+        );
+        let mut capped_input = random_signal(len);
+        let mut expected_input = capped_input.clone();
+        let mut capped_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+        let mut expected_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+        capped_fft.process(&mut capped_input, &mut capped_output);
+        expected_fft.process(&mut expected_input, &mut expected_output);
+        assert_eq!(capped_output, expected_output);
+
+        // sizes like 101^4 produce deeper plans, but they're too big to check against a DFT, so check that every depth
+        // cap produces a correct plan for a mix of smaller sizes instead
+        for &len in &[1, 2, 30, 64, 97, 1201, 2 * 3 * 97] {
+            for max_depth in 0..4 {
+                let mut planner = FFTplanner::with_max_depth(false, max_depth);
+                check_fft_algorithm(&*planner.plan_fft(len), len, false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_power_of_two_split() {
+        // 192 = 2^5 * 6 = 2^6 * 3, so every factor of 2 must end up on the power-of-two side, leaving an odd 3
+        let len = 192;
+        let mut planner = FFTplanner::new(false);
+        let planned_fft = planner.plan_fft(len);
+        check_fft_algorithm(&*planned_fft, len, false);
+
+        let expected_fft = MixedRadix::new(
+            Arc::new(Radix4::new(64, false)) as Arc<dyn FFT<f32>>,
+            Arc::new(Butterfly3::new(false)) as Arc<dyn FFT<f32>>,
+        );
+
+        // if the planner chose the same algorithms, the output will be bit-for-bit identical
+        let mut planned_input = random_signal(len);
+        let mut expected_input = planned_input.clone();
+        let mut planned_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+        let mut expected_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+        planned_fft.process(&mut planned_input, &mut planned_output);
+        expected_fft.process(&mut expected_input, &mut expected_output);
+        assert_eq!(planned_output, expected_output);
+
+        // other sizes where the remainder after taking out 2^5 is still even
+        for &len in &[32 * 6, 32 * 10, 32 * 12, 64 * 14, 128 * 18] {
+            check_fft_algorithm(&*planner.plan_fft(len), len, false);
+        }
+    }
+
+    #[test]
+    fn test_plan_huge_sizes() {
+        // usize::MAX is odd and not a perfect square, and its floating-point square root rounds up to 2^32
+        for &len in &[usize::MAX, usize::MAX - 1, (usize::MAX >> 1) + 1] {
+            let factors = math_utils::prime_factors(len);
+            let context = PlanContext { factorize: &math_utils::prime_factors, log: &|_| {} };
+            let plan = choose_plan(len, &factors, None, &context);
+            // PlanNode::len multiplies the sizes of the children back together, and panics if that overflows
+            assert_eq!(plan.len(), len, "len = {}", len);
+
+            assert!(!FFTplanner::<f32>::new(false).describe_plan(len).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_plan_power_of_three() {
+        let mut planner = FFTplanner::new(false);
+        for &len in &[9, 27, 81, 2187] {
+            let fft = planner.plan_fft(len);
+            assert_eq!(fft.describe(), format!("Radix3({})", len));
+            if len <= 81 {
+                check_fft_algorithm(&*fft, len, false);
+            }
+        }
+
+        // 3 is still a butterfly, and a power of three times something else still goes through mixed radix
+        assert_eq!(planner.plan_fft(3).describe(), "Butterfly3");
+        assert_eq!(planner.describe_plan(81 * 7), "MixedRadix(Radix3(27), GoodThomasAlgorithmDoubleButterfly(Butterfly3, Butterfly7))");
+    }
+
+    #[test]
+    fn test_describe_plan() {
+        let len = (1 << 10) * 3 * 7;
+        let planner = FFTplanner::<f32>::new(false);
+        let description = planner.describe_plan(len);
+        assert_eq!(description, "MixedRadix(Radix4(1024), GoodThomasAlgorithmDoubleButterfly(Butterfly3, Butterfly7))");
+        assert_eq!(description, FFTplanner::<f32>::new(false).plan_fft(len).describe());
 
-        let inner_fft = self.plan_fft_with_factors(inner_fft_len, &factors);
+        // the description should match the constructed plan for every kind of node, with and without a depth cap
+        for len in (0..200).chain(vec![1009, 1019, 2048, 10201, 65536 * 3, 1 << 20]) {
+            let mut planner = FFTplanner::<f32>::new(false);
+            assert_eq!(planner.describe_plan(len), planner.plan_fft(len).describe(), "len = {}", len);
 
-        Arc::new(RadersAlgorithm::new(len, inner_fft)) as Arc<FFT<T>>
+            for max_depth in 0..3 {
+                let mut planner = FFTplanner::<f32>::with_max_depth(true, max_depth);
+                assert_eq!(planner.describe_plan(len), planner.plan_fft(len).describe(), "len = {}, max_depth = {}", len, max_depth);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_raders_bluestein() {
+        // 1019 - 1 = 2 * 509, so its inner convolution is padded to a power of two instead of needing Rader's Algorithm
+        // again for 509. 1201 - 1 = 2^4 * 3 * 5^2 has only small factors, so its inner FFT is planned directly
+        for &inverse in &[false, true] {
+            let mut planner = FFTplanner::new(inverse);
+            let fft = planner.plan_fft(1019);
+            assert_eq!(fft.describe(), "RadersAlgorithm(1019, Bluestein(Radix4(2048)))");
+            check_fft_algorithm(&*fft, 1019, inverse);
+            check_fft_algorithm(&*fft.inverse_of(), 1019, !inverse);
+
+            assert!(planner.describe_plan(1201).starts_with("RadersAlgorithm(MixedRadix("));
+        }
+    }
+
+    #[test]
+    fn test_scratch_through_composites() {
+        // the padded convolution's scratch has to reach the padded Rader's FFT through the composite FFTs around it
+        let mut planner = FFTplanner::new(false);
+        let raders = planner.plan_fft(1019);
+        assert_eq!(raders.describe(), "RadersAlgorithm(1019, Bluestein(Radix4(2048)))");
+
+        let composites: Vec<Arc<dyn FFT<f32>>> = vec![
+            planner.plan_fft(2038),
+            Arc::new(GoodThomasAlgorithm::new(planner.plan_fft(6), Arc::clone(&raders))),
+            Arc::new(PrimeFactorAlgorithm::new(vec![planner.plan_fft(4), planner.plan_fft(3), Arc::clone(&raders)])),
+            Arc::new(ScaledFFT::new(planner.plan_fft(3057), Scaling::ByLen)),
+        ];
+        for fft in composites {
+            let len = fft.len();
+            assert_eq!(fft.extra_scratch_len(), 2 * 2048, "{}", fft.describe());
+            assert_eq!(fft.required_scratch_len(), len + 2 * 2048, "{}", fft.describe());
+
+            // reuse one scratch buffer for several signals, to make sure nothing carries over between calls
+            let mut scratch = vec![Zero::zero(); fft.required_scratch_len()];
+            for _ in 0..3 {
+                let signal = random_signal(len);
+                let mut expected = vec![Zero::zero(); len];
+                fft.process(&mut signal.clone(), &mut expected);
+
+                let mut actual = vec![Zero::zero(); len];
+                fft.process_with_scratch(&signal, &mut actual, &mut scratch);
+                assert_eq!(expected, actual, "{}", fft.describe());
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_many() {
+        let mut planner = FFTplanner::new(false);
+        let ffts = planner.plan_many(&[(512, false), (1024, false), (512, true)]);
+
+        for (fft, &(len, inverse)) in ffts.iter().zip(&[(512, false), (1024, false), (512, true)]) {
+            check_fft_algorithm(&**fft, len, inverse);
+        }
+
+        // every instance should be cached, in both directions
+        assert!(Arc::ptr_eq(&ffts[0], &planner.plan_fft(512)));
+        assert!(Arc::ptr_eq(&ffts[1], &planner.plan_fft(1024)));
+        let replanned = planner.plan_many(&[(512, Direction::Inverse), (512, Direction::Forward)]);
+        assert!(Arc::ptr_eq(&ffts[2], &replanned[0]));
+        assert!(Arc::ptr_eq(&ffts[0], &replanned[1]));
+    }
+
+    #[test]
+    fn test_plan_real_fft() {
+        for &len in &[1, 2, 9, 128, 210, 1000, 1001] {
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+            let mut lifted: Vec<Complex<f32>> = signal.iter().map(|&sample| Complex::new(sample, 0f32)).collect();
+
+            // the real FFT should match the first half of the complex FFT of the lifted signal
+            let mut full_spectrum = vec![Zero::zero(); len];
+            FFTplanner::new(false).plan_fft(len).process(&mut lifted, &mut full_spectrum);
+
+            let mut spectrum = vec![Zero::zero(); len / 2 + 1];
+            FFTplanner::new(false).plan_real_fft(len).process(&mut signal.clone(), &mut spectrum);
+            assert!(compare_vectors(&full_spectrum[..len / 2 + 1], &spectrum), "len = {}", len);
+
+            // and the inverse should recover the signal, multiplied by len
+            let mut output = vec![0f32; len];
+            FFTplanner::new(true).plan_real_fft(len).process(&mut output, &mut spectrum);
+            let scaled_signal: Vec<Complex<f32>> = signal.iter().map(|&sample| Complex::new(sample * len as f32, 0f32)).collect();
+            let lifted_output: Vec<Complex<f32>> = output.iter().map(|&sample| Complex::new(sample, 0f32)).collect();
+            assert!(compare_vectors(&scaled_signal, &lifted_output), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_set_factorizer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // a factorizer that counts its calls, and otherwise agrees with trial division
+        let calls = Arc::new(AtomicUsize::new(0));
+        let factorizer_calls = Arc::clone(&calls);
+        let mut planner = FFTplanner::new(false);
+        planner.set_factorizer(Box::new(move |len| {
+            factorizer_calls.fetch_add(1, Ordering::SeqCst);
+            let mut powers: Vec<(usize, usize)> = Vec::new();
+            for factor in math_utils::prime_factors(len) {
+                match powers.last_mut() {
+                    Some(&mut (prime, ref mut exponent)) if prime == factor => *exponent += 1,
+                    _ => powers.push((factor, 1)),
+                }
+            }
+            powers
+        }));
+
+        // 97 * 101 uses Rader's Algorithm on both sides, so the inner sizes 96 and 100 get factored too
+        let len = 97 * 101;
+        let fft = planner.plan_fft(len);
+        assert!(calls.load(Ordering::SeqCst) >= 3);
+        assert_eq!(fft.describe(), FFTplanner::<f32>::new(false).plan_fft(len).describe());
+
+        let signal = random_signal(len);
+        let mut expected = vec![Zero::zero(); len];
+        let mut actual = vec![Zero::zero(); len];
+        FFTplanner::new(false).plan_fft(len).process(&mut signal.clone(), &mut expected);
+        fft.process(&mut signal.clone(), &mut actual);
+        assert!(compare_vectors(&expected, &actual));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_set_factorizer_large_semiprime() {
+        // trial division would have to try every odd divisor up to 2^31 to factor this size. p - 1 and q - 1 only have
+        // small prime factors, so trial division handles the inner sizes of Rader's Algorithm quickly
+        let (p, q) = (2147500081, 2147503489);
+        let mut planner = FFTplanner::<f32>::new(false);
+        planner.set_factorizer(Box::new(move |len| {
+            if len == p * q {
+                vec![(p, 1), (q, 1)]
+            } else {
+                math_utils::prime_factors(len).into_iter().map(|factor| (factor, 1)).collect()
+            }
+        }));
+
+        let description = planner.describe_plan(p * q);
+        assert!(description.starts_with("MixedRadix(RadersAlgorithm("), "{}", description);
+    }
+
+    #[test]
+    fn test_decision_log() {
+        use std::sync::Mutex;
+        use PlannerDecision::*;
+
+        let decisions = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&decisions);
+        let mut planner = FFTplanner::<f32>::new(false);
+        planner.set_decision_log(Box::new(move |decision| log.lock().unwrap().push(decision)));
+
+        // 97 * 101 takes every kind of split on its way down
+        planner.plan_fft(97 * 101);
+        assert_eq!(*decisions.lock().unwrap(), vec![
+            SplitBalanced { len: 97 * 101, left: 97, right: 101 },
+            MixedRadix { left: 97, right: 101 },
+            Raders(97),
+            SplitPowerOfTwo { len: 96, power_of_two: 32 },
+            GoodThomasDoubleButterfly { left: 32, right: 3 },
+            Raders(101),
+            SplitSquare { len: 100, sqrt: 10 },
+            MixedRadixDoubleButterfly { left: 10, right: 10 },
+        ]);
+
+        // the plan is cached now, so planning it again doesn't decide anything, but describing it does
+        decisions.lock().unwrap().clear();
+        planner.plan_fft(97 * 101);
+        assert!(decisions.lock().unwrap().is_empty());
+        planner.describe_plan(1024 * 3);
+        assert_eq!(*decisions.lock().unwrap(), vec![
+            SplitPowerOfTwo { len: 1024 * 3, power_of_two: 1024 },
+            MixedRadix { left: 1024, right: 3 },
+            Radix4(1024),
+            Butterfly(3),
+        ]);
+
+        // when the depth limit is reached, the remaining sizes fall back to algorithms without inner FFTs
+        let depth_decisions = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&depth_decisions);
+        let mut planner = FFTplanner::<f32>::with_max_depth(false, 1);
+        planner.set_decision_log(Box::new(move |decision| log.lock().unwrap().push(decision)));
+        planner.plan_fft(23 * 29);
+        assert_eq!(*depth_decisions.lock().unwrap(), vec![
+            SplitBalanced { len: 23 * 29, left: 23, right: 29 },
+            MixedRadix { left: 23, right: 29 },
+            DepthLimitReached(23),
+            DFT(23),
+            DepthLimitReached(29),
+            DFT(29),
+        ]);
+    }
+
+    #[test]
+    fn test_plan_tiny_sizes() {
+        for &inverse in &[false, true] {
+            let mut planner = FFTplanner::new(inverse);
+
+            // a size-1 FFT must overwrite whatever the output held before
+            let x = Complex::new(1.5f32, -2.5f32);
+            let mut input = vec![x];
+            let mut output = vec![Complex::new(100f32, 100f32)];
+            planner.plan_fft(1).process(&mut input, &mut output);
+            assert_eq!(output, vec![x], "inverse = {}", inverse);
+
+            let mut input = vec![x, x * 2f32, x * 3f32];
+            let mut output = vec![Complex::new(100f32, 100f32); 3];
+            planner.plan_fft(1).process_multi(&mut input, &mut output);
+            assert_eq!(output, vec![x, x * 2f32, x * 3f32], "inverse = {}", inverse);
+
+            let mut empty: Vec<Complex<f32>> = Vec::new();
+            planner.plan_fft(0).process(&mut empty.clone(), &mut empty);
+        }
+    }
+
+    #[test]
+    fn test_plan_fft_from_factors() {
+        for &len in &[1, 2, 12, 64, 97, 360, 1200, 1201] {
+            // group the prime factors into (prime, exponent) pairs, and list them largest first to check that the order
+            // doesn't matter
+            let mut factors: Vec<(usize, usize)> = Vec::new();
+            for prime in math_utils::prime_factors(len).into_iter().rev() {
+                match factors.last_mut() {
+                    Some(&mut (last, ref mut exponent)) if last == prime => *exponent += 1,
+                    _ => factors.push((prime, 1)),
+                }
+            }
+
+            let mut planner = FFTplanner::new(false);
+            let expected_fft = planner.plan_fft(len);
+
+            let mut factors_planner = FFTplanner::new(false);
+            let actual_fft = factors_planner.plan_fft_from_factors(len, &factors);
+
+            assert_eq!(actual_fft.len(), len);
+            assert_eq!(actual_fft.memory_footprint(), expected_fft.memory_footprint(), "length = {}", len);
+
+            // both planners should have chosen exactly the same algorithms, so the output should be bit-for-bit identical
+            let mut expected_input = random_signal(len);
+            let mut actual_input = expected_input.clone();
+            let mut expected_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+            let mut actual_output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+
+            expected_fft.process(&mut expected_input, &mut expected_output);
+            actual_fft.process(&mut actual_input, &mut actual_output);
+            assert_eq!(expected_output, actual_output, "length = {}", len);
+        }
     }
 }
@@ -0,0 +1,324 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use num_integer::gcd;
+
+use common::FFTnum;
+
+use algorithm::*;
+use algorithm::butterflies::*;
+use math_utils;
+use plan::BUTTERFLIES;
+
+use {FFT, Direction};
+
+/// A description of a tree of FFT algorithms, which can be built into a FFT instance
+///
+/// The `FFTplanner` chooses how to decompose a FFT size on its own. `PlanBuilder` is for when you want to choose the
+/// decomposition yourself: each constructor describes one algorithm, and the composite algorithms take the
+/// descriptions of their inner FFTs as arguments. The constructors check that the sizes fit together, so a
+/// description that was successfully constructed will always build.
+///
+/// ~~~
+/// // Build a size-60 FFT from a size-4 butterfly and a size-15 Good-Thomas FFT, instead of letting the planner decide
+/// use rustfft::PlanBuilder;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let plan = PlanBuilder::mixed_radix(
+///     PlanBuilder::butterfly(4),
+///     PlanBuilder::good_thomas(PlanBuilder::butterfly(3), PlanBuilder::butterfly(5)),
+/// );
+/// assert_eq!(plan.len(), 60);
+///
+/// let fft = plan.build(false);
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 60];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 60];
+/// fft.process(&mut input, &mut output);
+/// ~~~
+#[derive(Clone, Debug)]
+pub struct PlanBuilder {
+    len: usize,
+    node: PlanNode,
+}
+
+#[derive(Clone, Debug)]
+enum PlanNode {
+    Dft,
+    Butterfly,
+    Radix4,
+    MixedRadix(Box<PlanBuilder>, Box<PlanBuilder>),
+    GoodThomas(Box<PlanBuilder>, Box<PlanBuilder>),
+    Raders(Box<PlanBuilder>),
+    PrimeFactor(Vec<PlanBuilder>),
+}
+
+impl PlanBuilder {
+    /// Describes a naive `DFT` of size `len`
+    pub fn dft(len: usize) -> Self {
+        PlanBuilder { len, node: PlanNode::Dft }
+    }
+
+    /// Describes a hardcoded butterfly of size `len`. Supported sizes are 2, 3, 4, 5, 6, 7, 8, 10, 14, 15, 16, and 32
+    ///
+    /// Panics if `len` isn't a supported size. See `try_butterfly` for a version that returns an error instead.
+    pub fn butterfly(len: usize) -> Self {
+        Self::try_butterfly(len).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Describes a `Radix4` FFT of size `len`, which must be a power of two
+    ///
+    /// Panics if `len` isn't a power of two. See `try_radix4` for a version that returns an error instead.
+    pub fn radix4(len: usize) -> Self {
+        Self::try_radix4(len).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Describes a `MixedRadix` FFT of size `width.len() * height.len()`
+    pub fn mixed_radix(width: PlanBuilder, height: PlanBuilder) -> Self {
+        PlanBuilder {
+            len: width.len * height.len,
+            node: PlanNode::MixedRadix(Box::new(width), Box::new(height)),
+        }
+    }
+
+    /// Describes a `GoodThomasAlgorithm` FFT of size `width.len() * height.len()`. The two sizes must be coprime
+    ///
+    /// Panics if the sizes aren't coprime. See `try_good_thomas` for a version that returns an error instead.
+    pub fn good_thomas(width: PlanBuilder, height: PlanBuilder) -> Self {
+        Self::try_good_thomas(width, height).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Describes a `RadersAlgorithm` FFT of size `len`, which must be prime. `inner` must have size `len - 1`
+    ///
+    /// Panics if `len` isn't prime or `inner` is the wrong size. See `try_raders` for a version that returns an error
+    /// instead.
+    pub fn raders(len: usize, inner: PlanBuilder) -> Self {
+        Self::try_raders(len, inner).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Describes a `PrimeFactorAlgorithm` FFT whose size is the product of the sizes of `inner`. The inner sizes must be
+    /// pairwise coprime
+    ///
+    /// Panics if `inner` is empty or the sizes aren't pairwise coprime. See `try_prime_factor` for a version that
+    /// returns an error instead.
+    pub fn prime_factor(inner: Vec<PlanBuilder>) -> Self {
+        Self::try_prime_factor(inner).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Same as `butterfly`, but returns `PlanError::InvalidButterfly` instead of panicking
+    pub fn try_butterfly(len: usize) -> Result<Self, PlanError> {
+        if !BUTTERFLIES.contains(&len) {
+            return Err(PlanError::InvalidButterfly { len });
+        }
+        Ok(PlanBuilder { len, node: PlanNode::Butterfly })
+    }
+
+    /// Same as `radix4`, but returns `PlanError::NotPowerOfTwo` instead of panicking
+    pub fn try_radix4(len: usize) -> Result<Self, PlanError> {
+        if !len.is_power_of_two() {
+            return Err(PlanError::NotPowerOfTwo { len });
+        }
+        Ok(PlanBuilder { len, node: PlanNode::Radix4 })
+    }
+
+    /// Same as `good_thomas`, but returns `PlanError::NotCoprime` instead of panicking
+    pub fn try_good_thomas(width: PlanBuilder, height: PlanBuilder) -> Result<Self, PlanError> {
+        if gcd(width.len, height.len) != 1 {
+            return Err(PlanError::NotCoprime { a: width.len, b: height.len });
+        }
+        Ok(PlanBuilder {
+            len: width.len * height.len,
+            node: PlanNode::GoodThomas(Box::new(width), Box::new(height)),
+        })
+    }
+
+    /// Same as `raders`, but returns `PlanError::NotPrime` or `PlanError::WrongInnerLen` instead of panicking
+    pub fn try_raders(len: usize, inner: PlanBuilder) -> Result<Self, PlanError> {
+        if !math_utils::is_prime(len as u64) {
+            return Err(PlanError::NotPrime { len });
+        }
+        if inner.len != len - 1 {
+            return Err(PlanError::WrongInnerLen { len, expected: len - 1, actual: inner.len });
+        }
+        Ok(PlanBuilder {
+            len,
+            node: PlanNode::Raders(Box::new(inner)),
+        })
+    }
+
+    /// Same as `prime_factor`, but returns `PlanError::NoInnerFFTs` or `PlanError::NotCoprime` instead of panicking
+    pub fn try_prime_factor(inner: Vec<PlanBuilder>) -> Result<Self, PlanError> {
+        if inner.is_empty() {
+            return Err(PlanError::NoInnerFFTs);
+        }
+        for (i, a) in inner.iter().enumerate() {
+            for b in &inner[i + 1..] {
+                if gcd(a.len, b.len) != 1 {
+                    return Err(PlanError::NotCoprime { a: a.len, b: b.len });
+                }
+            }
+        }
+        Ok(PlanBuilder {
+            len: inner.iter().map(|plan| plan.len).product(),
+            node: PlanNode::PrimeFactor(inner),
+        })
+    }
+
+    /// The size of the FFT that this description will build
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if this description will build a FFT of size 0
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Creates the FFT instances described by this tree
+    ///
+    /// `direction` is a `Direction`, or a `bool` that's true for an inverse FFT. Unlike
+    /// the planner, this doesn't cache anything: if the same size appears several times in the tree, each occurrence
+    /// gets its own instance.
+    pub fn build<T: FFTnum, D: Into<Direction>>(&self, direction: D) -> Arc<dyn FFT<T>> {
+        let inverse = direction.into().is_inverse();
+        match self.node {
+            PlanNode::Dft => Arc::new(DFT::new(self.len, inverse)) as Arc<dyn FFT<T>>,
+            PlanNode::Butterfly => build_butterfly(self.len, inverse),
+            PlanNode::Radix4 => Arc::new(Radix4::new(self.len, inverse)) as Arc<dyn FFT<T>>,
+            PlanNode::MixedRadix(ref width, ref height) => {
+                Arc::new(MixedRadix::new(width.build(inverse), height.build(inverse))) as Arc<dyn FFT<T>>
+            }
+            PlanNode::GoodThomas(ref width, ref height) => {
+                Arc::new(GoodThomasAlgorithm::new(width.build(inverse), height.build(inverse))) as Arc<dyn FFT<T>>
+            }
+            PlanNode::Raders(ref inner) => {
+                Arc::new(RadersAlgorithm::new(self.len, inner.build(inverse))) as Arc<dyn FFT<T>>
+            }
+            PlanNode::PrimeFactor(ref inner) => {
+                let inner_ffts = inner.iter().map(|plan| plan.build(inverse)).collect();
+                Arc::new(PrimeFactorAlgorithm::new(inner_ffts)) as Arc<dyn FFT<T>>
+            }
+        }
+    }
+}
+
+/// The ways that a `PlanBuilder` constructor can reject its arguments
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlanError {
+    /// There's no hardcoded butterfly of size `len`
+    InvalidButterfly { len: usize },
+    /// `Radix4` was given a size, `len`, that isn't a power of two
+    NotPowerOfTwo { len: usize },
+    /// The inner sizes `a` and `b` of a Good-Thomas or Prime-Factor FFT have a common factor
+    NotCoprime { a: usize, b: usize },
+    /// Rader's Algorithm was given a size, `len`, that isn't prime
+    NotPrime { len: usize },
+    /// Rader's Algorithm of size `len` was given an inner FFT of size `actual` instead of `expected`
+    WrongInnerLen { len: usize, expected: usize, actual: usize },
+    /// The Prime-Factor Algorithm was given no inner FFTs
+    NoInnerFFTs,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PlanError::InvalidButterfly { len } => write!(f, "Invalid butterfly size: {}", len),
+            PlanError::NotPowerOfTwo { len } => write!(f, "Radix4 algorithm requires a power-of-two input size. Got {}", len),
+            PlanError::NotCoprime { a, b } => write!(f, "Invalid inner FFT sizes: {} and {}. Sizes must be coprime", a, b),
+            PlanError::NotPrime { len } => write!(f, "Rader's Algorithm requires a prime size. Got {}", len),
+            PlanError::WrongInnerLen { len, expected, actual } => write!(f, "Rader's Algorithm of size {} requires an inner FFT of size {}. Got {}", len, expected, actual),
+            PlanError::NoInnerFFTs => write!(f, "The Prime-Factor Algorithm requires at least one inner FFT"),
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+fn build_butterfly<T: FFTnum>(len: usize, inverse: bool) -> Arc<dyn FFT<T>> {
+    match len {
+        2 => Arc::new(Butterfly2::new(inverse)),
+        3 => Arc::new(Butterfly3::new(inverse)),
+        4 => Arc::new(Butterfly4::new(inverse)),
+        5 => Arc::new(Butterfly5::new(inverse)),
+        6 => Arc::new(Butterfly6::new(inverse)),
+        7 => Arc::new(Butterfly7::new(inverse)),
+        8 => Arc::new(Butterfly8::new(inverse)),
+        10 => Arc::new(Butterfly10::new(inverse)),
+        14 => Arc::new(Butterfly14::new(inverse)),
+        15 => Arc::new(Butterfly15::new(inverse)),
+        16 => Arc::new(Butterfly16::new(inverse)),
+        32 => Arc::new(Butterfly32::new(inverse)),
+        _ => panic!("Invalid butterfly size: {}", len),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::check_fft_algorithm;
+
+    #[test]
+    fn test_plan_builder_length_12() {
+        let plans = [
+            PlanBuilder::mixed_radix(PlanBuilder::butterfly(4), PlanBuilder::butterfly(3)),
+            PlanBuilder::good_thomas(PlanBuilder::dft(3), PlanBuilder::radix4(4)),
+            PlanBuilder::mixed_radix(PlanBuilder::radix4(2), PlanBuilder::butterfly(6)),
+        ];
+
+        for plan in &plans {
+            assert_eq!(plan.len(), 12);
+            check_fft_algorithm(&*plan.build(false), 12, false);
+            check_fft_algorithm(&*plan.build(true), 12, true);
+        }
+    }
+
+    #[test]
+    fn test_plan_builder_nested() {
+        // 13 = 12 + 1, and 12 = 4 * 3
+        let raders = PlanBuilder::raders(13, PlanBuilder::prime_factor(vec![PlanBuilder::butterfly(4), PlanBuilder::dft(3)]));
+        let plan = PlanBuilder::mixed_radix(raders, PlanBuilder::radix4(8));
+
+        assert_eq!(plan.len(), 104);
+        check_fft_algorithm(&*plan.build(false), 104, false);
+        check_fft_algorithm(&*plan.build(true), 104, true);
+    }
+
+    #[test]
+    fn test_plan_builder_errors() {
+        assert_eq!(PlanBuilder::try_butterfly(9).unwrap_err(), PlanError::InvalidButterfly { len: 9 });
+        assert_eq!(PlanBuilder::try_radix4(12).unwrap_err(), PlanError::NotPowerOfTwo { len: 12 });
+        assert_eq!(PlanBuilder::try_good_thomas(PlanBuilder::butterfly(4), PlanBuilder::butterfly(6)).unwrap_err(),
+            PlanError::NotCoprime { a: 4, b: 6 });
+        assert_eq!(PlanBuilder::try_raders(15, PlanBuilder::dft(14)).unwrap_err(), PlanError::NotPrime { len: 15 });
+        assert_eq!(PlanBuilder::try_raders(13, PlanBuilder::butterfly(8)).unwrap_err(),
+            PlanError::WrongInnerLen { len: 13, expected: 12, actual: 8 });
+        assert_eq!(PlanBuilder::try_prime_factor(vec![]).unwrap_err(), PlanError::NoInnerFFTs);
+        assert_eq!(PlanBuilder::try_prime_factor(vec![PlanBuilder::butterfly(3), PlanBuilder::butterfly(4), PlanBuilder::butterfly(6)]).unwrap_err(),
+            PlanError::NotCoprime { a: 3, b: 6 });
+
+        // valid arguments build the same plans as the panicking constructors
+        let plan = PlanBuilder::try_raders(13, PlanBuilder::try_prime_factor(vec![PlanBuilder::try_radix4(4).unwrap(), PlanBuilder::dft(3)]).unwrap()).unwrap();
+        assert_eq!(plan.len(), 13);
+        check_fft_algorithm(&*plan.build(false), 13, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid butterfly size: 9")]
+    fn test_plan_builder_invalid_butterfly() {
+        PlanBuilder::butterfly(9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_plan_builder_raders_wrong_inner_size() {
+        PlanBuilder::raders(13, PlanBuilder::butterfly(8));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_plan_builder_good_thomas_not_coprime() {
+        PlanBuilder::good_thomas(PlanBuilder::butterfly(4), PlanBuilder::butterfly(6));
+    }
+}
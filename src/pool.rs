@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use num_complex::Complex;
+
+use common::FFTnum;
+
+use {FFT, FFTplanner, Direction};
+
+/// Keeps a FFT instance for every size it has been asked to process, so that signals whose size isn't known until
+/// runtime can be processed without planning a new FFT each time
+///
+/// The first time a size is requested, the pool plans it with its internal `FFTplanner`, which shares data between the
+/// instances it creates. Every later request for the same size returns the same instance. The pool never evicts
+/// instances, so memory usage grows with the number of distinct sizes it has seen.
+///
+/// ~~~
+/// // Process signals of whatever size arrives
+/// use rustfft::FFTpool;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut pool = FFTpool::new(false);
+/// for &len in &[512, 1024, 512] {
+///     let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); len];
+///     let mut output: Vec<Complex<f32>> = vec![Zero::zero(); len];
+///     pool.process(&mut input, &mut output);
+/// }
+/// assert_eq!(pool.cache_hits(), 1);
+/// ~~~
+pub struct FFTpool<T> {
+    planner: FFTplanner<T>,
+    ffts: HashMap<usize, Arc<dyn FFT<T>>>,
+    cache_hits: usize,
+}
+
+impl<T: FFTnum> FFTpool<T> {
+    /// Creates a new, empty FFT pool
+    ///
+    /// `direction` is the direction of the FFTs this pool will compute: a `Direction`, or a `bool` that's
+    /// true for inverse FFTs.
+    pub fn new<D: Into<Direction>>(direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        FFTpool {
+            planner: FFTplanner::new(inverse),
+            ffts: HashMap::new(),
+            cache_hits: 0,
+        }
+    }
+
+    /// Returns the FFT instance for size `len`, planning it if this pool hasn't seen that size before
+    pub fn get(&mut self, len: usize) -> Arc<dyn FFT<T>> {
+        if let Some(fft) = self.ffts.get(&len) {
+            self.cache_hits += 1;
+            return Arc::clone(fft);
+        }
+
+        let fft = self.planner.plan_fft(len);
+        self.ffts.insert(len, Arc::clone(&fft));
+        fft
+    }
+
+    /// Computes a FFT of size `input.len()`, using the pool's instance for that size
+    ///
+    /// `output` must have the same length as `input`. Like `FFT::process`, this uses `input` as scratch space, so its
+    /// contents are destroyed.
+    pub fn process(&mut self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.get(input.len()).process(input, output);
+    }
+
+    /// The number of requests that were answered with an instance the pool had already planned
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits
+    }
+
+    /// The number of distinct sizes this pool holds a FFT instance for
+    pub fn len(&self) -> usize {
+        self.ffts.len()
+    }
+
+    /// Returns true if this pool hasn't planned any FFTs yet
+    pub fn is_empty(&self) -> bool {
+        self.ffts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use test_utils::{random_signal, compare_vectors};
+
+    #[test]
+    fn test_pool_reuses_plans() {
+        let mut pool = FFTpool::new(false);
+        let mut planner = FFTplanner::new(false);
+
+        for (i, &len) in [512, 1024, 512].iter().enumerate() {
+            let signal = random_signal(len);
+
+            let mut input = signal.clone();
+            let mut output = vec![Zero::zero(); len];
+            pool.process(&mut input, &mut output);
+
+            let mut expected_input = signal.clone();
+            let mut expected_output = vec![Zero::zero(); len];
+            planner.plan_fft(len).process(&mut expected_input, &mut expected_output);
+
+            assert!(compare_vectors(&expected_output, &output), "len = {}", len);
+            assert_eq!(pool.cache_hits(), if i == 2 { 1 } else { 0 });
+        }
+        assert_eq!(pool.len(), 2);
+
+        // the same instance should be handed out for the same size
+        let fft = pool.get(512);
+        assert!(Arc::ptr_eq(&fft, &pool.get(512)));
+        assert_eq!(pool.cache_hits(), 3);
+    }
+}
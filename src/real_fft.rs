@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use common::FFTnum;
+use twiddles::{single_twiddle, rotate_90};
+
+use ::{Length, IsInverse, FFT, FFTplanner};
+
+/// An umbrella trait for FFTs of real signals
+///
+/// The spectrum of a real signal of length `len` is Hermitian: bin `len - k` is the complex conjugate of bin `k`. So a
+/// real FFT only computes the first `len / 2 + 1` bins, and for even sizes, it computes them with a complex FFT of half
+/// the size, which takes roughly half the time of lifting the signal to complex and computing the full complex FFT.
+///
+/// A forward instance reads `len` real samples and writes `len / 2 + 1` complex bins. An inverse instance reads
+/// `len / 2 + 1` bins, treats the missing bins as the conjugates of the given ones, and writes the `len` real samples of
+/// the inverse FFT. As with the complex FFTs, neither direction scales its output.
+///
+/// ~~~
+/// // Compute the spectrum of a real signal of size 1234, then transform it back
+/// use rustfft::FFTplanner;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut signal: Vec<f32> = vec![0f32; 1234];
+/// let mut spectrum: Vec<Complex<f32>> = vec![Zero::zero(); 1234 / 2 + 1];
+///
+/// let fft = FFTplanner::new(false).plan_real_fft(1234);
+/// fft.process(&mut signal, &mut spectrum);
+///
+/// let ifft = FFTplanner::new(true).plan_real_fft(1234);
+/// ifft.process(&mut signal, &mut spectrum);
+/// ~~~
+pub trait RealFFT<T: FFTnum>: Length + IsInverse + Sync + Send {
+    /// Computes a FFT between the real signal `real` and the first half of its spectrum `spectrum`. `real` must have
+    /// length `self.len()`, and `spectrum` must have length `self.spectrum_len()`.
+    ///
+    /// Forward instances read `real` and write `spectrum`. Inverse instances read `spectrum` and write `real`, and
+    /// ignore the imaginary parts of bin 0 and, for even sizes, bin `len / 2`, since those are zero in the spectrum of a
+    /// real signal. Either way, the buffer that's read from is used as scratch space, so its contents should be
+    /// considered garbage after calling.
+    fn process(&self, real: &mut [T], spectrum: &mut [Complex<T>]);
+
+    /// Returns the number of complex bins in the spectrum, IE `self.len() / 2 + 1`
+    fn spectrum_len(&self) -> usize {
+        self.len() / 2 + 1
+    }
+}
+
+fn verify_real_lengths<T: FFTnum>(fft: &dyn RealFFT<T>, real: &[T], spectrum: &[Complex<T>]) {
+    assert_eq!(real.len(), fft.len(), "Real buffer is the wrong length. Expected {}, got {}", fft.len(), real.len());
+    assert_eq!(spectrum.len(), fft.spectrum_len(), "Spectrum is the wrong length. Expected {}, got {}", fft.spectrum_len(), spectrum.len());
+}
+
+/// Computes a real FFT of even size `len` by packing the even samples into the real parts and the odd samples into the
+/// imaginary parts of a signal of size `len / 2`, then separating the two halves of that signal's spectrum
+pub struct HalfLengthRealFFT<T> {
+    inner_fft: Arc<dyn FFT<T>>,
+    twiddles: Box<[Complex<T>]>,
+    len: usize,
+    inverse: bool,
+}
+
+impl<T: FFTnum> HalfLengthRealFFT<T> {
+    /// Creates a real FFT of size `2 * inner_fft.len()`, in the same direction as `inner_fft`
+    pub fn new(inner_fft: Arc<dyn FFT<T>>) -> Self {
+        let half_len = inner_fft.len();
+        let len = half_len * 2;
+        let inverse = inner_fft.is_inverse();
+        assert!(half_len > 0, "The inner FFT of a HalfLengthRealFFT must be nonempty");
+
+        HalfLengthRealFFT {
+            inner_fft,
+            twiddles: (0..half_len + 1).map(|i| single_twiddle(i, len, inverse)).collect::<Vec<_>>().into_boxed_slice(),
+            len,
+            inverse,
+        }
+    }
+
+    fn process_forward(&self, real: &[T], spectrum: &mut [Complex<T>]) {
+        let half_len = self.inner_fft.len();
+        let half = T::from_f64(0.5).unwrap();
+
+        let mut packed: Vec<Complex<T>> = real.chunks(2).map(|pair| Complex::new(pair[0], pair[1])).collect();
+        let mut packed_spectrum = vec![Zero::zero(); half_len];
+        self.inner_fft.process(&mut packed, &mut packed_spectrum);
+
+        // the spectra of the even samples and of the odd samples are the Hermitian and anti-Hermitian parts of the
+        // packed spectrum. combine them with a final radix-2 step
+        for (k, (bin, &twiddle)) in spectrum.iter_mut().zip(self.twiddles.iter()).enumerate() {
+            let packed_bin = packed_spectrum[k % half_len];
+            let mirrored_bin = packed_spectrum[(half_len - k) % half_len].conj();
+
+            let even = (packed_bin + mirrored_bin) * half;
+            let odd = rotate_90((packed_bin - mirrored_bin) * half, false);
+            *bin = even + twiddle * odd;
+        }
+    }
+
+    fn process_inverse(&self, spectrum: &[Complex<T>], real: &mut [T]) {
+        let half_len = self.inner_fft.len();
+
+        // undo the radix-2 step of the forward transform to build the spectrum of the packed signal
+        let mut packed_spectrum: Vec<Complex<T>> = (0..half_len).map(|k| {
+            let bin = spectrum[k];
+            let mirrored_bin = spectrum[half_len - k].conj();
+            (bin + mirrored_bin) + rotate_90(self.twiddles[k] * (bin - mirrored_bin), true)
+        }).collect();
+        let mut packed = vec![Zero::zero(); half_len];
+        self.inner_fft.process(&mut packed_spectrum, &mut packed);
+
+        for (pair, element) in real.chunks_mut(2).zip(packed.iter()) {
+            pair[0] = element.re;
+            pair[1] = element.im;
+        }
+    }
+}
+
+impl<T: FFTnum> RealFFT<T> for HalfLengthRealFFT<T> {
+    fn process(&self, real: &mut [T], spectrum: &mut [Complex<T>]) {
+        verify_real_lengths(self, real, spectrum);
+
+        if self.inverse {
+            self.process_inverse(spectrum, real);
+        } else {
+            self.process_forward(real, spectrum);
+        }
+    }
+}
+impl<T> Length for HalfLengthRealFFT<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<T> IsInverse for HalfLengthRealFFT<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+}
+
+/// Computes a real FFT of any size by lifting the signal to complex and computing a complex FFT of the same size
+///
+/// This is how the planner handles odd sizes, which can't be split in half.
+pub struct FullLengthRealFFT<T> {
+    inner_fft: Arc<dyn FFT<T>>,
+}
+
+impl<T: FFTnum> FullLengthRealFFT<T> {
+    /// Creates a real FFT of size `inner_fft.len()`, in the same direction as `inner_fft`
+    pub fn new(inner_fft: Arc<dyn FFT<T>>) -> Self {
+        FullLengthRealFFT { inner_fft }
+    }
+}
+
+impl<T: FFTnum> RealFFT<T> for FullLengthRealFFT<T> {
+    fn process(&self, real: &mut [T], spectrum: &mut [Complex<T>]) {
+        verify_real_lengths(self, real, spectrum);
+
+        let len = self.len();
+        let mut input = vec![Zero::zero(); len];
+        let mut output = vec![Zero::zero(); len];
+
+        if self.inner_fft.is_inverse() {
+            // rebuild the second half of the spectrum from the conjugates of the first half
+            for (k, &bin) in spectrum.iter().enumerate().take(len) {
+                input[k] = bin;
+                if k > 0 {
+                    input[len - k] = bin.conj();
+                }
+            }
+            // for even sizes, the middle bin is its own mirror image, so the loop above overwrote it with its conjugate
+            if len % 2 == 0 && len > 0 {
+                input[len / 2] = spectrum[len / 2];
+            }
+            self.inner_fft.process(&mut input, &mut output);
+
+            for (sample, element) in real.iter_mut().zip(output.iter()) {
+                *sample = element.re;
+            }
+        } else {
+            for (element, &sample) in input.iter_mut().zip(real.iter()) {
+                *element = Complex::new(sample, T::zero());
+            }
+            self.inner_fft.process(&mut input, &mut output);
+
+            // a FFT of size 0 still has a spectrum of length 1, holding the sum of the empty signal
+            spectrum[0] = Zero::zero();
+            for (bin, &element) in spectrum.iter_mut().zip(output.iter()) {
+                *bin = element;
+            }
+        }
+    }
+}
+impl<T: FFTnum> Length for FullLengthRealFFT<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.inner_fft.len()
+    }
+}
+impl<T: FFTnum> IsInverse for FullLengthRealFFT<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inner_fft.is_inverse()
+    }
+}
+
+/// The symmetry of a real signal passed to `symmetric_fft`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// The signal is the first half of an even sequence, `x[0], x[1], ..., x[n - 1], x[n - 2], ..., x[1]`
+    Even,
+    /// The signal is the first half of an odd sequence, `0, x[0], ..., x[n - 1], 0, -x[n - 1], ..., -x[0]`
+    Odd,
+}
+
+/// Computes the FFT of a real signal that's known to be even or odd symmetric, given only its first half
+///
+/// The FFT of an even real sequence is real and even, and the FFT of an odd real sequence is imaginary and odd, so in
+/// both cases the output is real, and only its first half is returned. These are the type-I discrete cosine and sine
+/// transforms, scaled so that they match the full FFT:
+///
+/// - `Symmetry::Even`: `signal` holds `n` samples, extended to the even sequence of length `2 * (n - 1)` described on
+///   `Symmetry::Even`. The result holds bins `0..n` of its FFT, IE
+///   `result[k] = x[0] + (-1)^k * x[n - 1] + 2 * sum(x[j] * cos(pi * j * k / (n - 1)) for j in 1..n - 1)`.
+///   This is the DCT-I. A single sample is its own transform.
+/// - `Symmetry::Odd`: `signal` holds `n` samples, extended to the odd sequence of length `2 * (n + 1)` described on
+///   `Symmetry::Odd`. The result holds the imaginary parts of bins `1..n + 1` of its FFT, IE
+///   `result[k] = -2 * sum(x[j] * sin(pi * (j + 1) * (k + 1) / (n + 1)) for j in 0..n)`. This is the DST-I.
+///
+/// The extended sequence is transformed with a real FFT from `FFTplanner::plan_real_fft`, which is roughly half the cost
+/// of a complex FFT of the same size. Since the extension has even length, the real FFT uses a complex FFT of half the
+/// size internally.
+pub fn symmetric_fft<T: FFTnum>(signal: &[T], symmetry: Symmetry) -> Vec<T> {
+    let len = signal.len();
+
+    let extended: Vec<T> = match symmetry {
+        Symmetry::Even if len < 2 => return signal.to_vec(),
+        Symmetry::Even => signal.iter().chain(signal[1..len - 1].iter().rev()).cloned().collect(),
+        Symmetry::Odd if len == 0 => return Vec::new(),
+        Symmetry::Odd => {
+            let zero = [T::zero()];
+            zero.iter().chain(signal.iter()).chain(zero.iter()).cloned()
+                .chain(signal.iter().rev().map(|&x| -x))
+                .collect()
+        }
+    };
+
+    let mut real = extended;
+    let fft = FFTplanner::new(false).plan_real_fft(real.len());
+    let mut spectrum = vec![Zero::zero(); fft.spectrum_len()];
+    fft.process(&mut real, &mut spectrum);
+
+    match symmetry {
+        Symmetry::Even => spectrum.iter().map(|bin| bin.re).collect(),
+        Symmetry::Odd => spectrum[1..len + 1].iter().map(|bin| bin.im).collect(),
+    }
+}
+
+/// Returns true if `spectrum` is Hermitian symmetric within `tolerance`, IE if it's the full spectrum of a real signal
+///
+/// A spectrum is Hermitian if `X[k] == conj(X[len - k])` for every bin `k`, which also means that the DC bin, and for
+/// even lengths the Nyquist bin, are real. The inverse FFT of a Hermitian spectrum is real, so checking a spectrum
+/// before passing it to a complex inverse FFT catches corrupted or misassembled spectra before they produce a "real"
+/// signal with imaginary parts. The real and imaginary parts of each pair of bins are compared separately, and may
+/// differ by at most `tolerance`. An empty spectrum is Hermitian.
+///
+/// ~~~
+/// use rustfft::is_hermitian;
+/// use rustfft::num_complex::Complex;
+///
+/// let spectrum = vec![Complex::new(4f32, 0f32), Complex::new(1f32, -2f32), Complex::new(3f32, 0f32), Complex::new(1f32, 2f32)];
+/// assert!(is_hermitian(&spectrum, 1e-6));
+///
+/// // the Nyquist bin of an even-length spectrum has to be real
+/// let spectrum = vec![Complex::new(4f32, 0f32), Complex::new(1f32, -2f32), Complex::new(3f32, 0.5f32), Complex::new(1f32, 2f32)];
+/// assert!(!is_hermitian(&spectrum, 1e-6));
+/// ~~~
+pub fn is_hermitian<T: FFTnum + PartialOrd>(spectrum: &[Complex<T>], tolerance: T) -> bool {
+    let len = spectrum.len();
+    if len == 0 {
+        return true;
+    }
+
+    // the DC bin is its own partner, so comparing it with its own conjugate checks that it's real. the same goes for
+    // the Nyquist bin of even lengths, which is bin len / 2
+    (0..len / 2 + 1).all(|k| {
+        let bin = spectrum[k];
+        let partner = spectrum[(len - k) % len];
+        (bin.re - partner.re).abs() <= tolerance && (bin.im + partner.im).abs() <= tolerance
+    })
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use algorithm::DFT;
+    use test_utils::{random_signal, compare_vectors};
+
+    fn real_ffts(len: usize, inverse: bool) -> Vec<Box<dyn RealFFT<f32>>> {
+        let mut ffts: Vec<Box<dyn RealFFT<f32>>> = vec![Box::new(FullLengthRealFFT::new(Arc::new(DFT::new(len, inverse))))];
+        if len % 2 == 0 && len > 0 {
+            ffts.push(Box::new(HalfLengthRealFFT::new(Arc::new(DFT::new(len / 2, inverse)))));
+        }
+        ffts
+    }
+
+    fn lift(real: &[f32]) -> Vec<Complex<f32>> {
+        real.iter().map(|&sample| Complex::new(sample, 0f32)).collect()
+    }
+
+    #[test]
+    fn test_real_fft_forward() {
+        for len in (0..20).chain(vec![30, 31, 64]) {
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+
+            let mut expected = vec![Zero::zero(); len];
+            DFT::new(len, false).process(&mut lift(&signal), &mut expected);
+            expected.resize(len / 2 + 1, Zero::zero());
+
+            for fft in real_ffts(len, false) {
+                let mut spectrum = vec![Zero::zero(); len / 2 + 1];
+                fft.process(&mut signal.clone(), &mut spectrum);
+                assert!(compare_vectors(&expected, &spectrum), "len = {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_real_fft_inverse() {
+        for len in (1..20).chain(vec![30, 31, 64]) {
+            // the spectrum of a real signal, so that the full complex inverse is real
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+            let mut full_spectrum = vec![Zero::zero(); len];
+            DFT::new(len, false).process(&mut lift(&signal), &mut full_spectrum);
+
+            let mut expected = vec![Zero::zero(); len];
+            DFT::new(len, true).process(&mut full_spectrum.clone(), &mut expected);
+
+            for fft in real_ffts(len, true) {
+                let mut output = vec![0f32; len];
+                fft.process(&mut output, &mut full_spectrum[..len / 2 + 1].to_vec());
+                assert!(compare_vectors(&expected, &lift(&output)), "len = {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetric_fft() {
+        for len in 0..20 {
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+
+            // the even extension only exists for 2 samples or more
+            if len >= 2 {
+                let mut extended: Vec<f32> = signal.clone();
+                extended.extend(signal[1..len - 1].iter().rev());
+                let mut full_spectrum = vec![Zero::zero(); extended.len()];
+                DFT::new(extended.len(), false).process(&mut lift(&extended), &mut full_spectrum);
+
+                let actual = symmetric_fft(&signal, Symmetry::Even);
+                assert!(compare_vectors(&full_spectrum[..len], &lift(&actual)), "even, len = {}", len);
+                assert!(full_spectrum.iter().all(|bin| bin.im.abs() < 1e-3), "even, len = {}", len);
+            }
+
+            let mut extended = vec![0f32];
+            extended.extend(signal.iter());
+            extended.push(0f32);
+            extended.extend(signal.iter().rev().map(|&x| -x));
+            let mut full_spectrum = vec![Zero::zero(); extended.len()];
+            DFT::new(extended.len(), false).process(&mut lift(&extended), &mut full_spectrum);
+
+            let actual = symmetric_fft(&signal, Symmetry::Odd);
+            let expected: Vec<Complex<f32>> = full_spectrum[1..len + 1].iter().map(|bin| Complex::new(0f32, bin.im)).collect();
+            let actual_imaginary: Vec<Complex<f32>> = actual.iter().map(|&x| Complex::new(0f32, x)).collect();
+            assert_eq!(actual.len(), len);
+            if len > 0 {
+                assert!(compare_vectors(&expected, &actual_imaginary), "odd, len = {}", len);
+            }
+            assert!(full_spectrum.iter().all(|bin| bin.re.abs() < 1e-3), "odd, len = {}", len);
+        }
+
+        assert_eq!(symmetric_fft(&[2.5f32], Symmetry::Even), vec![2.5f32]);
+        assert!(symmetric_fft::<f32>(&[], Symmetry::Even).is_empty());
+    }
+
+    #[test]
+    fn test_is_hermitian() {
+        for len in (1..20).chain(vec![30, 31, 64]) {
+            let signal: Vec<f32> = random_signal(len).iter().map(|element| element.re).collect();
+            let mut spectrum = vec![Zero::zero(); len];
+            DFT::new(len, false).process(&mut lift(&signal), &mut spectrum);
+            assert!(is_hermitian(&spectrum, 1e-3), "len = {}", len);
+
+            // perturb the imaginary part of one bin, which breaks its symmetry with its partner, or makes the DC or
+            // Nyquist bin complex
+            for bin in 0..len {
+                let mut perturbed = spectrum.clone();
+                perturbed[bin].im += 0.01;
+                assert!(!is_hermitian(&perturbed, 1e-3), "len = {}, bin = {}", len, bin);
+            }
+
+            // the spectrum of a complex signal isn't Hermitian
+            if len > 1 {
+                DFT::new(len, false).process(&mut random_signal(len), &mut spectrum);
+                assert!(!is_hermitian(&spectrum, 1e-3), "len = {}", len);
+            }
+        }
+
+        assert!(is_hermitian::<f32>(&[], 0f32));
+    }
+
+    #[test]
+    #[should_panic(expected = "Spectrum is the wrong length")]
+    fn test_real_fft_wrong_spectrum_len() {
+        let fft = HalfLengthRealFFT::new(Arc::new(DFT::new(4, false)));
+        fft.process(&mut [0f32; 8], &mut [Zero::zero(); 8]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+
+use common::FFTnum;
+
+use ::{Length, IsInverse, FFT, FFTplanner, Direction};
+
+/// A FFT instance whose size and direction can be changed after it has been created
+///
+/// This is intended for situations like audio hosts, where the block size can change at runtime. Internally, this
+/// keeps a planner for each direction, so reconfiguring to a size that has been used before is just a cache lookup,
+/// and reconfiguring to a new size re-uses any inner FFTs that have already been planned.
+///
+/// ~~~
+/// // Process a block of size 256, then a block of size 512
+/// use rustfft::{FFT, ReconfigurableFFT};
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut fft = ReconfigurableFFT::new(256, false);
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 256];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 256];
+/// fft.process(&mut input, &mut output);
+///
+/// fft.reconfigure(512, false);
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 512];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 512];
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct ReconfigurableFFT<T> {
+    forward_planner: FFTplanner<T>,
+    inverse_planner: FFTplanner<T>,
+    fft: Arc<dyn FFT<T>>,
+}
+
+impl<T: FFTnum> ReconfigurableFFT<T> {
+    /// Creates a FFT instance which will initially process signals of size `len`
+    pub fn new<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        let mut forward_planner = FFTplanner::new(false);
+        let mut inverse_planner = FFTplanner::new(true);
+
+        let fft = if inverse {
+            inverse_planner.plan_fft(len)
+        } else {
+            forward_planner.plan_fft(len)
+        };
+
+        ReconfigurableFFT {
+            forward_planner,
+            inverse_planner,
+            fft,
+        }
+    }
+
+    /// Changes the size and direction of the FFTs computed by this instance
+    ///
+    /// If `len` and `direction` match the current configuration, this does nothing. Otherwise, the FFT for the new
+    /// configuration is planned, re-using any previously planned FFTs.
+    pub fn reconfigure<D: Into<Direction>>(&mut self, len: usize, direction: D) {
+        let inverse = direction.into().is_inverse();
+        if len == self.fft.len() && inverse == self.fft.is_inverse() {
+            return;
+        }
+
+        self.fft = if inverse {
+            self.inverse_planner.plan_fft(len)
+        } else {
+            self.forward_planner.plan_fft(len)
+        };
+    }
+}
+
+impl<T: FFTnum> FFT<T> for ReconfigurableFFT<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.fft.process(input, output);
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.fft.process_unchecked(input, output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.fft.process_multi(input, output);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        self.fft.extra_scratch_len()
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        self.fft.process_multi_with_scratch(input, output, scratch);
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        self.fft.inverse_of()
+    }
+    fn memory_footprint(&self) -> usize {
+        self.fft.memory_footprint()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        self.fft.error_bound_estimate()
+    }
+    fn describe(&self) -> String {
+        self.fft.describe()
+    }
+    fn operation_count(&self) -> usize {
+        self.fft.operation_count()
+    }
+}
+impl<T> Length for ReconfigurableFFT<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.fft.len()
+    }
+}
+impl<T> IsInverse for ReconfigurableFFT<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.fft.is_inverse()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use test_utils::check_fft_algorithm;
+
+    #[test]
+    fn test_reconfigure() {
+        let mut fft: ReconfigurableFFT<f32> = ReconfigurableFFT::new(8, false);
+        check_fft_algorithm(&fft, 8, false);
+
+        fft.reconfigure(16, false);
+        check_fft_algorithm(&fft, 16, false);
+
+        fft.reconfigure(16, true);
+        check_fft_algorithm(&fft, 16, true);
+
+        fft.reconfigure(8, false);
+        check_fft_algorithm(&fft, 8, false);
+    }
+
+    #[test]
+    fn test_reconfigure_reuses_plans() {
+        let mut fft: ReconfigurableFFT<f32> = ReconfigurableFFT::new(100, false);
+        let first_plan = Arc::clone(&fft.fft);
+
+        // reconfiguring to the current configuration shouldn't replace the plan
+        fft.reconfigure(100, false);
+        assert!(Arc::ptr_eq(&first_plan, &fft.fft));
+
+        // going to a different size and back should hit the planner's cache
+        fft.reconfigure(200, false);
+        assert!(!Arc::ptr_eq(&first_plan, &fft.fft));
+        fft.reconfigure(100, false);
+        assert!(Arc::ptr_eq(&first_plan, &fft.fft));
+    }
+}
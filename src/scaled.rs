@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+
+use common::{FFTnum, epsilon};
+
+use ::{Length, IsInverse, FFT, FFTplanner, Direction};
+
+/// The factor that a `ScaledFFT` multiplies its output by
+///
+/// RustFFT's algorithms never scale their output, so a forward FFT followed by an inverse FFT multiplies the signal by
+/// `len`. Different applications compensate for this in different ways: some scale the inverse FFT by `1/len`, and
+/// others scale both directions by `1/sqrt(len)`, which makes the transform unitary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Scaling {
+    /// Don't scale the output
+    None,
+    /// Multiply the output by `1/len`
+    ByLen,
+    /// Multiply the output by `1/sqrt(len)`. If both the forward and inverse FFTs use this scaling, the transform is
+    /// unitary, so Parseval's theorem holds without any extra bookkeeping.
+    BySqrtLen,
+}
+
+/// Wraps a FFT instance, and multiplies its output by a scale factor chosen by a `Scaling`
+///
+/// ~~~
+/// // Perform a unitary forward FFT of size 1234
+/// use rustfft::{FFTplanner, Scaling};
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let mut input:  Vec<Complex<f32>> = vec![Zero::zero(); 1234];
+/// let mut output: Vec<Complex<f32>> = vec![Zero::zero(); 1234];
+///
+/// let mut planner = FFTplanner::new(false);
+/// let fft = planner.plan_fft_with_scale(1234, Scaling::BySqrtLen);
+/// fft.process(&mut input, &mut output);
+/// ~~~
+pub struct ScaledFFT<T> {
+    inner_fft: Arc<dyn FFT<T>>,
+    scaling: Scaling,
+    scale: T,
+}
+
+impl<T: FFTnum> ScaledFFT<T> {
+    /// Creates a FFT instance which computes the same FFT as `inner_fft`, then scales the output according to `scaling`
+    pub fn new(inner_fft: Arc<dyn FFT<T>>, scaling: Scaling) -> Self {
+        let len = inner_fft.len().max(1) as f64;
+        let scale = match scaling {
+            Scaling::None => 1f64,
+            Scaling::ByLen => 1f64 / len,
+            Scaling::BySqrtLen => 1f64 / len.sqrt(),
+        };
+
+        ScaledFFT {
+            inner_fft,
+            scaling,
+            scale: T::from_f64(scale).unwrap(),
+        }
+    }
+
+    /// Plans a forward FFT of size `len` that matches NumPy's `numpy.fft.fft` with the default `norm="backward"`, IE
+    /// an unscaled forward FFT
+    ///
+    /// Together with `numpy_inverse`, a forward FFT followed by an inverse FFT gives back the original signal.
+    ///
+    /// ~~~
+    /// // Port numpy.fft.ifft(numpy.fft.fft(signal)) == signal
+    /// use rustfft::{FFT, ScaledFFT};
+    /// use rustfft::num_complex::Complex;
+    /// use rustfft::num_traits::Zero;
+    ///
+    /// let signal = vec![Complex::new(1f32, 0f32), Complex::new(2f32, 0f32), Complex::new(3f32, 0f32), Complex::new(4f32, 0f32)];
+    /// let mut spectrum = vec![Zero::zero(); 4];
+    /// let mut output = vec![Zero::zero(); 4];
+    ///
+    /// ScaledFFT::numpy_forward(4).process(&mut signal.clone(), &mut spectrum);
+    /// assert_eq!(spectrum[0], Complex::new(10f32, 0f32));
+    ///
+    /// ScaledFFT::numpy_inverse(4).process(&mut spectrum, &mut output);
+    /// assert_eq!(output, signal);
+    /// ~~~
+    pub fn numpy_forward(len: usize) -> Self {
+        Self::new(FFTplanner::new(false).plan_fft(len), Scaling::None)
+    }
+
+    /// Plans an inverse FFT of size `len` that matches NumPy's `numpy.fft.ifft` with the default `norm="backward"`, IE
+    /// an inverse FFT scaled by `1/len`
+    pub fn numpy_inverse(len: usize) -> Self {
+        Self::new(FFTplanner::new(true).plan_fft(len), Scaling::ByLen)
+    }
+
+    /// Plans a FFT of size `len` that matches NumPy's `norm="ortho"`, IE a FFT scaled by `1/sqrt(len)`, which is
+    /// unitary in both directions
+    ///
+    /// `direction` is the direction of the FFT: a `Direction`, or a `bool` that's true for an inverse FFT.
+    pub fn ortho<D: Into<Direction>>(len: usize, direction: D) -> Self {
+        let inverse = direction.into().is_inverse();
+        Self::new(FFTplanner::new(inverse).plan_fft(len), Scaling::BySqrtLen)
+    }
+
+    /// Returns the scaling applied by this instance
+    pub fn scaling(&self) -> Scaling {
+        self.scaling
+    }
+
+    fn apply_scale(&self, output: &mut [Complex<T>]) {
+        if self.scaling != Scaling::None {
+            for element in output.iter_mut() {
+                *element = *element * self.scale;
+            }
+        }
+    }
+}
+
+impl<T: FFTnum> FFT<T> for ScaledFFT<T> {
+    fn process(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.inner_fft.process(input, output);
+        self.apply_scale(output);
+    }
+    unsafe fn process_unchecked(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.inner_fft.process_unchecked(input, output);
+        self.apply_scale(output);
+    }
+    fn process_multi(&self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.inner_fft.process_multi(input, output);
+        self.apply_scale(output);
+    }
+    fn extra_scratch_len(&self) -> usize {
+        self.inner_fft.extra_scratch_len()
+    }
+    fn process_multi_with_scratch(&self, input: &mut [Complex<T>], output: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        self.inner_fft.process_multi_with_scratch(input, output, scratch);
+        self.apply_scale(output);
+    }
+    fn inverse_of(&self) -> Arc<dyn FFT<T>> {
+        Arc::new(ScaledFFT {
+            inner_fft: self.inner_fft.inverse_of(),
+            scaling: self.scaling,
+            scale: self.scale,
+        })
+    }
+    fn memory_footprint(&self) -> usize {
+        self.inner_fft.memory_footprint()
+    }
+    fn error_bound_estimate(&self) -> f64 {
+        self.inner_fft.error_bound_estimate() + epsilon::<T>()
+    }
+    fn describe(&self) -> String {
+        format!("ScaledFFT({}, {:?})", self.inner_fft.describe(), self.scaling)
+    }
+    fn operation_count(&self) -> usize {
+        self.inner_fft.operation_count() + if self.scaling == Scaling::None { 0 } else { self.len() }
+    }
+}
+impl<T> Length for ScaledFFT<T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.inner_fft.len()
+    }
+}
+impl<T> IsInverse for ScaledFFT<T> {
+    #[inline(always)]
+    fn is_inverse(&self) -> bool {
+        self.inner_fft.is_inverse()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use algorithm::DFT;
+    use test_utils::{random_signal, compare_vectors};
+
+    fn energy(signal: &[Complex<f32>]) -> f32 {
+        signal.iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    #[test]
+    fn test_scaled_parseval() {
+        for &len in &[1, 2, 7, 16, 30] {
+            let fft = ScaledFFT::new(Arc::new(DFT::new(len, false)), Scaling::BySqrtLen);
+
+            let signal = random_signal(len);
+            let mut input = signal.clone();
+            let mut spectrum = vec![Zero::zero(); len];
+            fft.process(&mut input, &mut spectrum);
+
+            let signal_energy = energy(&signal);
+            let spectrum_energy = energy(&spectrum);
+            assert!((signal_energy - spectrum_energy).abs() < 1e-3 * signal_energy,
+                "len = {}: signal energy {} != spectrum energy {}", len, signal_energy, spectrum_energy);
+
+            // a unitary forward FFT followed by a unitary inverse FFT should give back the original signal
+            let mut output = vec![Zero::zero(); len];
+            fft.inverse_of().process(&mut spectrum, &mut output);
+            assert!(compare_vectors(&signal, &output), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_numpy_conventions() {
+        for &len in &[1, 2, 7, 16, 30] {
+            let signal = random_signal(len);
+            let scale = len as f32;
+
+            let mut unscaled_spectrum = vec![Zero::zero(); len];
+            DFT::new(len, false).process(&mut signal.clone(), &mut unscaled_spectrum);
+            let mut unscaled_output = vec![Zero::zero(); len];
+            DFT::new(len, true).process(&mut unscaled_spectrum.clone(), &mut unscaled_output);
+
+            let mut spectrum = vec![Zero::zero(); len];
+            let numpy_forward = ScaledFFT::numpy_forward(len);
+            numpy_forward.process(&mut signal.clone(), &mut spectrum);
+            assert!(compare_vectors(&unscaled_spectrum, &spectrum), "len = {}", len);
+            assert!(!numpy_forward.is_inverse());
+
+            let mut output = vec![Zero::zero(); len];
+            let numpy_inverse = ScaledFFT::numpy_inverse(len);
+            numpy_inverse.process(&mut unscaled_spectrum.clone(), &mut output);
+            let expected: Vec<Complex<f32>> = unscaled_output.iter().map(|&x| x / scale).collect();
+            assert!(compare_vectors(&expected, &output), "len = {}", len);
+            assert!(numpy_inverse.is_inverse());
+
+            let ortho_forward = ScaledFFT::ortho(len, false);
+            let ortho_inverse = ScaledFFT::ortho(len, true);
+            ortho_forward.process(&mut signal.clone(), &mut spectrum);
+            let expected: Vec<Complex<f32>> = unscaled_spectrum.iter().map(|&x| x / scale.sqrt()).collect();
+            assert!(compare_vectors(&expected, &spectrum), "len = {}", len);
+
+            // an orthonormal forward FFT followed by an orthonormal inverse FFT is the identity
+            ortho_inverse.process(&mut spectrum, &mut output);
+            assert!(compare_vectors(&signal, &output), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_scaled_by_len() {
+        let len = 12;
+        let forward = ScaledFFT::new(Arc::new(DFT::new(len, false)), Scaling::None);
+        let inverse = ScaledFFT::new(Arc::new(DFT::new(len, true)), Scaling::ByLen);
+
+        let signal = random_signal(len);
+        let mut input = signal.clone();
+        let mut spectrum = vec![Zero::zero(); len];
+        forward.process(&mut input, &mut spectrum);
+
+        let mut output = vec![Zero::zero(); len];
+        inverse.process(&mut spectrum, &mut output);
+        assert!(compare_vectors(&signal, &output));
+    }
+}
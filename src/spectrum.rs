@@ -0,0 +1,390 @@
+use num_complex::Complex;
+use num_traits::{Float, FromPrimitive};
+
+use common::{FFTnum, two_pi};
+
+use FFTplanner;
+
+/// The bins of a FFT's output, along with the sample rate and length of the signal they were computed from
+///
+/// Keeping the sample rate with the bins means the conversions between bin indexes and frequencies can't be done with
+/// the wrong sample rate. Frequencies are in the same unit as the sample rate, usually Hz. Like NumPy's `fftfreq`, bins
+/// past the middle of the spectrum represent negative frequencies.
+///
+/// ~~~
+/// use rustfft::FFT;
+/// use rustfft::algorithm::DFT;
+/// use rustfft::num_complex::Complex;
+///
+/// // one cycle of a 1 kHz tone every 8 samples, sampled at 8 kHz
+/// let signal: Vec<Complex<f64>> = (0..8).map(|i| Complex::from_polar(&1f64, &(i as f64 * std::f64::consts::PI / 4f64))).collect();
+///
+/// let spectrum = DFT::new(8, false).process_to_spectrum(&signal, 8000f64);
+/// assert_eq!(spectrum.frequency_of(1), 1000f64);
+/// assert_eq!(spectrum.bin_of(1000f64), 1);
+/// assert_eq!(spectrum.peak().unwrap().0, 1000f64);
+/// ~~~
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum<T> {
+    bins: Vec<Complex<T>>,
+    sample_rate: f64,
+    original_len: usize,
+}
+
+impl<T: FFTnum> Spectrum<T> {
+    /// Wraps the FFT of a signal with `bins.len()` elements, sampled at `sample_rate` samples per unit of time
+    pub fn new(bins: Vec<Complex<T>>, sample_rate: f64) -> Self {
+        assert!(sample_rate > 0f64, "The sample rate must be positive. Got {}", sample_rate);
+        let original_len = bins.len();
+        Spectrum { bins, sample_rate, original_len }
+    }
+
+    /// The bins of the spectrum, in the order the FFT produced them
+    pub fn bins(&self) -> &[Complex<T>] {
+        &self.bins
+    }
+
+    /// Unwraps the bins of the spectrum
+    pub fn into_bins(self) -> Vec<Complex<T>> {
+        self.bins
+    }
+
+    /// The sample rate of the signal the spectrum was computed from
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// The length of the signal the spectrum was computed from
+    pub fn original_len(&self) -> usize {
+        self.original_len
+    }
+
+    /// Returns the frequency that bin `bin` represents. Bins past `original_len / 2` represent negative frequencies
+    pub fn frequency_of(&self, bin: usize) -> f64 {
+        assert!(bin < self.original_len, "Bin {} is out of range for a spectrum of length {}", bin, self.original_len);
+
+        let signed_bin = if bin > self.original_len / 2 { bin as f64 - self.original_len as f64 } else { bin as f64 };
+        signed_bin * self.sample_rate / self.original_len as f64
+    }
+
+    /// Returns the index of the bin whose frequency is closest to `frequency`
+    ///
+    /// Frequencies outside of `[-sample_rate / 2, sample_rate / 2]` alias to a bin inside that range, just like they
+    /// would when sampling a signal.
+    pub fn bin_of(&self, frequency: f64) -> usize {
+        assert!(self.original_len > 0, "An empty spectrum has no bins");
+
+        let len = self.original_len as f64;
+        let bin = (frequency * len / self.sample_rate).round().rem_euclid(len);
+        bin as usize % self.original_len
+    }
+}
+
+impl<T: FFTnum + Float> Spectrum<T> {
+    /// Returns the frequency and magnitude of the bin with the largest magnitude, or `None` if the spectrum is empty
+    ///
+    /// If several bins share the largest magnitude, the first one is returned.
+    pub fn peak(&self) -> Option<(f64, T)> {
+        let mut peak: Option<(usize, T)> = None;
+        for (bin, element) in self.bins.iter().enumerate() {
+            let magnitude = element.norm();
+            match peak {
+                Some((_, peak_magnitude)) if peak_magnitude >= magnitude => {}
+                _ => peak = Some((bin, magnitude)),
+            }
+        }
+        peak.map(|(bin, magnitude)| (self.frequency_of(bin), magnitude))
+    }
+
+    /// Returns the magnitude of the bin whose frequency is closest to `frequency`
+    pub fn magnitude_at(&self, frequency: f64) -> T {
+        self.bins[self.bin_of(frequency)].norm()
+    }
+}
+
+/// Computes the magnitude of each bin of `spectrum` and places the result in `output`
+///
+/// `spectrum` and `output` must have the same length.
+pub fn magnitude<T: FFTnum + Float>(spectrum: &[Complex<T>], output: &mut [T]) {
+    assert_eq!(spectrum.len(), output.len(), "Output is the wrong length. Expected {}, got {}", spectrum.len(), output.len());
+
+    for (bin, out) in spectrum.iter().zip(output.iter_mut()) {
+        *out = bin.norm();
+    }
+}
+
+/// Computes the FFT of the real-valued `signal`, and places the magnitude of each non-negative frequency bin in
+/// `output`
+///
+/// The spectrum of a real signal is conjugate-symmetric, so the bins above `signal.len() / 2` have the same magnitudes
+/// as the bins below it, and only the first `signal.len() / 2 + 1` bins are computed. `output` must have exactly that
+/// length. The FFT is planned internally, and the signal is lifted into a single complex buffer that's transformed
+/// with `process_multi_in_place`, so the only other allocations are the FFT's own scratch space. To analyze many
+/// signals of the same size, plan a FFT once and call `magnitude` on its output instead.
+///
+/// ~~~
+/// use rustfft::spectrum::real_magnitude_spectrum;
+///
+/// let signal = [1f32, 0f32, -1f32, 0f32];
+/// let mut magnitudes = [0f32; 3];
+/// real_magnitude_spectrum(&signal, &mut magnitudes);
+///
+/// assert_eq!(magnitudes, [0f32, 2f32, 0f32]);
+/// ~~~
+pub fn real_magnitude_spectrum<T: FFTnum + Float>(signal: &[T], output: &mut [T]) {
+    let len = signal.len();
+    let expected_output_len = len / 2 + 1;
+    assert_eq!(output.len(), expected_output_len, "Output is the wrong length. Expected {}, got {}", expected_output_len, output.len());
+
+    if len == 0 {
+        // the sum of an empty signal is zero
+        output[0] = T::zero();
+        return;
+    }
+
+    let mut buffer: Vec<Complex<T>> = signal.iter().map(|&element| Complex::new(element, T::zero())).collect();
+    FFTplanner::new(false).plan_fft(len).process_multi_in_place(&mut buffer);
+
+    magnitude(&buffer[..expected_output_len], output);
+}
+
+/// Computes the magnitude of each bin of `spectrum` in decibels relative to `reference`, IE
+/// `20 * log10(|X[k]| / reference)`, and places the result in `output`
+///
+/// Bins quieter than `min_db` (including bins that are exactly zero, whose level would otherwise be negative infinity)
+/// are clamped to `min_db`. `spectrum` and `output` must have the same length.
+///
+/// ~~~
+/// use rustfft::spectrum::magnitude_db;
+/// use rustfft::num_complex::Complex;
+///
+/// let spectrum = [Complex::new(10f32, 0f32), Complex::new(0f32, 1f32), Complex::new(0f32, 0f32)];
+/// let mut levels = [0f32; 3];
+/// magnitude_db(&spectrum, &mut levels, 1f32, -120f32);
+///
+/// assert_eq!(levels, [20f32, 0f32, -120f32]);
+/// ~~~
+pub fn magnitude_db<T: FFTnum + Float>(spectrum: &[Complex<T>], output: &mut [T], reference: T, min_db: T) {
+    assert_eq!(spectrum.len(), output.len(), "Output is the wrong length. Expected {}, got {}", spectrum.len(), output.len());
+
+    let twenty: T = FromPrimitive::from_f64(20f64).unwrap();
+    for (bin, out) in spectrum.iter().zip(output.iter_mut()) {
+        let level = twenty * (bin.norm() / reference).log10();
+        *out = level.max(min_db);
+    }
+}
+
+/// Unwraps a sequence of phases in place, so that the difference between successive elements is never more than pi
+///
+/// Phases computed with `arg()` are wrapped into the range `[-pi, pi]`, so a phase that steadily increases will jump
+/// down by `2 * pi` whenever it passes pi. Whenever the difference between two successive elements is greater than pi,
+/// this adds the multiple of `2 * pi` that brings the difference back into `[-pi, pi]` to that element and every
+/// element after it. The first element is never changed. This matches NumPy's `unwrap` with its default arguments.
+///
+/// ~~~
+/// use rustfft::spectrum::unwrap_phase;
+///
+/// let mut phases = [3f32, -3f32, -1f32];
+/// unwrap_phase(&mut phases);
+///
+/// let two_pi = 2f32 * std::f32::consts::PI;
+/// assert_eq!(phases, [3f32, two_pi - 3f32, two_pi - 1f32]);
+/// ~~~
+pub fn unwrap_phase<T: FFTnum + Float>(phases: &mut [T]) {
+    let two_pi: T = two_pi();
+    let pi = two_pi / (T::one() + T::one());
+
+    let mut correction = T::zero();
+    let mut previous = match phases.first() {
+        Some(&first) => first,
+        None => return,
+    };
+    for phase in phases.iter_mut().skip(1) {
+        let difference = *phase - previous;
+        previous = *phase;
+
+        if difference.abs() >= pi {
+            // wrap the difference into [-pi, pi), keeping a positive jump of exactly pi as pi rather than -pi
+            let mut wrapped = difference - two_pi * ((difference + pi) / two_pi).floor();
+            if wrapped == -pi && difference > T::zero() {
+                wrapped = pi;
+            }
+            correction = correction + wrapped - difference;
+        }
+        *phase = *phase + correction;
+    }
+}
+
+/// Returns the ratio of the energy of `spectrum` to the energy of `signal` times its length
+///
+/// By Parseval's theorem, the energy of an unscaled FFT's output is `len` times the energy of its input, so if
+/// `spectrum` is the FFT of `signal`, the result should be very close to 1. This makes it a cheap sanity check that a
+/// transform preserves energy. If `signal` is entirely zero, the result is NaN.
+///
+/// ~~~
+/// use rustfft::FFTplanner;
+/// use rustfft::spectrum::parseval_check;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::num_traits::Zero;
+///
+/// let signal: Vec<Complex<f64>> = (0..100).map(|i| Complex::new(i as f64, 0f64)).collect();
+/// let mut spectrum = vec![Zero::zero(); 100];
+///
+/// let mut planner = FFTplanner::new(false);
+/// planner.plan_fft(100).process(&mut signal.clone(), &mut spectrum);
+///
+/// assert!((parseval_check(&signal, &spectrum) - 1f64).abs() < 1e-10);
+/// ~~~
+pub fn parseval_check<T: FFTnum + Float>(signal: &[Complex<T>], spectrum: &[Complex<T>]) -> T {
+    assert_eq!(signal.len(), spectrum.len(), "Spectrum is the wrong length. Expected {}, got {}", signal.len(), spectrum.len());
+
+    let signal_energy = signal.iter().fold(T::zero(), |sum, element| sum + element.norm_sqr());
+    let spectrum_energy = spectrum.iter().fold(T::zero(), |sum, element| sum + element.norm_sqr());
+    let len: T = FromPrimitive::from_usize(signal.len()).unwrap();
+
+    spectrum_energy / (signal_energy * len)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use algorithm::DFT;
+    use test_utils::random_signal;
+    use {FFT, FFTplanner};
+
+    #[test]
+    fn test_magnitude() {
+        let spectrum = [Complex::new(3f32, 4f32), Complex::new(0f32, -2f32), Complex::zero()];
+        let mut output = [0f32; 3];
+        magnitude(&spectrum, &mut output);
+        assert_eq!(output, [5f32, 2f32, 0f32]);
+    }
+
+    #[test]
+    fn test_real_magnitude_spectrum() {
+        for &len in &[64, 63] {
+            // a pure cosine at bin 5 has two bins of magnitude len / 2, at bins 5 and len - 5
+            let signal: Vec<f64> = (0..len).map(|i| (2f64 * ::std::f64::consts::PI * 5f64 * i as f64 / len as f64).cos()).collect();
+            let mut magnitudes = vec![0f64; len / 2 + 1];
+            real_magnitude_spectrum(&signal, &mut magnitudes);
+
+            for (bin, &actual) in magnitudes.iter().enumerate() {
+                let expected = if bin == 5 { len as f64 / 2f64 } else { 0f64 };
+                assert!((actual - expected).abs() < 1e-9, "len = {}, bin = {}, magnitude = {}", len, bin, actual);
+            }
+        }
+
+        let mut output = [1f32];
+        real_magnitude_spectrum(&[], &mut output);
+        assert_eq!(output, [0f32]);
+    }
+
+    #[test]
+    fn test_magnitude_db() {
+        let len = 16;
+        let dft = DFT::new(len, false);
+
+        // the spectrum of an impulse is flat with magnitude 1, which is 0 dB
+        let mut impulse = vec![Complex::zero(); len];
+        impulse[0] = Complex::new(1f32, 0f32);
+        let mut spectrum = vec![Complex::zero(); len];
+        dft.process(&mut impulse, &mut spectrum);
+
+        let mut levels = vec![0f32; len];
+        magnitude_db(&spectrum, &mut levels, 1f32, -100f32);
+        for &level in &levels {
+            assert!(level.abs() < 1e-4, "level = {}", level);
+        }
+
+        // relative to a reference of 10, the same spectrum is at -20 dB
+        magnitude_db(&spectrum, &mut levels, 10f32, -100f32);
+        for &level in &levels {
+            assert!((level + 20f32).abs() < 1e-4, "level = {}", level);
+        }
+
+        // silence should be clamped to the floor rather than being -inf
+        let mut silence = vec![Complex::zero(); len];
+        dft.process(&mut silence, &mut spectrum);
+        magnitude_db(&spectrum, &mut levels, 1f32, -100f32);
+        for &level in &levels {
+            assert_eq!(level, -100f32);
+        }
+    }
+
+    #[test]
+    fn test_unwrap_phase() {
+        // a phase that increases by 1 radian per sample wraps every 6 or 7 samples
+        let expected: Vec<f64> = (0..40).map(|i| i as f64 - 5f64).collect();
+        let mut phases: Vec<f64> = expected.iter().map(|&phase| Complex::from_polar(&1f64, &phase).arg()).collect();
+        unwrap_phase(&mut phases);
+
+        assert!(phases.windows(2).all(|pair| pair[0] < pair[1]));
+        for (actual, expected) in phases.iter().zip(expected.iter()) {
+            // the first phase of -5 wraps to 2pi - 5, and the rest of the sequence is unwrapped relative to it
+            let offset = 2f64 * ::std::f64::consts::PI;
+            assert!((actual - (expected + offset)).abs() < 1e-10, "actual = {}, expected = {}", actual, expected + offset);
+        }
+
+        // decreasing phases should unwrap downwards
+        let mut phases: Vec<f32> = (0..20).map(|i| Complex::from_polar(&1f32, &(i as f32 * -2f32)).arg()).collect();
+        unwrap_phase(&mut phases);
+        assert!(phases.windows(2).all(|pair| (pair[1] - pair[0] + 2f32).abs() < 1e-4));
+
+        let mut empty: [f32; 0] = [];
+        unwrap_phase(&mut empty);
+    }
+
+    #[test]
+    fn test_spectrum_frequency_mapping() {
+        let spectrum = Spectrum::new(vec![Complex::<f32>::zero(); 10], 1000f64);
+        let expected = [0f64, 100f64, 200f64, 300f64, 400f64, 500f64, -400f64, -300f64, -200f64, -100f64];
+        for (bin, &frequency) in expected.iter().enumerate() {
+            assert_eq!(spectrum.frequency_of(bin), frequency, "bin = {}", bin);
+            assert_eq!(spectrum.bin_of(frequency), bin, "frequency = {}", frequency);
+        }
+
+        // frequencies between bins round to the nearest one, and frequencies past the sample rate alias
+        assert_eq!(spectrum.bin_of(149f64), 1);
+        assert_eq!(spectrum.bin_of(151f64), 2);
+        assert_eq!(spectrum.bin_of(1100f64), 1);
+        assert_eq!(spectrum.bin_of(-1100f64), 9);
+    }
+
+    #[test]
+    fn test_spectrum_peak() {
+        let len = 64;
+        let sample_rate = 6400f64;
+
+        // a tone at 700 Hz, which is bin 7, plus a quieter tone at -1500 Hz, which is bin 49
+        let signal: Vec<Complex<f64>> = (0..len).map(|i| {
+            let t = i as f64 / sample_rate;
+            Complex::from_polar(&2f64, &(2f64 * ::std::f64::consts::PI * 700f64 * t))
+                + Complex::from_polar(&0.5f64, &(-2f64 * ::std::f64::consts::PI * 1500f64 * t))
+        }).collect();
+        let spectrum = DFT::new(len, false).process_to_spectrum(&signal, sample_rate);
+
+        assert_eq!(spectrum.original_len(), len);
+        assert_eq!(spectrum.sample_rate(), sample_rate);
+
+        let (frequency, magnitude) = spectrum.peak().unwrap();
+        assert_eq!(frequency, 700f64);
+        assert!((magnitude - 128f64).abs() < 1e-9);
+        assert!((spectrum.magnitude_at(-1500f64) - 32f64).abs() < 1e-9);
+        assert!(spectrum.magnitude_at(1000f64) < 1e-9);
+
+        assert_eq!(Spectrum::<f32>::new(Vec::new(), 1f64).peak(), None);
+    }
+
+    #[test]
+    fn test_parseval_check() {
+        for &len in &[1, 2, 3, 16, 30, 97, 256, 1000] {
+            let signal = random_signal(len);
+            let mut spectrum = vec![Complex::zero(); len];
+            FFTplanner::new(false).plan_fft(len).process(&mut signal.clone(), &mut spectrum);
+
+            let ratio = parseval_check(&signal, &spectrum);
+            assert!((ratio - 1f32).abs() < 1e-4, "len = {}, ratio = {}", len, ratio);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+
+use num_complex::Complex;
+use num_traits::Float;
+
+use common::FFTnum;
+
+// the bins of a real signal's spectrum that aren't redundant: the rest are the complex conjugates of these
+fn non_negative_bins<T>(spectrum: &[Complex<T>]) -> &[Complex<T>] {
+    if spectrum.is_empty() {
+        spectrum
+    } else {
+        &spectrum[..spectrum.len() / 2 + 1]
+    }
+}
+
+/// Computes the spectral centroid of the FFT of a real signal, IE the average frequency of its bins, weighted by their
+/// magnitudes
+///
+/// `spectrum` is the full output of a FFT of a signal sampled at `sample_rate`, and only its non-negative frequency bins
+/// `0..=spectrum.len() / 2` are used. The result is in the same unit as the sample rate. A spectrum with no energy has a
+/// centroid of 0.
+///
+/// ~~~
+/// use rustfft::spectrum_stats::spectral_centroid;
+/// use rustfft::num_complex::Complex;
+///
+/// // equal energy at 1 kHz and 3 kHz
+/// let mut spectrum = vec![Complex::new(0f32, 0f32); 8];
+/// spectrum[1] = Complex::new(1f32, 0f32);
+/// spectrum[3] = Complex::new(0f32, 1f32);
+///
+/// assert_eq!(spectral_centroid(&spectrum, 8000f64), 2000f64);
+/// ~~~
+pub fn spectral_centroid<T: FFTnum + Float>(spectrum: &[Complex<T>], sample_rate: f64) -> f64 {
+    let bin_width = sample_rate / spectrum.len() as f64;
+
+    let mut weighted_sum = 0f64;
+    let mut magnitude_sum = 0f64;
+    for (bin, element) in non_negative_bins(spectrum).iter().enumerate() {
+        let magnitude = element.norm().to_f64().unwrap();
+        weighted_sum += bin as f64 * bin_width * magnitude;
+        magnitude_sum += magnitude;
+    }
+
+    if magnitude_sum > 0f64 { weighted_sum / magnitude_sum } else { 0f64 }
+}
+
+/// Computes the spectral flatness of the FFT of a real signal, IE the geometric mean of the power of its bins divided by
+/// the arithmetic mean
+///
+/// The result is between 0 and 1: white noise has a flatness close to 1, and a pure tone has a flatness close to 0. If
+/// any bin is exactly zero, the geometric mean and the flatness are 0. Like `spectral_centroid`, only the non-negative
+/// frequency bins `0..=spectrum.len() / 2` are used, and a spectrum with no energy has a flatness of 0.
+pub fn spectral_flatness<T: FFTnum + Float>(spectrum: &[Complex<T>]) -> T {
+    let bins = non_negative_bins(spectrum);
+    if bins.is_empty() {
+        return T::zero();
+    }
+    let count = T::from_usize(bins.len()).unwrap();
+
+    let arithmetic_mean = bins.iter().fold(T::zero(), |sum, element| sum + element.norm_sqr()) / count;
+    if arithmetic_mean == T::zero() {
+        return T::zero();
+    }
+
+    // the mean of the logs avoids the overflow that multiplying all of the powers together would cause
+    let mean_log = bins.iter().fold(T::zero(), |sum, element| sum + element.norm_sqr().ln()) / count;
+    mean_log.exp() / arithmetic_mean
+}
+
+/// Computes the energy of the real signal whose FFT is `spectrum`, IE `sum(signal[i]^2)`
+///
+/// Only the non-negative frequency bins `0..=spectrum.len() / 2` are read. Each bin other than DC and (for even
+/// lengths) the Nyquist bin stands in for its negative frequency counterpart as well, so it's counted twice. By
+/// Parseval's theorem, the result is the energy of the signal, without needing to keep the signal around.
+pub fn total_energy<T: FFTnum + Float>(spectrum: &[Complex<T>]) -> T {
+    let len = spectrum.len();
+    if len == 0 {
+        return T::zero();
+    }
+
+    let two = T::one() + T::one();
+    let sum = non_negative_bins(spectrum).iter().enumerate().fold(T::zero(), |sum, (bin, element)| {
+        let is_unpaired = bin == 0 || bin * 2 == len;
+        sum + if is_unpaired { element.norm_sqr() } else { two * element.norm_sqr() }
+    });
+    sum / T::from_usize(len).unwrap()
+}
+
+/// Returns the `k` strongest bins of the FFT of a real signal as `(bin, power)` pairs, IE the bins with the largest
+/// `norm_sqr()`, sorted from strongest to weakest
+///
+/// Only the non-negative frequency bins `0..=spectrum.len() / 2` are considered. A tone that doesn't land exactly on a
+/// bin leaks into its neighbours, so the strongest bins are often several bins of the same tone. If `local_maxima_only`
+/// is true, only bins that are at least as strong as both of their neighbours are reported, so that each tone is
+/// reported once. Among a run of equally strong bins, only the first is a local maximum. Fewer than `k` pairs are
+/// returned if there aren't enough candidate bins, and bins with equal power are returned in increasing order.
+///
+/// ~~~
+/// use rustfft::spectrum_stats::top_k_peaks;
+/// use rustfft::num_complex::Complex;
+///
+/// let spectrum: Vec<Complex<f32>> = [0f32, 1f32, 4f32, 1f32, 0f32, 3f32, 0f32, 0f32].iter()
+///     .map(|&re| Complex::new(re, 0f32))
+///     .collect();
+///
+/// assert_eq!(top_k_peaks(&spectrum, 2, false), vec![(2, 16f32), (1, 1f32)]);
+/// assert_eq!(top_k_peaks(&spectrum, 2, true), vec![(2, 16f32)]);
+/// ~~~
+pub fn top_k_peaks<T: FFTnum + PartialOrd>(spectrum: &[Complex<T>], k: usize, local_maxima_only: bool) -> Vec<(usize, T)> {
+    let powers: Vec<T> = non_negative_bins(spectrum).iter().map(|element| element.norm_sqr()).collect();
+
+    let is_local_maximum = |bin: usize| {
+        let beats_left = bin == 0 || powers[bin] > powers[bin - 1];
+        let beats_right = bin + 1 == powers.len() || powers[bin] >= powers[bin + 1];
+        beats_left && beats_right
+    };
+
+    let mut peaks: Vec<(usize, T)> = powers.iter().cloned().enumerate()
+        .filter(|&(bin, _)| !local_maxima_only || is_local_maximum(bin))
+        .collect();
+
+    // the sort is stable, so bins with equal power stay in increasing order
+    peaks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    peaks.truncate(k);
+    peaks
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use num_traits::Zero;
+    use test_utils::random_signal;
+    use FFTplanner;
+
+    #[test]
+    fn test_tonal_spectrum() {
+        let len = 64;
+        let sample_rate = 6400f64;
+
+        // every bin except the tone's is exactly zero
+        let mut spectrum = vec![Zero::zero(); len];
+        spectrum[10] = Complex::new(3f32, -4f32);
+        spectrum[len - 10] = spectrum[10].conj();
+
+        assert!((spectral_centroid(&spectrum, sample_rate) - 1000f64).abs() < 1e-6);
+        assert_eq!(spectral_flatness(&spectrum), 0f32);
+    }
+
+    #[test]
+    fn test_flat_spectrum() {
+        for &len in &[63, 64] {
+            let sample_rate = 1000f64;
+
+            // a perfectly flat spectrum is the limit of white noise
+            let spectrum: Vec<Complex<f32>> = (0..len).map(|i| Complex::from_polar(&2f32, &(i as f32))).collect();
+
+            // the bins are evenly spaced from 0 up to (nearly) the Nyquist frequency, so the centroid is their midpoint
+            let highest_frequency = (len / 2) as f64 * sample_rate / len as f64;
+            assert!((spectral_centroid(&spectrum, sample_rate) - highest_frequency / 2f64).abs() < 1e-6, "len = {}", len);
+            assert!((spectral_flatness(&spectrum) - 1f32).abs() < 1e-5, "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_noise_flatter_than_tone() {
+        let len = 256;
+        let noise: Vec<Complex<f32>> = random_signal(len).iter().map(|element| Complex::new(element.re, 0f32)).collect();
+        // a tone that lands exactly on bin 20, plus a little of the noise
+        let tone: Vec<Complex<f32>> = (0..len).map(|i| {
+            let phase = 2f32 * ::std::f32::consts::PI * 20f32 * i as f32 / len as f32;
+            Complex::new(phase.sin() + 0.01 * noise[i].re, 0f32)
+        }).collect();
+
+        let fft = FFTplanner::new(false).plan_fft(len);
+        let mut noise_spectrum = vec![Zero::zero(); len];
+        let mut tone_spectrum = vec![Zero::zero(); len];
+        fft.process(&mut noise.clone(), &mut noise_spectrum);
+        fft.process(&mut tone.clone(), &mut tone_spectrum);
+
+        assert!(spectral_flatness(&noise_spectrum) > 0.3);
+        assert!(spectral_flatness(&tone_spectrum) < 0.01);
+    }
+
+    #[test]
+    fn test_total_energy() {
+        for &len in &[1, 2, 7, 16, 30] {
+            let signal: Vec<Complex<f32>> = random_signal(len).iter().map(|element| Complex::new(element.re, 0f32)).collect();
+            let expected: f32 = signal.iter().map(|element| element.norm_sqr()).sum();
+
+            let mut spectrum = vec![Zero::zero(); len];
+            FFTplanner::new(false).plan_fft(len).process(&mut signal.clone(), &mut spectrum);
+
+            assert!((total_energy(&spectrum) - expected).abs() < 1e-4 * expected.max(1f32), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_top_k_peaks() {
+        let len = 512;
+        // two tones land exactly on bins 20 and 150, and one falls between bins 83 and 84, leaking into its neighbours
+        let tones = [(20f32, 1f32), (83.4f32, 0.8f32), (150f32, 0.2f32)];
+        let signal: Vec<Complex<f32>> = (0..len).map(|i| {
+            let sample = tones.iter().fold(0f32, |sum, &(bin, amplitude)| {
+                sum + amplitude * (2f32 * ::std::f32::consts::PI * bin * i as f32 / len as f32).sin()
+            });
+            Complex::new(sample, 0f32)
+        }).collect();
+
+        let mut spectrum = vec![Zero::zero(); len];
+        FFTplanner::new(false).plan_fft(len).process(&mut signal.clone(), &mut spectrum);
+
+        let peak_bins = |peaks: Vec<(usize, f32)>| -> Vec<usize> { peaks.iter().map(|&(bin, _)| bin).collect() };
+
+        // the leakage around bin 83 is stronger than the tone at bin 150, so it crowds it out of the strongest bins
+        let strongest = top_k_peaks(&spectrum, 3, false);
+        assert_eq!(peak_bins(strongest.clone()), vec![20, 83, 84]);
+        assert!(strongest[0].1 >= strongest[1].1 && strongest[1].1 >= strongest[2].1);
+
+        // filtering for local maxima reports each tone once
+        assert_eq!(peak_bins(top_k_peaks(&spectrum, 3, true)), vec![20, 83, 150]);
+
+        assert_eq!(top_k_peaks(&spectrum, 0, true), vec![]);
+        assert_eq!(top_k_peaks(&spectrum[..4], 10, false).len(), 3);
+        assert_eq!(top_k_peaks::<f32>(&[], 3, true), vec![]);
+    }
+
+    #[test]
+    fn test_empty_spectrum() {
+        let empty: &[Complex<f32>] = &[];
+        assert_eq!(spectral_centroid(empty, 1000f64), 0f64);
+        assert_eq!(spectral_flatness(empty), 0f32);
+        assert_eq!(total_energy(empty), 0f32);
+
+        let silence = vec![Zero::zero(); 8];
+        assert_eq!(spectral_centroid::<f32>(&silence, 1000f64), 0f64);
+        assert_eq!(spectral_flatness::<f32>(&silence), 0f32);
+    }
+}
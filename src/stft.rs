@@ -0,0 +1,441 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::{Zero, Float};
+
+use common::{FFTnum, two_pi};
+
+use twiddles;
+use FFT;
+
+/// An iterator adaptor that computes the FFT of a sliding window over a stream of samples
+///
+/// Each item is the spectrum of `fft.len()` consecutive samples, and each window starts `hop` samples after the
+/// previous one. Samples are pulled from the source lazily, so only one window of samples is ever buffered, and the
+/// internal buffers are reused from one window to the next. If the source runs out partway through a window, that
+/// partial window is discarded.
+///
+/// ~~~
+/// // Compute the spectra of windows of size 256 that overlap by 50%
+/// use rustfft::FFTplanner;
+/// use rustfft::stft::WindowedFftIter;
+/// use rustfft::num_complex::Complex;
+///
+/// let samples = (0..1024).map(|i| Complex::new((i as f32 * 0.1).sin(), 0f32));
+///
+/// let mut planner = FFTplanner::new(false);
+/// let fft = planner.plan_fft(256);
+///
+/// let spectra: Vec<Vec<Complex<f32>>> = WindowedFftIter::new(samples, fft, 128).collect();
+/// assert_eq!(spectra.len(), 7);
+/// ~~~
+pub struct WindowedFftIter<T, I> {
+    source: I,
+    fft: Arc<dyn FFT<T>>,
+    hop: usize,
+
+    window: Vec<Complex<T>>,
+    scratch: Vec<Complex<T>>,
+    started: bool,
+}
+
+impl<T: FFTnum, I: Iterator<Item = Complex<T>>> WindowedFftIter<T, I> {
+    /// Creates an iterator that yields the spectra of windows of `source`, using `fft` to compute them
+    ///
+    /// The window size is `fft.len()`, and each window starts `hop` samples after the start of the previous one. `hop`
+    /// must be nonzero, but it may be larger than the window size, in which case some samples are skipped.
+    pub fn new(source: I, fft: Arc<dyn FFT<T>>, hop: usize) -> Self {
+        assert!(hop > 0, "The hop size of a WindowedFftIter must be nonzero");
+
+        let len = fft.len();
+        WindowedFftIter {
+            source,
+            fft,
+            hop,
+
+            window: Vec::with_capacity(len),
+            scratch: vec![Zero::zero(); len],
+            started: false,
+        }
+    }
+
+    // move the window forward by `hop` samples. returns false if the source doesn't have enough samples for a full window
+    fn advance_window(&mut self) -> bool {
+        let len = self.fft.len();
+
+        if self.started {
+            let retained = len.saturating_sub(self.hop);
+            self.window.drain(..len - retained);
+
+            // if the hop is bigger than the window, throw away the samples between windows
+            for _ in len..self.hop {
+                if self.source.next().is_none() {
+                    return false;
+                }
+            }
+        }
+        self.started = true;
+
+        while self.window.len() < len {
+            match self.source.next() {
+                Some(sample) => self.window.push(sample),
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+impl<T: FFTnum, I: Iterator<Item = Complex<T>>> Iterator for WindowedFftIter<T, I> {
+    type Item = Vec<Complex<T>>;
+
+    fn next(&mut self) -> Option<Vec<Complex<T>>> {
+        if !self.advance_window() {
+            return None;
+        }
+
+        // the FFT uses its input as scratch space, so copy the window rather than processing it directly
+        self.scratch.copy_from_slice(&self.window);
+
+        let mut spectrum = vec![Zero::zero(); self.fft.len()];
+        self.fft.process(&mut self.scratch, &mut spectrum);
+        Some(spectrum)
+    }
+}
+
+/// Returns a periodic Hann window of length `len`, IE `0.5 - 0.5 * cos(2 * pi * n / len)`
+///
+/// The periodic form satisfies the constant overlap-add condition at a hop of `len / 2`, and at `len / 4`, so it can
+/// be used as the synthesis window of an `IStft`.
+pub fn hann_window<T: FFTnum + Float>(len: usize) -> Vec<T> {
+    let half = T::from_f64(0.5).unwrap();
+    let len_float = T::from_usize(len).unwrap();
+    (0..len).map(|n| half - half * (two_pi::<T>() * T::from_usize(n).unwrap() / len_float).cos()).collect()
+}
+
+/// An inverse short-time Fourier transform: turns a sequence of spectra back into a signal, by overlap-adding the
+/// inverse FFT of each spectrum multiplied by a synthesis window
+///
+/// Frame `i` is added to the output starting at sample `i * hop`. The window and hop must satisfy the constant
+/// overlap-add (COLA) condition, IE the copies of the window shifted by multiples of `hop` must sum to a constant, and
+/// the output is divided by that constant as well as by the FFT length. So if the frames were computed by a
+/// `WindowedFftIter` with the same FFT length and hop, the output reproduces the original signal, except for the first
+/// and last `len - hop` samples, which aren't covered by every overlapping window.
+///
+/// ~~~
+/// // Reconstruct a signal from its spectra, using a Hann window at 50% overlap
+/// use rustfft::FFTplanner;
+/// use rustfft::stft::{WindowedFftIter, IStft, hann_window};
+/// use rustfft::num_complex::Complex;
+///
+/// let signal: Vec<Complex<f32>> = (0..1024).map(|i| Complex::new((i as f32 * 0.1).sin(), 0f32)).collect();
+///
+/// let fft = FFTplanner::new(false).plan_fft(256);
+/// let spectra: Vec<Vec<Complex<f32>>> = WindowedFftIter::new(signal.iter().cloned(), fft, 128).collect();
+///
+/// let ifft = FFTplanner::new(true).plan_fft(256);
+/// let istft = IStft::new(ifft, hann_window(256), 128);
+/// let reconstructed = istft.process(&spectra);
+///
+/// assert_eq!(reconstructed.len(), 1024);
+/// assert!((reconstructed[500] - signal[500]).norm() < 1e-4);
+/// ~~~
+pub struct IStft<T> {
+    ifft: Arc<dyn FFT<T>>,
+    window: Box<[T]>,
+    hop: usize,
+}
+
+impl<T: FFTnum + Float> IStft<T> {
+    /// Creates an inverse STFT that uses `ifft` to transform each frame, and `window` as the synthesis window
+    ///
+    /// Panics if `ifft` isn't an inverse FFT, if `window.len()` isn't `ifft.len()`, if `hop` is zero or larger than the
+    /// window, or if the window and hop don't satisfy the COLA condition.
+    pub fn new(ifft: Arc<dyn FFT<T>>, window: Vec<T>, hop: usize) -> Self {
+        let len = ifft.len();
+        assert!(ifft.is_inverse(), "The FFT of an IStft must be an inverse FFT");
+        assert_eq!(window.len(), len, "Window is the wrong length. Expected {}, got {}", len, window.len());
+        assert!(hop > 0 && hop <= len, "The hop size of an IStft must be between 1 and {}, got {}", len, hop);
+
+        // the overlap-added window has to sum to the same value at every offset within a hop
+        let overlap_sums: Vec<T> = (0..hop).map(|offset| {
+            window.iter().skip(offset).step_by(hop).fold(T::zero(), |sum, &x| sum + x)
+        }).collect();
+        let overlap_sum = overlap_sums[0];
+
+        let tolerance = overlap_sum.abs() * T::from_f64(1e-4).unwrap();
+        assert!(overlap_sum.abs() > T::zero() && overlap_sums.iter().all(|&sum| (sum - overlap_sum).abs() <= tolerance),
+            "The window doesn't satisfy the constant overlap-add condition for a hop of {}", hop);
+
+        let scale = overlap_sum * T::from_usize(len).unwrap();
+        IStft {
+            ifft,
+            window: window.into_iter().map(|x| x / scale).collect::<Vec<T>>().into_boxed_slice(),
+            hop,
+        }
+    }
+
+    /// Overlap-adds the inverse FFTs of `frames` into a signal of length `(frames.len() - 1) * hop + len`, or an empty
+    /// signal if there are no frames. Each frame must have `len` elements.
+    pub fn process(&self, frames: &[Vec<Complex<T>>]) -> Vec<Complex<T>> {
+        let len = self.ifft.len();
+        if frames.is_empty() {
+            return Vec::new();
+        }
+
+        let mut signal = vec![Zero::zero(); (frames.len() - 1) * self.hop + len];
+        let mut scratch = vec![Zero::zero(); len];
+        let mut frame_output = vec![Zero::zero(); len];
+
+        for (i, frame) in frames.iter().enumerate() {
+            scratch.copy_from_slice(frame);
+            self.ifft.process(&mut scratch, &mut frame_output);
+
+            let start = i * self.hop;
+            for ((sample, &x), &w) in signal[start..start + len].iter_mut().zip(frame_output.iter()).zip(self.window.iter()) {
+                *sample = *sample + x * w;
+            }
+        }
+        signal
+    }
+
+    /// Returns the number of samples between the starts of consecutive frames
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+}
+
+/// Keeps the spectrum of the most recent `len` samples of a stream up to date, one sample at a time
+///
+/// Recomputing a FFT of the whole window for every new sample takes O(n log n) time per sample. The sliding DFT instead
+/// updates each bin with the recurrence `X[k] = (X[k] - oldest + newest) * w^k`, where `w` is the twiddle factor for a
+/// shift of one sample, so each sample only takes O(n) time. Before the first `len` samples, the window is padded with
+/// zeros at the start.
+///
+/// The recurrence never forgets a rounding error: each update multiplies the errors of the previous one by twiddle
+/// factors that are only approximately on the unit circle, so the spectrum slowly drifts away from the exact spectrum of
+/// the window, by roughly `epsilon` per bin per sample. Use `set_recompute_interval` to replace the spectrum with a full
+/// FFT of the window after every so many samples, which resets the drift.
+///
+/// ~~~
+/// // Monitor the spectrum of the most recent 64 samples
+/// use rustfft::FFTplanner;
+/// use rustfft::stft::SlidingDft;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut planner = FFTplanner::new(false);
+/// let mut sliding_dft = SlidingDft::new(planner.plan_fft(64));
+/// sliding_dft.set_recompute_interval(4096);
+///
+/// for i in 0..1000 {
+///     let spectrum = sliding_dft.push(Complex::new((i as f32 * 0.1).sin(), 0f32));
+///     assert_eq!(spectrum.len(), 64);
+/// }
+/// ~~~
+pub struct SlidingDft<T> {
+    fft: Arc<dyn FFT<T>>,
+    twiddles: Box<[Complex<T>]>,
+
+    // a ring buffer of the most recent samples, where `oldest` is the index of the oldest one
+    window: Vec<Complex<T>>,
+    oldest: usize,
+    spectrum: Vec<Complex<T>>,
+    scratch: Vec<Complex<T>>,
+
+    recompute_interval: Option<usize>,
+    pushes_since_recompute: usize,
+}
+
+impl<T: FFTnum> SlidingDft<T> {
+    /// Creates a sliding DFT over the most recent `fft.len()` samples, starting from a window of zeros
+    ///
+    /// The spectrum has the same direction as `fft`, and `fft` is used to compute the spectrum from scratch in
+    /// `recompute`. The length must be nonzero.
+    pub fn new(fft: Arc<dyn FFT<T>>) -> Self {
+        let len = fft.len();
+        assert!(len > 0, "The length of a SlidingDft must be nonzero");
+
+        // dropping the oldest sample moves every sample one step closer to the start of the window, which rotates bin
+        // k by the opposite of the FFT's own twiddle factor for index k
+        let twiddles: Vec<Complex<T>> = (0..len).map(|k| twiddles::single_twiddle(k, len, !fft.is_inverse())).collect();
+
+        SlidingDft {
+            fft,
+            twiddles: twiddles.into_boxed_slice(),
+
+            window: vec![Zero::zero(); len],
+            oldest: 0,
+            spectrum: vec![Zero::zero(); len],
+            scratch: vec![Zero::zero(); len],
+
+            recompute_interval: None,
+            pushes_since_recompute: 0,
+        }
+    }
+
+    /// Replaces the spectrum with a full FFT of the window after every `interval` calls to `push`, to stop rounding
+    /// errors from accumulating. `interval` must be nonzero
+    pub fn set_recompute_interval(&mut self, interval: usize) {
+        assert!(interval > 0, "The recompute interval of a SlidingDft must be nonzero");
+        self.recompute_interval = Some(interval);
+    }
+
+    /// Adds `sample` to the end of the window, drops the oldest sample, and returns the updated spectrum
+    pub fn push(&mut self, sample: Complex<T>) -> &[Complex<T>] {
+        let delta = sample - self.window[self.oldest];
+        self.window[self.oldest] = sample;
+        self.oldest = (self.oldest + 1) % self.window.len();
+
+        self.pushes_since_recompute += 1;
+        if self.recompute_interval == Some(self.pushes_since_recompute) {
+            self.recompute();
+        } else {
+            for (bin, &twiddle) in self.spectrum.iter_mut().zip(self.twiddles.iter()) {
+                *bin = (*bin + delta) * twiddle;
+            }
+        }
+        &self.spectrum
+    }
+
+    /// Replaces the spectrum with a full FFT of the window, discarding any rounding errors that have accumulated
+    pub fn recompute(&mut self) {
+        let (newer, older) = self.window.split_at(self.oldest);
+        let (older_scratch, newer_scratch) = self.scratch.split_at_mut(older.len());
+        older_scratch.copy_from_slice(older);
+        newer_scratch.copy_from_slice(newer);
+
+        self.fft.process(&mut self.scratch, &mut self.spectrum);
+        self.pushes_since_recompute = 0;
+    }
+
+    /// Returns the spectrum of the current window
+    pub fn spectrum(&self) -> &[Complex<T>] {
+        &self.spectrum
+    }
+
+    /// Returns the number of samples in the window, which is the length of the FFT it was created with
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use algorithm::DFT;
+    use test_utils::{random_signal, compare_vectors};
+
+    fn eager_stft(signal: &[Complex<f32>], fft: &dyn FFT<f32>, hop: usize) -> Vec<Vec<Complex<f32>>> {
+        let len = fft.len();
+        let mut result = Vec::new();
+
+        let mut start = 0;
+        while start + len <= signal.len() {
+            let mut input = signal[start..start + len].to_vec();
+            let mut output = vec![Zero::zero(); len];
+            fft.process(&mut input, &mut output);
+
+            result.push(output);
+            start += hop;
+        }
+        result
+    }
+
+    #[test]
+    fn test_windowed_fft_iter() {
+        let signal = random_signal(100);
+
+        for &(len, hop) in &[(8, 8), (8, 4), (8, 3), (8, 1), (8, 11), (16, 7), (1, 1)] {
+            let fft = Arc::new(DFT::new(len, false)) as Arc<dyn FFT<f32>>;
+
+            let expected = eager_stft(&signal, &*fft, hop);
+            let actual: Vec<Vec<Complex<f32>>> = WindowedFftIter::new(signal.iter().cloned(), fft, hop).collect();
+
+            assert_eq!(expected.len(), actual.len(), "len = {}, hop = {}", len, hop);
+            for (expected_spectrum, actual_spectrum) in expected.iter().zip(actual.iter()) {
+                assert!(compare_vectors(expected_spectrum, actual_spectrum), "len = {}, hop = {}", len, hop);
+            }
+        }
+    }
+
+    #[test]
+    fn test_windowed_fft_iter_short_source() {
+        let signal = random_signal(5);
+        let fft = Arc::new(DFT::new(8, false)) as Arc<dyn FFT<f32>>;
+
+        assert_eq!(WindowedFftIter::new(signal.into_iter(), fft, 4).count(), 0);
+    }
+
+    #[test]
+    fn test_istft_round_trip() {
+        let signal = random_signal(200);
+
+        for &(len, hop) in &[(16, 8), (16, 4), (32, 16), (10, 5)] {
+            let fft = Arc::new(DFT::new(len, false)) as Arc<dyn FFT<f32>>;
+            let ifft = Arc::new(DFT::new(len, true)) as Arc<dyn FFT<f32>>;
+
+            let frames: Vec<Vec<Complex<f32>>> = WindowedFftIter::new(signal.iter().cloned(), fft, hop).collect();
+            let reconstructed = IStft::new(ifft, hann_window(len), hop).process(&frames);
+            assert_eq!(reconstructed.len(), (frames.len() - 1) * hop + len, "len = {}, hop = {}", len, hop);
+
+            // only the samples covered by every overlapping window are reconstructed exactly
+            let edge = len - hop;
+            let end = reconstructed.len() - edge;
+            assert!(compare_vectors(&signal[edge..end], &reconstructed[edge..end]), "len = {}, hop = {}", len, hop);
+        }
+    }
+
+    #[test]
+    fn test_sliding_dft() {
+        let signal = random_signal(100);
+
+        for &(len, inverse) in &[(1, false), (8, false), (16, true), (30, false)] {
+            let fft = Arc::new(DFT::new(len, inverse)) as Arc<dyn FFT<f32>>;
+            let mut sliding_dft = SlidingDft::new(Arc::clone(&fft));
+            assert_eq!(sliding_dft.window_len(), len);
+
+            for (i, &sample) in signal.iter().enumerate() {
+                let actual = sliding_dft.push(sample).to_vec();
+
+                // before the window is full, it's padded with zeros at the start
+                let mut window = vec![Zero::zero(); len.saturating_sub(i + 1)];
+                window.extend_from_slice(&signal[(i + 1).saturating_sub(len)..i + 1]);
+                let mut expected = vec![Zero::zero(); len];
+                fft.process(&mut window, &mut expected);
+
+                assert!(compare_vectors(&expected, &actual), "len = {}, inverse = {}, i = {}", len, inverse, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sliding_dft_recompute() {
+        let len = 16;
+        let signal = random_signal(3 * len);
+        let fft = Arc::new(DFT::new(len, false)) as Arc<dyn FFT<f32>>;
+
+        let mut sliding_dft = SlidingDft::new(Arc::clone(&fft));
+        sliding_dft.set_recompute_interval(len);
+        for &sample in &signal {
+            sliding_dft.push(sample);
+        }
+
+        // the last push recomputed the spectrum, so it's exactly the FFT of the last window
+        let mut expected = vec![Zero::zero(); len];
+        fft.process(&mut signal[2 * len..].to_vec(), &mut expected);
+        assert_eq!(sliding_dft.spectrum(), &expected[..]);
+    }
+
+    #[test]
+    fn test_istft_empty() {
+        let ifft = Arc::new(DFT::new(8, true)) as Arc<dyn FFT<f32>>;
+        assert!(IStft::new(ifft, hann_window(8), 4).process(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "constant overlap-add")]
+    fn test_istft_rejects_non_cola() {
+        let ifft = Arc::new(DFT::new(16, true)) as Arc<dyn FFT<f32>>;
+        IStft::new(ifft, hann_window(16), 6);
+    }
+}
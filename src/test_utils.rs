@@ -4,7 +4,7 @@ use num_traits::Zero;
 use std::sync::Arc;
 
 use rand::{StdRng, SeedableRng};
-use rand::distributions::{Normal, IndependentSample};
+use rand::distributions::{Normal, Distribution};
 
 use algorithm::{DFT, butterflies};
 use FFT;
@@ -12,31 +12,62 @@ use FFT;
 
 /// The seed for the random number generator used to generate
 /// random signals. It's defined here so that we have deterministic
-/// tests
-const RNG_SEED: [u8; 32] = [1, 9, 1, 0, 1, 1, 4, 3, 1, 4, 9, 8,
-    4, 1, 4, 8, 2, 8, 1, 2, 2, 2, 6, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+/// tests. Failure messages include the seed, so a failing input can be
+/// regenerated with `random_signal_seeded`
+pub const RNG_SEED: u64 = 1910114314984148;
 
 pub fn random_signal(length: usize) -> Vec<Complex<f32>> {
+    random_signal_seeded(length, RNG_SEED)
+}
+
+pub fn random_signal_seeded(length: usize, seed: u64) -> Vec<Complex<f32>> {
     let mut sig = Vec::with_capacity(length);
     let normal_dist = Normal::new(0.0, 10.0);
-    let mut rng: StdRng = SeedableRng::from_seed(RNG_SEED);
+    let mut rng = StdRng::seed_from_u64(seed);
     for _ in 0..length {
-        sig.push(Complex{re: (normal_dist.ind_sample(&mut rng) as f32),
-                         im: (normal_dist.ind_sample(&mut rng) as f32)});
+        sig.push(Complex{re: (normal_dist.sample(&mut rng) as f32),
+                         im: (normal_dist.sample(&mut rng) as f32)});
     }
-    return sig;
+    sig
 }
 
+/// The average per-element error that `compare_vectors` accepts
+const COMPARE_TOLERANCE: f32 = 0.1f32;
+
+/// Returns true if the average distance between the elements of the two vectors is under `COMPARE_TOLERANCE`. If it
+/// isn't, prints a table of the elements that differ by more than that, which the test harness shows with the failure
 pub fn compare_vectors(vec1: &[Complex<f32>], vec2: &[Complex<f32>]) -> bool {
     assert_eq!(vec1.len(), vec2.len());
     let mut sse = 0f32;
     for (&a, &b) in vec1.iter().zip(vec2.iter()) {
-        sse = sse + (a - b).norm();
+        sse += (a - b).norm();
+    }
+    let matches = (sse / vec1.len() as f32) < COMPARE_TOLERANCE;
+    if !matches {
+        println!("{}", format_differences(vec1, vec2, COMPARE_TOLERANCE));
     }
-    return (sse / vec1.len() as f32) < 0.1f32;
+    matches
 }
 
-pub fn check_fft_algorithm(fft: &FFT<f32>, size: usize, inverse: bool) {
+/// Formats a table of every index where `expected` and `actual` differ by more than `tolerance`, showing both values in
+/// rectangular and polar form along with the distance between them
+pub fn format_differences(expected: &[Complex<f32>], actual: &[Complex<f32>], tolerance: f32) -> String {
+    let mut table = format!("{:>6} | {:>24} | {:>24} | {:>10} | {:>22} | {:>22}\n",
+        "index", "expected (re, im)", "actual (re, im)", "abs diff", "expected (mag, phase)", "actual (mag, phase)");
+
+    let mut num_differences = 0;
+    for (i, (&e, &a)) in expected.iter().zip(actual.iter()).enumerate() {
+        let difference = (e - a).norm();
+        if difference > tolerance || difference.is_nan() {
+            num_differences += 1;
+            table += &format!("{:>6} | {:>11.4}, {:>11.4} | {:>11.4}, {:>11.4} | {:>10.4} | {:>10.4}, {:>10.4} | {:>10.4}, {:>10.4}\n",
+                i, e.re, e.im, a.re, a.im, difference, e.norm(), e.arg(), a.norm(), a.arg());
+        }
+    }
+    table + &format!("{} of {} elements differ by more than {}", num_differences, expected.len(), tolerance)
+}
+
+pub fn check_fft_algorithm(fft: &dyn FFT<f32>, size: usize, inverse: bool) {
     assert_eq!(fft.len(), size, "Algorithm reported incorrect size");
     assert_eq!(fft.is_inverse(), inverse, "Algorithm reported incorrect inverse value");
 
@@ -46,7 +77,7 @@ pub fn check_fft_algorithm(fft: &FFT<f32>, size: usize, inverse: bool) {
     let dft = DFT::new(size, inverse);
 
     // set up buffers
-    let mut expected_input = random_signal(size * n);
+    let mut expected_input = random_signal_seeded(size * n, RNG_SEED);
     let mut actual_input = expected_input.clone();
     let mut multi_input = expected_input.clone();
 
@@ -62,11 +93,19 @@ pub fn check_fft_algorithm(fft: &FFT<f32>, size: usize, inverse: bool) {
         fft.process(input_chunk, output_chunk);
     }
 
-    //assert!(compare_vectors(&expected_output, &actual_output), "process() failed, length = {}, inverse = {}", size, inverse);
-    assert!(compare_vectors(&expected_output, &multi_output), "process_multi() failed, length = {}, inverse = {}", size, inverse);
+    // process_unchecked skips the length check, but should otherwise compute exactly the same thing as process
+    let mut unchecked_input = random_signal_seeded(size * n, RNG_SEED);
+    let mut unchecked_output = vec![Zero::zero(); size * n];
+    for (input_chunk, output_chunk) in unchecked_input.chunks_mut(size).zip(unchecked_output.chunks_mut(size)) {
+        unsafe { fft.process_unchecked(input_chunk, output_chunk) };
+    }
+    assert_eq!(actual_output, unchecked_output, "process_unchecked() failed, length = {}, inverse = {}", size, inverse);
+
+    //assert!(compare_vectors(&expected_output, &actual_output), "process() failed, length = {}, inverse = {}, seed = {}", size, inverse, RNG_SEED);
+    assert!(compare_vectors(&expected_output, &multi_output), "process_multi() failed, length = {}, inverse = {}, seed = {}", size, inverse, RNG_SEED);
 }
 
-pub fn make_butterfly(len: usize, inverse: bool) -> Arc<butterflies::FFTButterfly<f32>> {
+pub fn make_butterfly(len: usize, inverse: bool) -> Arc<dyn butterflies::FFTButterfly<f32>> {
     match len {
         2 => Arc::new(butterflies::Butterfly2::new(inverse)),
         3 => Arc::new(butterflies::Butterfly3::new(inverse)),
@@ -75,7 +114,39 @@ pub fn make_butterfly(len: usize, inverse: bool) -> Arc<butterflies::FFTButterfl
         6 => Arc::new(butterflies::Butterfly6::new(inverse)),
         7 => Arc::new(butterflies::Butterfly7::new(inverse)),
         8 => Arc::new(butterflies::Butterfly8::new(inverse)),
+        10 => Arc::new(butterflies::Butterfly10::new(inverse)),
+        14 => Arc::new(butterflies::Butterfly14::new(inverse)),
+        15 => Arc::new(butterflies::Butterfly15::new(inverse)),
         16 => Arc::new(butterflies::Butterfly16::new(inverse)),
         _ => panic!("Invalid butterfly size: {}", len),
     }
 }
+
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_random_signal_seeded() {
+        assert_eq!(random_signal_seeded(100, 12345), random_signal_seeded(100, 12345));
+        assert_ne!(random_signal_seeded(100, 12345), random_signal_seeded(100, 54321));
+    }
+
+    #[test]
+    fn test_format_differences() {
+        let expected = random_signal(20);
+        let mut actual = expected.clone();
+        actual[3] += Complex::new(0.5f32, 0f32);
+        actual[7] += Complex::new(0.05f32, 0f32);
+        actual[12] += Complex::new(0f32, -0.8f32);
+
+        // the vectors are close enough on average to pass, but two elements are far off
+        assert!(compare_vectors(&expected, &actual));
+
+        let table = format_differences(&expected, &actual, COMPARE_TOLERANCE);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows.len(), 4, "{}", table);
+        assert!(rows[1].trim_start().starts_with("3 |"), "{}", table);
+        assert!(rows[2].trim_start().starts_with("12 |"), "{}", table);
+        assert_eq!(rows[3], "2 of 20 elements differ by more than 0.1");
+    }
+}
@@ -1,24 +1,41 @@
 
-use std::f64;
-
 use num_complex::Complex;
 use num_traits::{FromPrimitive, One};
 
-use common::FFTnum;
+use common::{FFTnum, two_pi};
+
+use Direction;
 
-pub fn generate_twiddle_factors<T: FFTnum>(fft_len: usize, inverse: bool) -> Vec<Complex<T>> {
+pub fn generate_twiddle_factors<T: FFTnum, D: Into<Direction>>(fft_len: usize, direction: D) -> Vec<Complex<T>> {
+    let inverse = direction.into().is_inverse();
     (0..fft_len).map(|i| single_twiddle(i, fft_len, inverse)).collect()
 }
 
+/// Returns the twiddle factor `exp(-2 * pi * i * index / len)`, or `exp(2 * pi * i * index / len)` if `direction` is
+/// inverse. `direction` is a `Direction`, or a `bool` that's true for the inverse direction
+///
+/// These are the factors that a forward FFT of size `len` multiplies input `n` by to compute bin `k`, with
+/// `index = n * k`, so a forward FFT uses a negative exponent and an inverse FFT uses a positive one. The factor is
+/// computed in `f64` precision, then converted to `T`, so the `f32` twiddles are correctly rounded rather than
+/// accumulating error from computing the angle in `f32`.
+///
+/// ~~~
+/// // A quarter turn clockwise, for the forward direction
+/// use rustfft::single_twiddle;
+/// use rustfft::num_complex::Complex;
+///
+/// let twiddle: Complex<f64> = single_twiddle(1, 4, false);
+/// assert!((twiddle - Complex::new(0f64, -1f64)).norm() < 1e-15);
+/// ~~~
 #[inline(always)]
-pub fn single_twiddle<T: FFTnum>(i: usize, fft_len: usize, inverse: bool) -> Complex<T> {
-    let constant = if inverse {
-        2f64 * f64::consts::PI
+pub fn single_twiddle<T: FFTnum, D: Into<Direction>>(index: usize, len: usize, direction: D) -> Complex<T> {
+    let constant = if direction.into().is_inverse() {
+        two_pi::<f64>()
     } else {
-        -2f64 * f64::consts::PI
+        -two_pi::<f64>()
     };
 
-    let c = Complex::from_polar(&One::one(), &(constant * i as f64 / fft_len as f64));
+    let c = Complex::from_polar(&One::one(), &(constant * index as f64 / len as f64));
 
     Complex {
         re: FromPrimitive::from_f64(c.re).unwrap(),
@@ -26,6 +43,9 @@ pub fn single_twiddle<T: FFTnum>(i: usize, fft_len: usize, inverse: bool) -> Com
     }
 }
 
+// Multiplies `value` by `single_twiddle(1, 4, inverse)`, IE by -i for forward FFTs and by i for inverse FFTs.
+// The computed twiddle has a tiny nonzero real part due to rounding, so swapping components is both faster and more
+// accurate than a full complex multiply
 pub fn rotate_90<T: FFTnum>(value: Complex<T>, inverse:bool) -> Complex<T>
 {
     if inverse {
@@ -38,7 +58,6 @@ pub fn rotate_90<T: FFTnum>(value: Complex<T>, inverse:bool) -> Complex<T>
 #[cfg(test)]
 mod unit_tests {
 	use super::*;
-	use std::f32;
     use test_utils::{compare_vectors};
 
     #[test]
@@ -47,7 +66,7 @@ mod unit_tests {
         let zero_twiddles: Vec<Complex<f32>> = generate_twiddle_factors(0, false);
         assert_eq!(0, zero_twiddles.len());
 
-        let constant = -2f32 * f32::consts::PI;
+        let constant = -two_pi::<f32>();
 
         for len in 1..10 {
             let actual: Vec<Complex<f32>> = generate_twiddle_factors(len, false);
@@ -72,17 +91,38 @@ mod unit_tests {
 
     #[test]
     fn test_single() {
-        let len = 20;
+        for &len in &[1, 2, 7, 20, 64, 1000] {
+            let twiddles: Vec<Complex<f32>> = generate_twiddle_factors(len, false);
+            let twiddles_inverse: Vec<Complex<f32>> = generate_twiddle_factors(len, true);
+
+            for i in 0..len {
+                let single: Complex<f32> = single_twiddle(i, len, false);
+                let single_inverse: Complex<f32> = single_twiddle(i, len, true);
+
+                assert_eq!(single, twiddles[i], "forwards, len = {}, i = {}", len, i);
+                assert_eq!(single_inverse, twiddles_inverse[i], "inverse, len = {}, i = {}", len, i);
+
+                // the f32 twiddle should be the f64 twiddle, rounded
+                let single_f64: Complex<f64> = single_twiddle(i, len, false);
+                assert_eq!(single, Complex::new(single_f64.re as f32, single_f64.im as f32), "len = {}, i = {}", len, i);
+            }
+        }
+    }
 
-        let twiddles: Vec<Complex<f32>> = generate_twiddle_factors(len, false);
-        let twiddles_inverse: Vec<Complex<f32>> = generate_twiddle_factors(len, true);
+    #[test]
+    fn test_rotate_90() {
+        let value = Complex::new(3f32, -5f32);
 
-        for i in 0..len {
-            let single: Complex<f32> = single_twiddle(i, len, false);
-            let single_inverse: Complex<f32> = single_twiddle(i, len, true);
+        for &inverse in &[false, true] {
+            let twiddle: Complex<f32> = single_twiddle(1, 4, inverse);
+            let expected = value * twiddle;
+            let actual = rotate_90(value, inverse);
 
-            assert_eq!(single, twiddles[i], "forwards, i = {}", i);
-            assert_eq!(single_inverse, twiddles_inverse[i], "inverse, i = {}", i);
+            assert!(compare_vectors(&[expected], &[actual]), "inverse = {}", inverse);
         }
+
+        // spell out the convention: forward FFTs rotate by -i, inverse FFTs rotate by i
+        assert_eq!(rotate_90(Complex::new(1f32, 0f32), false), Complex::new(0f32, -1f32));
+        assert_eq!(rotate_90(Complex::new(1f32, 0f32), true), Complex::new(0f32, 1f32));
     }
-}
\ No newline at end of file
+}
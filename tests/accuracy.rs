@@ -14,7 +14,7 @@ use rustfft::num_complex::Complex;
 use rustfft::num_traits::Zero;
 
 use rand::{StdRng, SeedableRng};
-use rand::distributions::{Normal, IndependentSample};
+use rand::distributions::{Normal, Distribution};
 use rustfft::{FFT, FFTplanner};
 use rustfft::algorithm::DFT;
 
@@ -30,9 +30,9 @@ fn compare_vectors(vec1: &[Complex<f32>], vec2: &[Complex<f32>]) -> bool {
     assert_eq!(vec1.len(), vec2.len());
     let mut sse = 0f32;
     for (&a, &b) in vec1.iter().zip(vec2.iter()) {
-        sse = sse + (a - b).norm();
+        sse += (a - b).norm();
     }
-    return (sse / vec1.len() as f32) < 0.1f32;
+    (sse / vec1.len() as f32) < 0.1f32
 }
 
 
@@ -53,7 +53,7 @@ fn fft_matches_dft(signal: Vec<Complex<f32>>, inverse: bool) -> bool {
     let dft = DFT::new(signal.len(), inverse);
     dft.process(&mut signal_dft, &mut spectrum_dft);
 
-    return compare_vectors(&spectrum_dft[..], &spectrum_fft[..]);
+    compare_vectors(&spectrum_dft[..], &spectrum_fft[..])
 }
 
 fn random_signal(length: usize) -> Vec<Complex<f32>> {
@@ -61,10 +61,10 @@ fn random_signal(length: usize) -> Vec<Complex<f32>> {
     let normal_dist = Normal::new(0.0, 10.0);
     let mut rng: StdRng = SeedableRng::from_seed(RNG_SEED);
     for _ in 0..length {
-        sig.push(Complex{re: (normal_dist.ind_sample(&mut rng) as f32),
-                         im: (normal_dist.ind_sample(&mut rng) as f32)});
+        sig.push(Complex{re: (normal_dist.sample(&mut rng) as f32),
+                         im: (normal_dist.sample(&mut rng) as f32)});
     }
-    return sig;
+    sig
 }
 
 /// Integration tests that verify our FFT output matches the direct DFT calculation
@@ -0,0 +1,108 @@
+//! `FFTnum` isn't sealed: any type that implements its `num_traits` bounds can be used with the FFT algorithms. To
+//! make sure it stays that way, this implements `FFTnum` for a newtype around `f64`, and checks that the planner's
+//! FFTs of it match the FFTs of plain `f64`.
+
+
+extern crate rustfft;
+
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg};
+
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::{Zero, One, Num, Signed, FromPrimitive};
+use rustfft::{FFTnum, FFTplanner};
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+struct MyFloat(f64);
+
+macro_rules! impl_binary_op {
+    ($op_trait:ident, $op_fn:ident) => (
+        impl $op_trait for MyFloat {
+            type Output = MyFloat;
+            fn $op_fn(self, other: MyFloat) -> MyFloat {
+                MyFloat(self.0.$op_fn(other.0))
+            }
+        }
+    )
+}
+impl_binary_op!(Add, add);
+impl_binary_op!(Sub, sub);
+impl_binary_op!(Mul, mul);
+impl_binary_op!(Div, div);
+impl_binary_op!(Rem, rem);
+
+impl Neg for MyFloat {
+    type Output = MyFloat;
+    fn neg(self) -> MyFloat {
+        MyFloat(-self.0)
+    }
+}
+impl Zero for MyFloat {
+    fn zero() -> MyFloat {
+        MyFloat(0f64)
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0f64
+    }
+}
+impl One for MyFloat {
+    fn one() -> MyFloat {
+        MyFloat(1f64)
+    }
+}
+impl Num for MyFloat {
+    type FromStrRadixErr = <f64 as Num>::FromStrRadixErr;
+    fn from_str_radix(string: &str, radix: u32) -> Result<MyFloat, Self::FromStrRadixErr> {
+        f64::from_str_radix(string, radix).map(MyFloat)
+    }
+}
+impl Signed for MyFloat {
+    fn abs(&self) -> MyFloat {
+        MyFloat(self.0.abs())
+    }
+    fn abs_sub(&self, other: &MyFloat) -> MyFloat {
+        MyFloat((self.0 - other.0).max(0f64))
+    }
+    fn signum(&self) -> MyFloat {
+        MyFloat(self.0.signum())
+    }
+    fn is_positive(&self) -> bool {
+        self.0 > 0f64
+    }
+    fn is_negative(&self) -> bool {
+        self.0 < 0f64
+    }
+}
+impl FromPrimitive for MyFloat {
+    fn from_i64(n: i64) -> Option<MyFloat> {
+        Some(MyFloat(n as f64))
+    }
+    fn from_u64(n: u64) -> Option<MyFloat> {
+        Some(MyFloat(n as f64))
+    }
+    // the default implementation goes through from_i64, which would truncate the twiddle factors
+    fn from_f64(n: f64) -> Option<MyFloat> {
+        Some(MyFloat(n))
+    }
+}
+
+impl FFTnum for MyFloat {}
+
+#[test]
+fn test_custom_scalar() {
+    for &len in &[1, 2, 5, 12, 17, 64, 100] {
+        let signal: Vec<Complex<f64>> = (0..len).map(|i| Complex::new((i as f64 * 0.7).sin(), (i as f64 * 0.3).cos())).collect();
+
+        let mut expected_input = signal.clone();
+        let mut expected_output = vec![Zero::zero(); len];
+        FFTplanner::new(false).plan_fft(len).process(&mut expected_input, &mut expected_output);
+
+        let mut custom_input: Vec<Complex<MyFloat>> = signal.iter().map(|c| Complex::new(MyFloat(c.re), MyFloat(c.im))).collect();
+        let mut custom_output = vec![Zero::zero(); len];
+        FFTplanner::new(false).plan_fft(len).process(&mut custom_input, &mut custom_output);
+
+        for (expected, actual) in expected_output.iter().zip(custom_output.iter()) {
+            assert!((expected.re - actual.re.0).abs() < 1e-10 && (expected.im - actual.im.0).abs() < 1e-10,
+                "len = {}: expected {}, got ({}, {})", len, expected, actual.re.0, actual.im.0);
+        }
+    }
+}
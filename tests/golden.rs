@@ -0,0 +1,129 @@
+//! Compares the planner's FFTs against golden spectra that were computed with the naive DFT and stored in
+//! `tests/golden_spectra.txt`. Unlike the accuracy tests, which compare against a DFT computed by the current version
+//! of the crate, the golden spectra don't change when the code does, so they catch regressions anywhere in the path
+//! from the planner's choice of algorithms to the algorithms themselves.
+//!
+//! To regenerate the golden file, for example after adding lengths to `GOLDEN_LENGTHS`, run
+//! `cargo test --features regen-golden --test golden`, and review the diff before committing it.
+
+extern crate rustfft;
+
+use std::fs;
+
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FFTplanner;
+
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden_spectra.txt");
+
+/// The lengths in the golden file. They cover every path through the planner: butterflies, Radix4, Radix3, the
+/// power-of-two, square, and balanced splits, and both kinds of Rader's Algorithm
+const GOLDEN_LENGTHS: &[usize] = &[
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32,
+    49, 64, 97, 100, 121, 128, 210, 243, 256, 343, 1019, 1201,
+];
+
+/// The largest root-mean-square difference between the planner's FFT and the golden spectrum, relative to the
+/// root-mean-square magnitude of the golden spectrum
+const TOLERANCE: f64 = 1e-10;
+
+/// A xorshift64* generator, so that the signals depend only on the seed, and not on the version of any dependency
+fn seeded_signal(len: usize, seed: u64) -> Vec<Complex<f64>> {
+    let mut state = seed;
+    let mut next_sample = || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let bits = state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        // 53 random bits, scaled to [-1, 1)
+        bits as f64 / (1u64 << 52) as f64 - 1f64
+    };
+    (0..len).map(|_| Complex::new(next_sample(), next_sample())).collect()
+}
+
+#[cfg(feature = "regen-golden")]
+fn seed_for(len: usize) -> u64 {
+    0x9E37_79B9_7F4A_7C15 ^ len as u64
+}
+
+struct GoldenCase {
+    len: usize,
+    seed: u64,
+    spectrum: Vec<Complex<f64>>,
+}
+
+// the file has a header line `len <len> seed <seed>` for each case, followed by one line per bin, with the real and
+// imaginary parts separated by a space. lines starting with `#` are comments
+fn parse_golden_file(contents: &str) -> Vec<GoldenCase> {
+    let mut cases: Vec<GoldenCase> = Vec::new();
+    for line in contents.lines().filter(|line| !line.starts_with('#') && !line.trim().is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["len", len, "seed", seed] => cases.push(GoldenCase {
+                len: len.parse().unwrap(),
+                seed: seed.parse().unwrap(),
+                spectrum: Vec::new(),
+            }),
+            [re, im] => {
+                let case = cases.last_mut().expect("The golden file has a bin before the first header");
+                case.spectrum.push(Complex::new(re.parse().unwrap(), im.parse().unwrap()));
+            }
+            _ => panic!("Malformed line in the golden file: {:?}", line),
+        }
+    }
+    cases
+}
+
+#[test]
+fn test_golden_spectra() {
+    let contents = fs::read_to_string(GOLDEN_PATH).expect("Couldn't read the golden file");
+    let cases = parse_golden_file(&contents);
+
+    let golden_lengths: Vec<usize> = cases.iter().map(|case| case.len).collect();
+    assert_eq!(golden_lengths, GOLDEN_LENGTHS, "The golden file is out of date. Regenerate it with --features regen-golden");
+
+    let mut planner = FFTplanner::new(false);
+    for case in &cases {
+        assert_eq!(case.spectrum.len(), case.len, "len = {}", case.len);
+
+        let mut signal = seeded_signal(case.len, case.seed);
+        let mut spectrum = vec![Zero::zero(); case.len];
+        planner.plan_fft(case.len).process(&mut signal, &mut spectrum);
+
+        let error: f64 = spectrum.iter().zip(case.spectrum.iter()).map(|(actual, golden)| (actual - golden).norm_sqr()).sum();
+        let magnitude: f64 = case.spectrum.iter().map(|golden| golden.norm_sqr()).sum();
+        let relative_error = (error / magnitude).sqrt();
+        assert!(relative_error < TOLERANCE, "len = {}, plan = {}, relative error = {}",
+            case.len, planner.describe_plan(case.len), relative_error);
+    }
+}
+
+/// Recomputes the golden spectra with the naive DFT and overwrites the golden file
+#[cfg(feature = "regen-golden")]
+#[test]
+fn regenerate_golden_spectra() {
+    use std::fmt::Write;
+    use rustfft::FFT;
+    use rustfft::algorithm::DFT;
+
+    let mut contents = String::new();
+    writeln!(contents, "# Golden spectra for tests/golden.rs, computed with the naive DFT. Regenerate with").unwrap();
+    writeln!(contents, "# `cargo test --features regen-golden --test golden`").unwrap();
+    for &len in GOLDEN_LENGTHS {
+        let seed = seed_for(len);
+        let mut signal = seeded_signal(len, seed);
+        let mut spectrum = vec![Zero::zero(); len];
+        DFT::new(len, false).process(&mut signal, &mut spectrum);
+
+        writeln!(contents, "len {} seed {}", len, seed).unwrap();
+        for bin in &spectrum {
+            // Debug formatting prints the shortest representation that parses back to exactly the same value
+            writeln!(contents, "{:?} {:?}", bin.re, bin.im).unwrap();
+        }
+    }
+
+    // write to a temporary file first, so that test_golden_spectra never reads a partially written file
+    let temporary_path = format!("{}.tmp", GOLDEN_PATH);
+    fs::write(&temporary_path, contents).unwrap();
+    fs::rename(&temporary_path, GOLDEN_PATH).unwrap();
+}
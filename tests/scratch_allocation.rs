@@ -0,0 +1,74 @@
+//! `FFT::process_with_scratch` promises not to allocate, so that it can be called from a real-time thread. To check
+//! that the promise holds for the planner's FFTs, including the ones that need extra scratch space for a padded
+//! Rader's Algorithm somewhere inside them, this counts the allocations made by the global allocator while processing.
+//! The types that own their scratch buffers, and promise not to allocate either, are checked the same way.
+
+
+extern crate rustfft;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::{FFTplanner, FFT2D};
+use rustfft::convolution::FFTfilter;
+
+struct CountingAllocator;
+
+// counted per thread, so that allocations made by the test harness on other threads don't count
+thread_local!(static ALLOCATIONS: Cell<usize> = const { Cell::new(0) });
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_process_with_scratch_does_not_allocate() {
+    // 1019 and 2038 contain a padded Rader's Algorithm, 1201 an unpadded one, and the rest are the common cases
+    for &len in &[0, 1, 7, 32, 1000, 1019, 1201, 2038, 3057, 4096, 65536 * 3] {
+        for &inverse in &[false, true] {
+            let fft = FFTplanner::<f32>::new(inverse).plan_fft(len);
+
+            let signal = vec![Complex::new(1.0, 0.5); len];
+            let mut spectrum = vec![Zero::zero(); len];
+            let mut scratch = vec![Zero::zero(); fft.required_scratch_len()];
+
+            let before = ALLOCATIONS.with(Cell::get);
+            fft.process_with_scratch(&signal, &mut spectrum, &mut scratch);
+            let after = ALLOCATIONS.with(Cell::get);
+
+            assert_eq!(before, after, "{} allocated while processing", fft.describe());
+        }
+    }
+}
+
+#[test]
+fn test_owned_scratch_does_not_allocate() {
+    // 1019 and 2038 are planned with a padded Rader's Algorithm
+    let mut filter = FFTfilter::new(vec![Complex::new(0.5f32, 0.0); 2038]);
+    let signal = vec![Complex::new(1.0, 0.5); 2038];
+    let mut output = vec![Zero::zero(); 2038];
+
+    let before = ALLOCATIONS.with(Cell::get);
+    filter.process(&signal, &mut output);
+    let after = ALLOCATIONS.with(Cell::get);
+    assert_eq!(before, after, "FFTfilter allocated while processing");
+
+    let mut fft = FFT2D::<f32>::new(1019, 3, false);
+    let mut image = vec![Complex::new(1.0, 0.5); 1019 * 3];
+
+    let before = ALLOCATIONS.with(Cell::get);
+    fft.process(&mut image);
+    let after = ALLOCATIONS.with(Cell::get);
+    assert_eq!(before, after, "FFT2D allocated while processing");
+}